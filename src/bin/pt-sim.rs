@@ -0,0 +1,89 @@
+// src/bin/pt-sim.rs
+//
+// Local power.trade style FIX acceptor for testing the client without the exchange test environment
+//
+//   cargo run --bin pt-sim -- --port 2021
+//   cargo run --bin pt-sim -- --port 2021 --tls-cert pt-sim-cert.pem --tls-key pt-sim-key.pem
+//
+// faults are injected by typing commands on the console:
+//   reject-order [text] | reject [text] | gap <count> | disconnect | quit
+//
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use client_rust_fix::sim::{SimCommand, SimConfig, SimTls, Simulator};
+use log::LevelFilter;
+use simplelog::{CombinedLogger, Config, WriteLogger};
+use std::{fs, fs::File, io::{stdin, BufRead}, process::ExitCode};
+
+fn main() -> ExitCode {
+    let matches: ArgMatches = Command::new("Power.Trade FIX Simulator")
+        .about("Local FIX 4.4 acceptor behaving like power.trade order entry")
+        .arg(Arg::new("host").long("host").action(ArgAction::Set).default_value("127.0.0.1").help("Address to listen on"))
+        .arg(
+            Arg::new("port")
+                .long("port")
+                .short('p')
+                .action(ArgAction::Set)
+                .default_value("2021")
+                .value_parser(value_parser!(u16))
+                .help("Port to listen on"),
+        )
+        .arg(Arg::new("tls-cert").long("tls-cert").action(ArgAction::Set).requires("tls-key").help("PEM certificate, enables TLS"))
+        .arg(Arg::new("tls-key").long("tls-key").action(ArgAction::Set).requires("tls-cert").help("PEM PKCS#8 private key for --tls-cert"))
+        .arg(Arg::new("verify-key").long("verify-key").action(ArgAction::Set).help("PEM public key used to verify Logon JWT signatures"))
+        .get_matches();
+
+    CombinedLogger::init(vec![WriteLogger::new(LevelFilter::Info, Config::default(), File::create("pt-sim.log").unwrap())]).unwrap();
+
+    let host: &String = matches.get_one::<String>("host").expect("host has a default");
+    let port: u16 = *matches.get_one::<u16>("port").expect("port has a default");
+    let mut config: SimConfig = SimConfig::new(&format!("{host}:{port}"));
+
+    if let (Some(cert), Some(key)) = (matches.get_one::<String>("tls-cert"), matches.get_one::<String>("tls-key")) {
+        match (fs::read(cert), fs::read(key)) {
+            (Ok(cert_pem), Ok(key_pem)) => config.tls = Some(SimTls { cert_pem, key_pem }),
+            (Err(error), _) | (_, Err(error)) => {
+                println!("Error reading TLS certificate/key: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if let Some(path) = matches.get_one::<String>("verify-key") {
+        match fs::read(path) {
+            Ok(pem) => config.verify_key_pem = Some(pem),
+            Err(error) => {
+                println!("Error reading JWT verify key '{path}': {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let simulator: Simulator = match Simulator::start(config) {
+        Ok(simulator) => simulator,
+        Err(error) => {
+            println!("Error starting simulator: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("pt-sim listening on {} - commands: reject-order [text] | reject [text] | gap <count> | disconnect | liquidity <symbol> <buy|sell> <qty>@<price> | quit", simulator.local_addr());
+
+    for line in stdin().lock().lines() {
+        let line: String = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        match line.trim() {
+            "" => continue,
+            "quit" | "exit" => break,
+            command => match command.parse::<SimCommand>() {
+                Ok(command) => {
+                    println!("Applying '{command}'");
+                    simulator.command(command);
+                }
+                Err(error) => println!("{error}"),
+            },
+        }
+    }
+    simulator.stop();
+    ExitCode::SUCCESS
+}
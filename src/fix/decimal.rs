@@ -0,0 +1,373 @@
+// src/fix/decimal.rs
+//
+// Fixed-point decimal for prices and quantities on the wire
+//  - value is mantissa x 10^-scale, so '0.3' stays 0.3 and is never written as 0.30000000000000004 or 3e-1
+//  - parsed from FIX values as sent, e.g. LastPx [31], AvgPx [6], CumQty [14], without rounding
+//  - `rescale` rounds half away from zero, e.g. to the tick precision of an instrument
+//  - values compare equal whatever their scale, '2' == '2.00'
+//  - at most `MAX_INTEGER_DIGITS` digits before the decimal point are accepted, so any value aligned to `MAX_SCALE`
+//    and the sum of two such values fit the i128 mantissa, arithmetic past that is checked and never wraps
+//
+
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+/// digits after the decimal point kept at most, values with more are rounded
+pub const MAX_SCALE: u32 = 18;
+
+/// digits before the decimal point accepted at most
+pub const MAX_INTEGER_DIGITS: u32 = 19;
+
+/// `DecimalError`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecimalError {
+    /// value is not digits with an optional sign and decimal point
+    Invalid(String),
+    /// value has more than `MAX_SCALE` digits after the decimal point or `MAX_INTEGER_DIGITS` before it
+    OutOfRange(String),
+}
+
+impl fmt::Display for DecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecimalError::Invalid(value) => write!(f, "'{value}' is not a decimal number"),
+            DecimalError::OutOfRange(value) => write!(f, "'{value}' has too many digits"),
+        }
+    }
+}
+
+impl std::error::Error for DecimalError {}
+
+/// `Decimal`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+fn pow10(scale: u32) -> i128 {
+    10_i128.pow(scale)
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal { mantissa: 0, scale: 0 };
+
+    /// `new`
+    ///
+    /// `mantissa` x 10^-`scale`, e.g. `Decimal::new(38850, 2)` is 388.50
+    /// n.b. a scale above `MAX_SCALE` is rounded to `MAX_SCALE` digits after the decimal point, as `rescale` does
+    pub const fn new(mantissa: i64, scale: u32) -> Self {
+        let mantissa: i128 = mantissa as i128;
+        if scale <= MAX_SCALE {
+            return Decimal { mantissa, scale };
+        }
+        // any i64 is below 10^19, so it rounds to zero when more digits than that are dropped
+        if scale - MAX_SCALE > 19 {
+            return Decimal { mantissa: 0, scale: MAX_SCALE };
+        }
+        let divisor: i128 = 10_i128.pow(scale - MAX_SCALE);
+        let (quotient, remainder) = (mantissa / divisor, mantissa % divisor);
+        let rounding: i128 = if remainder.abs() >= divisor - remainder.abs() { mantissa.signum() } else { 0 };
+        Decimal { mantissa: quotient + rounding, scale: MAX_SCALE }
+    }
+
+    /// `from_f64`
+    ///
+    /// `value` rounded to `scale` digits, None when not finite or too large
+    pub fn from_f64(value: f64, scale: u32) -> Option<Decimal> {
+        let scale: u32 = scale.min(MAX_SCALE);
+        let scaled: f64 = (value * 10_f64.powi(scale as i32)).round();
+        (scaled.is_finite() && value.abs() < 10_f64.powi(MAX_INTEGER_DIGITS as i32)).then_some(Decimal { mantissa: scaled as i128, scale })
+    }
+
+    /// nearest f64, for PnL and risk arithmetic
+    pub fn to_f64(self) -> f64 {
+        self.mantissa as f64 / 10_f64.powi(self.scale as i32)
+    }
+
+    /// digits after the decimal point
+    pub fn scale(self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_sign_negative(self) -> bool {
+        self.mantissa < 0
+    }
+
+    pub fn abs(self) -> Decimal {
+        Decimal { mantissa: self.mantissa.abs(), scale: self.scale }
+    }
+
+    /// `rescale`
+    ///
+    /// value with `scale` digits after the decimal point, rounded half away from zero when digits are dropped
+    ///
+    /// # Panics
+    ///
+    /// function will panic if the value has too many digits for `scale`, see `checked_rescale`
+    pub fn rescale(self, scale: u32) -> Decimal {
+        self.checked_rescale(scale).expect("Decimal overflow in rescale")
+    }
+
+    /// `checked_rescale`
+    ///
+    /// as `rescale`, None when the mantissa would overflow
+    pub fn checked_rescale(self, scale: u32) -> Option<Decimal> {
+        let scale: u32 = scale.min(MAX_SCALE);
+        match scale.cmp(&self.scale) {
+            Ordering::Equal => Some(self),
+            Ordering::Greater => self.mantissa.checked_mul(pow10(scale - self.scale)).map(|mantissa| Decimal { mantissa, scale }),
+            Ordering::Less => {
+                let divisor: i128 = pow10(self.scale - scale);
+                let (quotient, remainder) = (self.mantissa / divisor, self.mantissa % divisor);
+                let rounding: i128 = if remainder.abs() >= divisor - remainder.abs() { self.mantissa.signum() } else { 0 };
+                Some(Decimal { mantissa: quotient + rounding, scale })
+            }
+        }
+    }
+
+    /// `checked_add`
+    ///
+    /// sum, None when the mantissa would overflow
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        let (left, right, scale) = self.aligned(other)?;
+        left.checked_add(right).map(|mantissa| Decimal { mantissa, scale })
+    }
+
+    /// `checked_mul`
+    ///
+    /// product rounded to `MAX_SCALE` digits after the decimal point, None when the mantissa would overflow
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let (left, right) = (self.normalize(), other.normalize());
+        let product = Decimal { mantissa: left.mantissa.checked_mul(right.mantissa)?, scale: left.scale + right.scale }.normalize();
+        product.checked_rescale(product.scale.min(MAX_SCALE))
+    }
+
+    /// `checked_rem`
+    ///
+    /// exact remainder of dividing by `other`, with the sign of `self`, None when dividing by zero
+    /// or the mantissa would overflow
+    pub fn checked_rem(self, other: Decimal) -> Option<Decimal> {
+        let (left, right, scale) = self.aligned(other)?;
+        left.checked_rem(right).map(|mantissa| Decimal { mantissa, scale })
+    }
+
+    /// `normalize`
+    ///
+    /// same value without trailing zeros after the decimal point, '2.500' -> '2.5'
+    pub fn normalize(self) -> Decimal {
+        let mut decimal: Decimal = self;
+        while decimal.scale > 0 && decimal.mantissa % 10 == 0 {
+            decimal = Decimal { mantissa: decimal.mantissa / 10, scale: decimal.scale - 1 };
+        }
+        decimal
+    }
+
+    /// `checked_div`
+    ///
+    /// quotient rounded half away from zero to `MAX_SCALE` digits after the decimal point, None when dividing by zero
+    /// or the mantissa would overflow
+    pub fn checked_div(self, other: Decimal) -> Option<Decimal> {
+        let (left, right, _) = self.aligned(other)?;
+        if right == 0 {
+            return None;
+        }
+        let (mut mantissa, mut remainder) = (left / right, left % right);
+        for _ in 0..MAX_SCALE {
+            remainder = remainder.checked_mul(10)?;
+            mantissa = mantissa.checked_mul(10)?.checked_add(remainder / right)?;
+            remainder %= right;
+        }
+        let rounding: i128 = if remainder.abs() >= right.abs() - remainder.abs() { left.signum() * right.signum() } else { 0 };
+        Some(Decimal { mantissa: mantissa.checked_add(rounding)?, scale: MAX_SCALE }.normalize())
+    }
+
+    // mantissas of both values at their common scale
+    fn aligned(self, other: Decimal) -> Option<(i128, i128, u32)> {
+        let scale: u32 = self.scale.max(other.scale);
+        Some((self.checked_rescale(scale)?.mantissa, other.checked_rescale(scale)?.mantissa, scale))
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = DecimalError;
+
+    /// parses '388', '-0.25' or '.5', exponents are not accepted
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DecimalError::Invalid(s.to_string());
+        let (negative, digits) = match s.trim().as_bytes().first() {
+            Some(b'-') => (true, &s.trim()[1..]),
+            Some(b'+') => (false, &s.trim()[1..]),
+            _ => (false, s.trim()),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if integer.is_empty() && fraction.is_empty() || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        if fraction.len() > MAX_SCALE as usize || integer.trim_start_matches('0').len() > MAX_INTEGER_DIGITS as usize {
+            return Err(DecimalError::OutOfRange(s.to_string()));
+        }
+        let mantissa: i128 = format!("{integer}{fraction}").parse::<i128>().unwrap_or(0);
+        Ok(Decimal { mantissa: if negative { -mantissa } else { mantissa }, scale: fraction.len() as u32 })
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits: String = format!("{:0>width$}", self.mantissa.unsigned_abs(), width = self.scale as usize + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - self.scale as usize);
+        let sign: &str = if self.mantissa < 0 { "-" } else { "" };
+        if fraction.is_empty() { write!(f, "{sign}{integer}") } else { write!(f, "{sign}{integer}.{fraction}") }
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Decimal::new(value, 0)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    /// compares integer parts, then fractions at `MAX_SCALE`, so no value is too large to compare
+    fn cmp(&self, other: &Self) -> Ordering {
+        let parts = |decimal: &Decimal| {
+            let divisor: i128 = pow10(decimal.scale);
+            (decimal.mantissa / divisor, decimal.mantissa % divisor * pow10(MAX_SCALE - decimal.scale.min(MAX_SCALE)))
+        };
+        parts(self).cmp(&parts(other))
+    }
+}
+
+impl Add for Decimal {
+    type Output = Decimal;
+
+    /// n.b. panics rather than wraps on overflow, see `checked_add`
+    fn add(self, other: Decimal) -> Decimal {
+        self.checked_add(other).expect("Decimal overflow in add")
+    }
+}
+
+impl Sub for Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: Decimal) -> Decimal {
+        self + -other
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal { mantissa: -self.mantissa, scale: self.scale }
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Decimal;
+
+    /// n.b. product is rounded to `MAX_SCALE` digits after the decimal point, panics rather than wraps on overflow,
+    /// see `checked_mul`
+    fn mul(self, other: Decimal) -> Decimal {
+        self.checked_mul(other).expect("Decimal overflow in mul")
+    }
+}
+
+impl Div for Decimal {
+    type Output = Decimal;
+
+    /// n.b. quotient is rounded to `MAX_SCALE` digits after the decimal point, panics on division by zero or overflow,
+    /// see `checked_div`
+    fn div(self, other: Decimal) -> Decimal {
+        self.checked_div(other).expect("Decimal division by zero or overflow")
+    }
+}
+
+impl AddAssign for Decimal {
+    fn add_assign(&mut self, other: Decimal) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Decimal {
+    fn sub_assign(&mut self, other: Decimal) {
+        *self = *self - other;
+    }
+}
+
+#[cfg(test)]
+mod decimal_tests {
+
+    use super::{Decimal, DecimalError};
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_format_round_and_compare() {
+        for value in ["388", "388.50", "-0.25", "0.000001", "60000.12345678"] {
+            assert_eq!(dec(value).to_string(), value);
+        }
+        assert_eq!(dec(".5").to_string(), "0.5");
+        assert_eq!(dec("0.1") + dec("0.2"), dec("0.3"));
+        assert_eq!((dec("0.1") + dec("0.2")).to_string(), "0.3");
+        assert_eq!(dec("2"), dec("2.00"));
+        assert!(dec("-1.5") < dec("0.01"));
+        assert_eq!((dec("2.5") * dec("388.12")).to_string(), "970.3");
+        assert_eq!((dec("777") / dec("2")).to_string(), "388.5");
+        assert_eq!((dec("-2") / dec("3")).to_string(), "-0.666666666666666667");
+        assert_eq!(dec("1").checked_div(Decimal::ZERO), None);
+        assert_eq!((dec("388.13").checked_rem(dec("0.05")), dec("-2.5").checked_rem(dec("1"))), (Some(dec("0.03")), Some(dec("-0.5"))));
+        assert_eq!(dec("1").checked_rem(Decimal::ZERO), None);
+        assert_eq!(dec("388").rescale(2).to_string(), "388.00");
+        assert_eq!(dec("0.125").rescale(2).to_string(), "0.13");
+        assert_eq!(dec("-0.125").rescale(2).to_string(), "-0.13");
+        assert_eq!(Decimal::from_f64(0.1 + 0.2, 2).map(|d| d.to_string()), Some("0.30".to_string()));
+        assert_eq!(Decimal::new(38850, 2).to_f64(), 388.5);
+        assert_eq!((Decimal::new(15, 19), Decimal::new(-15, 19)), (dec("0.000000000000000002"), dec("-0.000000000000000002")));
+        assert_eq!((Decimal::new(i64::MAX, 40), Decimal::new(1, u32::MAX)), (Decimal::ZERO, Decimal::ZERO));
+
+        assert!(matches!("3e-1".parse::<Decimal>(), Err(DecimalError::Invalid(_))));
+        assert!(matches!("1.2.3".parse::<Decimal>(), Err(DecimalError::Invalid(_))));
+        assert!(matches!("-".parse::<Decimal>(), Err(DecimalError::Invalid(_))));
+        assert!(matches!("0.0000000000000000001".parse::<Decimal>(), Err(DecimalError::OutOfRange(_))));
+    }
+
+    #[test]
+    fn test_large_values_do_not_overflow() {
+        assert!(matches!("1000000000000000000000000000000".parse::<Decimal>(), Err(DecimalError::OutOfRange(_))));
+        assert_eq!(dec("0009999999999999999999").to_string(), "9999999999999999999");
+
+        let (largest, smallest) = (dec("9999999999999999999.999999999999999999"), dec("0.000000000000000001"));
+        assert!(largest > smallest && -largest < -smallest && -largest < smallest);
+        assert_eq!((largest + largest).to_string(), "19999999999999999999.999999999999999998");
+        assert_eq!(largest.checked_add(largest).and_then(|sum| sum.checked_add(sum)).map(|sum| sum > largest), Some(true));
+        assert_eq!(largest.checked_mul(largest), None);
+        assert_eq!(dec("1000000000").checked_mul(dec("1000000000")), Some(dec("1000000000000000000")));
+        let product = Decimal::new(i64::MAX, 0) * Decimal::new(i64::MAX, 0);
+        assert!(product > smallest && product > largest && -product < smallest);
+    }
+}
@@ -0,0 +1,289 @@
+// src/fix/mod.rs
+//
+// Wire level helpers for FIX 4.4 messages exchanged with power.trade
+//  - parse raw tag=value text into a `RawMessage`
+//  - re-encode a `RawMessage` with fresh BodyLength [9] and CheckSum [10]
+//  - split a byte stream into complete messages using BodyLength [9]
+//  - prices and quantities as fixed-point `Decimal`s, see decimal.rs
+//
+
+mod decimal;
+
+pub use decimal::{Decimal, DecimalError, MAX_SCALE};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::fmt;
+
+pub const SOH: char = '\x01';
+pub const BEGIN_STRING: &str = "FIX.4.4";
+
+// standard header tags which must precede body fields
+const HEADER_TAGS: [u32; 9] = [8, 9, 35, 34, 43, 49, 52, 56, 97];
+
+/// `FixError`
+///
+/// Errors raised while parsing or encoding FIX messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixError {
+    /// message text could not be split into tag=value pairs
+    Malformed(String),
+    /// a required tag was not present in the message
+    MissingTag(u32),
+}
+
+impl fmt::Display for FixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixError::Malformed(text) => write!(f, "malformed FIX message: {text}"),
+            FixError::MissingTag(tag) => write!(f, "missing tag {tag}"),
+        }
+    }
+}
+
+impl std::error::Error for FixError {}
+
+/// `RawMessage`
+///
+/// FIX message held as an ordered list of tag/value pairs
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RawMessage {
+    fields: Vec<(u32, String)>,
+}
+
+impl RawMessage {
+    /// `new`
+    ///
+    /// creates an empty message with BeginString [8] and MsgType [35] set
+    pub fn new(msg_type: &str) -> Self {
+        RawMessage { fields: vec![(8, BEGIN_STRING.to_string()), (35, msg_type.to_string())] }
+    }
+
+    /// `parse`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - text contains a field without '=' separator
+    /// - a tag is not a positive number
+    /// - MsgType [35] is missing
+    pub fn parse(text: &str) -> Result<Self, FixError> {
+        let mut fields: Vec<(u32, String)> = Vec::new();
+        for field in text.split(SOH).filter(|f| !f.is_empty()) {
+            let (tag, value) = field.split_once('=').ok_or_else(|| FixError::Malformed(field.to_string()))?;
+            let tag: u32 = tag.parse().map_err(|_| FixError::Malformed(field.to_string()))?;
+            fields.push((tag, value.to_string()));
+        }
+        let msg = RawMessage { fields };
+        if msg.get(35).is_none() {
+            return Err(FixError::MissingTag(35));
+        }
+        Ok(msg)
+    }
+
+    pub fn msg_type(&self) -> &str {
+        self.get(35).unwrap_or("")
+    }
+
+    /// `get`
+    ///
+    /// returns first value found for tag
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields.iter().find(|(t, _)| *t == tag).map(|(_, v)| v.as_str())
+    }
+
+    /// `get_all`
+    ///
+    /// returns every value found for tag, in message order (used for repeating groups)
+    pub fn get_all(&self, tag: u32) -> Vec<&str> {
+        self.fields.iter().filter(|(t, _)| *t == tag).map(|(_, v)| v.as_str()).collect()
+    }
+
+    pub fn fields(&self) -> &[(u32, String)] {
+        &self.fields
+    }
+
+    /// `set`
+    ///
+    /// replaces first value found for tag or appends tag to the end of the message
+    pub fn set(&mut self, tag: u32, value: impl ToString) -> &mut Self {
+        match self.fields.iter_mut().find(|(t, _)| *t == tag) {
+            Some(field) => field.1 = value.to_string(),
+            None => self.fields.push((tag, value.to_string())),
+        }
+        self
+    }
+
+    /// `set_header`
+    ///
+    /// replaces header tag or inserts it after the header fields already present
+    pub fn set_header(&mut self, tag: u32, value: impl ToString) -> &mut Self {
+        if let Some(field) = self.fields.iter_mut().find(|(t, _)| *t == tag) {
+            field.1 = value.to_string();
+            return self;
+        }
+        let position: usize = self.fields.iter().position(|(t, _)| !HEADER_TAGS.contains(t)).unwrap_or(self.fields.len());
+        self.fields.insert(position, (tag, value.to_string()));
+        self
+    }
+
+    /// `push`
+    ///
+    /// appends tag even if already present (used for repeating groups)
+    pub fn push(&mut self, tag: u32, value: impl ToString) -> &mut Self {
+        self.fields.push((tag, value.to_string()));
+        self
+    }
+
+    /// `to_fix_string`
+    ///
+    /// encodes message with BodyLength [9] and CheckSum [10] computed from the current fields
+    pub fn to_fix_string(&self) -> String {
+        let begin_string: &str = self.get(8).unwrap_or(BEGIN_STRING);
+        let body: String = self.fields
+            .iter()
+            .filter(|(tag, _)| !matches!(tag, 8..=10))
+            .map(|(tag, value)| format!("{tag}={value}{SOH}"))
+            .collect();
+        let head: String = format!("8={begin_string}{SOH}9={}{SOH}{body}", body.len());
+        let checksum: u32 = head.bytes().map(u32::from).sum::<u32>() % 256;
+        format!("{head}10={checksum:03}{SOH}")
+    }
+
+    /// `to_display`
+    ///
+    /// message text with SOH replaced by '|' for logging
+    pub fn to_display(&self) -> String {
+        self.to_fix_string().replace(SOH, "|")
+    }
+}
+
+/// `FrameReader`
+///
+/// accumulates bytes read from a stream and returns complete FIX messages using BodyLength [9]
+#[derive(Debug, Default)]
+pub struct FrameReader {
+    buffer: Vec<u8>,
+}
+
+impl FrameReader {
+    pub fn new() -> Self {
+        FrameReader::default()
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// `next_frame`
+    ///
+    /// returns next complete message text or None if more bytes are needed
+    /// - bytes before a BeginString [8] are discarded
+    /// - a frame without a valid BodyLength [9] is dropped
+    pub fn next_frame(&mut self) -> Option<String> {
+        loop {
+            let start = find(&self.buffer, b"8=FIX")?;
+            self.buffer.drain(..start);
+
+            let begin_end = find(&self.buffer, b"\x01")?;
+            let rest = &self.buffer[begin_end + 1..];
+            if rest.len() < 2 {
+                return None;
+            }
+            if !rest.starts_with(b"9=") {
+                // no BodyLength after BeginString, skip this frame
+                self.buffer.drain(..1);
+                continue;
+            }
+            let length_end = begin_end + 1 + find(rest, b"\x01")?;
+            let body_length: usize = match std::str::from_utf8(&self.buffer[begin_end + 3..length_end]).ok().and_then(|s| s.parse().ok()) {
+                Some(body_length) => body_length,
+                None => {
+                    self.buffer.drain(..1);
+                    continue;
+                }
+            };
+
+            // CheckSum [10] is always 7 bytes -> "10=nnn<SOH>"
+            let frame_end = length_end + 1 + body_length + 7;
+            if self.buffer.len() < frame_end {
+                return None;
+            }
+            let frame: Vec<u8> = self.buffer.drain(..frame_end).collect();
+            return Some(String::from_utf8_lossy(&frame).to_string());
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// `utc_timestamp`
+///
+/// current time formatted for SendingTime [52] / TransactTime [60]
+pub fn utc_timestamp() -> String {
+    Utc::now().format("%Y%m%d-%H:%M:%S%.9f").to_string()
+}
+
+/// `parse_utc_timestamp`
+///
+/// UTCTimestamp value, e.g. ExpireTime [126], with or without fractional seconds
+pub fn parse_utc_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y%m%d-%H:%M:%S%.f").ok().map(|time| time.and_utc())
+}
+
+#[cfg(test)]
+mod fix_wire_tests {
+
+    use super::{FrameReader, RawMessage};
+
+    #[test]
+    fn test_encode_sets_body_length_and_checksum() {
+        let mut msg = RawMessage::new("0");
+        msg.set(34, 2).set(49, "CLIENT").set(56, "PT-OE");
+        let text = msg.to_fix_string();
+        assert!(text.starts_with("8=FIX.4.4\x019=29\x0135=0\x01"));
+        let parsed = RawMessage::parse(&text).unwrap();
+        assert_eq!(parsed.get(10).unwrap().len(), 3);
+        assert_eq!(parsed.get(34), Some("2"));
+    }
+
+    #[test]
+    fn test_parse_rejects_missing_msg_type() {
+        assert!(RawMessage::parse("8=FIX.4.4\x0134=1\x01").is_err());
+    }
+
+    #[test]
+    fn test_set_replaces_existing_tag() {
+        let mut msg = RawMessage::new("D");
+        msg.set(34, 5).set(34, 6);
+        assert_eq!(msg.get_all(34), vec!["6"]);
+    }
+
+    #[test]
+    fn test_set_header_keeps_header_before_body() {
+        let mut msg = RawMessage::new("8");
+        msg.set(11, "ORDER-1").set(39, 0);
+        msg.set_header(34, 7).set_header(49, "PT-OE");
+        let tags: Vec<u32> = msg.fields().iter().map(|(tag, _)| *tag).collect();
+        assert_eq!(tags, vec![8, 35, 34, 49, 11, 39]);
+    }
+
+    #[test]
+    fn test_frame_reader_splits_partial_and_joined_messages() {
+        let first = RawMessage::new("0").to_fix_string();
+        let mut second_msg = RawMessage::new("8");
+        second_msg.set(39, 0);
+        let second = second_msg.to_fix_string();
+        let joined = format!("{first}{second}");
+        let (head, tail) = joined.as_bytes().split_at(first.len() + 5);
+
+        let mut reader = FrameReader::new();
+        reader.push(head);
+        assert_eq!(reader.next_frame(), Some(first));
+        assert_eq!(reader.next_frame(), None);
+        reader.push(tail);
+        assert_eq!(reader.next_frame(), Some(second));
+        assert_eq!(reader.next_frame(), None);
+    }
+}
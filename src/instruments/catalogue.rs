@@ -0,0 +1,275 @@
+// src/instruments/catalogue.rs
+//
+// Typed instrument catalogue built from SecurityList (35=y) NoRelatedSym [146] entries
+//  - SecurityType [167] 'SPOT', 'FUT', 'PERP' or 'OPT'
+//  - options and futures carry MaturityDate [541], options StrikePrice [202] and PutOrCall [201]
+//  - `InstrumentQuery` selects instruments by underlying, type, put/call and expiry, e.g. SOL options expiring this week
+//
+
+use super::Instrument;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// `SecurityType`
+///
+/// SecurityType [167] of an instrument
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecurityType {
+    Spot,
+    Future,
+    Perpetual,
+    Option,
+}
+
+impl SecurityType {
+    pub fn from_fix(value: &str) -> Option<SecurityType> {
+        match value {
+            "SPOT" => Some(SecurityType::Spot),
+            "FUT" => Some(SecurityType::Future),
+            "PERP" => Some(SecurityType::Perpetual),
+            "OPT" => Some(SecurityType::Option),
+            _ => None,
+        }
+    }
+
+    pub fn as_fix(self) -> &'static str {
+        match self {
+            SecurityType::Spot => "SPOT",
+            SecurityType::Future => "FUT",
+            SecurityType::Perpetual => "PERP",
+            SecurityType::Option => "OPT",
+        }
+    }
+}
+
+impl FromStr for SecurityType {
+    type Err = String;
+
+    /// parses FIX values ('OPT') or names ('option', 'options')
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value: String = s.trim().to_uppercase();
+        SecurityType::from_fix(&value).ok_or(()).or_else(|()| match value.trim_end_matches('S') {
+            "SPOT" => Ok(SecurityType::Spot),
+            "FUTURE" => Ok(SecurityType::Future),
+            "PERPETUAL" => Ok(SecurityType::Perpetual),
+            "OPTION" => Ok(SecurityType::Option),
+            _ => Err(format!("security type '{s}' is not valid, expected spot, future, perpetual or option")),
+        })
+    }
+}
+
+impl fmt::Display for SecurityType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_fix())
+    }
+}
+
+/// `PutOrCall`
+///
+/// PutOrCall [201] of an option, '0' = Put and '1' = Call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PutOrCall {
+    Put,
+    Call,
+}
+
+impl PutOrCall {
+    pub fn from_fix(value: &str) -> Option<PutOrCall> {
+        match value {
+            "0" => Some(PutOrCall::Put),
+            "1" => Some(PutOrCall::Call),
+            _ => None,
+        }
+    }
+
+    pub fn as_fix(self) -> char {
+        match self {
+            PutOrCall::Put => '0',
+            PutOrCall::Call => '1',
+        }
+    }
+}
+
+impl FromStr for PutOrCall {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().trim_end_matches('s') {
+            "put" | "p" | "0" => Ok(PutOrCall::Put),
+            "call" | "c" | "1" => Ok(PutOrCall::Call),
+            _ => Err(format!("put/call '{s}' is not valid, expected put or call")),
+        }
+    }
+}
+
+impl fmt::Display for PutOrCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `parse_maturity_date`
+///
+/// MaturityDate [541] as 'YYYYMMDD', 'YYYY-MM-DD' is accepted too (cache files)
+pub fn parse_maturity_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y%m%d").or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d")).ok()
+}
+
+pub(super) fn serialize_expiry<S: Serializer>(expiry: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error> {
+    match expiry {
+        Some(expiry) => serializer.serialize_some(&expiry.format("%Y-%m-%d").to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub(super) fn deserialize_expiry<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveDate>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) => {
+            parse_maturity_date(&value).map(Some).ok_or_else(|| serde::de::Error::custom(format!("expiry '{value}' is not a valid date")))
+        }
+        None => Ok(None),
+    }
+}
+
+/// `ExpiryFilter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryFilter {
+    /// expiring today
+    Today,
+    /// expiring from today until Sunday of the current week
+    ThisWeek,
+    /// expiring from today until the end of the current month
+    ThisMonth,
+    /// expiring from today until the date (inclusive)
+    Until(NaiveDate),
+}
+
+impl ExpiryFilter {
+    /// `last_day`
+    ///
+    /// last expiry date selected by the filter when today is `today`
+    pub fn last_day(self, today: NaiveDate) -> NaiveDate {
+        match self {
+            ExpiryFilter::Today => today,
+            ExpiryFilter::ThisWeek => today + Duration::days(6 - i64::from(today.weekday().num_days_from_monday())),
+            ExpiryFilter::ThisMonth => {
+                let (year, month) = if today.month() == 12 { (today.year() + 1, 1) } else { (today.year(), today.month() + 1) };
+                NaiveDate::from_ymd_opt(year, month, 1).map_or(today, |first| first - Duration::days(1))
+            }
+            ExpiryFilter::Until(date) => date,
+        }
+    }
+}
+
+impl FromStr for ExpiryFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "today" => Ok(ExpiryFilter::Today),
+            "week" | "this-week" => Ok(ExpiryFilter::ThisWeek),
+            "month" | "this-month" => Ok(ExpiryFilter::ThisMonth),
+            value => parse_maturity_date(value)
+                .map(ExpiryFilter::Until)
+                .ok_or(format!("expiry '{s}' is not valid, expected today, week, month or a date")),
+        }
+    }
+}
+
+/// `InstrumentQuery`
+///
+/// every filter set must match, an empty query selects every instrument
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstrumentQuery {
+    /// base currency, e.g. 'SOL'
+    pub underlying: Option<String>,
+    pub security_type: Option<SecurityType>,
+    pub put_or_call: Option<PutOrCall>,
+    /// instruments without expiry never match
+    pub expiry: Option<ExpiryFilter>,
+}
+
+impl InstrumentQuery {
+    /// `matches`
+    ///
+    /// true when instrument is selected by the query, `today` is the first expiry date selected
+    pub fn matches(&self, instrument: &Instrument, today: NaiveDate) -> bool {
+        let underlying_matches: bool =
+            self.underlying.as_deref().map_or(true, |underlying| instrument.underlying().eq_ignore_ascii_case(underlying));
+        let type_matches: bool = self.security_type.map_or(true, |security_type| instrument.security_type == Some(security_type));
+        let put_or_call_matches: bool = self.put_or_call.map_or(true, |put_or_call| instrument.put_or_call == Some(put_or_call));
+        let expiry_matches: bool = match (self.expiry, instrument.expiry) {
+            (None, _) => true,
+            (Some(filter), Some(expiry)) => expiry >= today && expiry <= filter.last_day(today),
+            (Some(_), None) => false,
+        };
+        underlying_matches && type_matches && put_or_call_matches && expiry_matches
+    }
+}
+
+impl FromStr for InstrumentQuery {
+    type Err = String;
+
+    /// parses `all` or space / comma separated filters
+    /// `underlying=<base>`, `type=<spot|future|perpetual|option>`, `put-call=<put|call>`, `expires=<today|week|month|YYYY-MM-DD>`
+    /// e.g. `underlying=SOL type=option expires=week`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut query = InstrumentQuery::default();
+        for filter in
+            s.split(|c: char| c == ',' || c.is_whitespace()).filter(|filter| !filter.is_empty() && !filter.eq_ignore_ascii_case("all"))
+        {
+            match filter.split_once('=') {
+                Some(("underlying", underlying)) if !underlying.is_empty() => query.underlying = Some(underlying.to_uppercase()),
+                Some(("type", security_type)) => query.security_type = Some(security_type.parse()?),
+                Some(("put-call", put_or_call)) => query.put_or_call = Some(put_or_call.parse()?),
+                Some(("expires", expiry)) => query.expiry = Some(expiry.parse()?),
+                _ => return Err(format!("instrument filter '{filter}' is not valid, expected underlying=, type=, put-call= or expires=")),
+            }
+        }
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+mod catalogue_tests {
+
+    use super::{ExpiryFilter, InstrumentQuery, PutOrCall, SecurityType};
+    use crate::fix::Decimal;
+    use crate::instruments::Instrument;
+    use chrono::NaiveDate;
+
+    fn option(symbol: &str, expiry: NaiveDate, put_or_call: PutOrCall) -> Instrument {
+        let mut instrument = Instrument::new(symbol, Decimal::new(1, 2), Decimal::new(1, 2), Decimal::new(1, 2));
+        instrument.security_type = Some(SecurityType::Option);
+        instrument.underlying = Some("SOL".to_string());
+        instrument.expiry = Some(expiry);
+        instrument.strike = Some(150.0);
+        instrument.put_or_call = Some(put_or_call);
+        instrument
+    }
+
+    #[test]
+    fn test_query_sol_options_expiring_this_week() {
+        // Wednesday 2024-06-26, week ends Sunday 2024-06-30
+        let today = NaiveDate::from_ymd_opt(2024, 6, 26).unwrap();
+        let query: InstrumentQuery = "underlying=sol type=options expires=week".parse().unwrap();
+        assert_eq!(query.security_type, Some(SecurityType::Option));
+        assert_eq!(ExpiryFilter::ThisWeek.last_day(today), NaiveDate::from_ymd_opt(2024, 6, 30).unwrap());
+        assert_eq!(ExpiryFilter::ThisMonth.last_day(today), NaiveDate::from_ymd_opt(2024, 6, 30).unwrap());
+
+        let friday = option("SOL-20240628-150C", NaiveDate::from_ymd_opt(2024, 6, 28).unwrap(), PutOrCall::Call);
+        let next_week = option("SOL-20240705-150C", NaiveDate::from_ymd_opt(2024, 7, 5).unwrap(), PutOrCall::Call);
+        let expired = option("SOL-20240621-150P", NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(), PutOrCall::Put);
+        let spot = Instrument::new("SOL-USD", Decimal::new(1, 2), Decimal::new(1, 2), Decimal::new(1, 2));
+        assert!(query.matches(&friday, today));
+        assert!(!query.matches(&next_week, today) && !query.matches(&expired, today) && !query.matches(&spot, today));
+
+        let puts: InstrumentQuery = "put-call=put".parse().unwrap();
+        assert!(puts.matches(&expired, today) && !puts.matches(&friday, today));
+        assert!("all".parse::<InstrumentQuery>().unwrap().matches(&spot, today));
+        assert!("strike=150".parse::<InstrumentQuery>().is_err());
+    }
+}
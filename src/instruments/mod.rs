@@ -0,0 +1,584 @@
+// src/instruments/mod.rs
+//
+// Instrument reference data used to validate orders before they are sent
+//  - one `Instrument` per Symbol [55] with tick size, lot size, minimum quantity, contract multiplier and status
+//  - loaded from a local cache file (JSON array or CSV with a header row) and from SecurityList (35=y) responses,
+//    NoRelatedSym [146] entries with MinPriceIncrement [969], RoundLot [561], MinTradeVol [562],
+//    ContractMultiplier [231] and SecurityTradingStatus [326]
+//  - SecurityList entries also carry SecurityType [167], UnderlyingSymbol [311], MaturityDate [541],
+//    StrikePrice [202] and PutOrCall [201], queried with `InstrumentQuery`, see catalogue.rs
+//  - tick size, lot size and minimum quantity are `Decimal`, prices and quantities are checked against them exactly
+//    and formatted at their precision, `format_price`
+//  - `Symbol` parses and formats power.trade instrument names, see symbol.rs
+//  - the registry used by the message factory is set once at startup with `set_registry`,
+//    orders are not checked until a registry was set
+//
+
+mod catalogue;
+mod symbol;
+
+pub use catalogue::{parse_maturity_date, ExpiryFilter, InstrumentQuery, PutOrCall, SecurityType};
+pub use symbol::{Symbol, SymbolError};
+
+use crate::fix::{Decimal, RawMessage};
+use chrono::NaiveDate;
+use log::info;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::HashMap, fmt, fs, path::Path, sync::Mutex};
+
+/// `InstrumentStatus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstrumentStatus {
+    /// orders are accepted
+    Trading,
+    /// trading is suspended for now, orders are rejected
+    Halted,
+    /// instrument expired or delisted
+    Closed,
+}
+
+impl InstrumentStatus {
+    /// `from_fix`
+    ///
+    /// maps SecurityTradingStatus [326], '17' = ReadyToTrade, '2' = TradingHalt, '18' = NotAvailableForTrading
+    pub fn from_fix(value: &str) -> Option<InstrumentStatus> {
+        match value {
+            "17" | "3" => Some(InstrumentStatus::Trading),
+            "2" => Some(InstrumentStatus::Halted),
+            "18" | "19" => Some(InstrumentStatus::Closed),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for InstrumentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "trading" => Ok(InstrumentStatus::Trading),
+            "halted" => Ok(InstrumentStatus::Halted),
+            "closed" => Ok(InstrumentStatus::Closed),
+            _ => Err(format!("instrument status '{s}' is not valid, expected trading, halted or closed")),
+        }
+    }
+}
+
+impl fmt::Display for InstrumentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `Instrument`
+///
+/// reference data for one Symbol [55]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instrument {
+    pub symbol: String,
+    /// smallest price increment, Price [44] / StopPx [99] must be a multiple of it
+    #[serde(serialize_with = "serialize_step", deserialize_with = "deserialize_step")]
+    pub tick_size: Decimal,
+    /// OrderQty [38] must be a multiple of it
+    #[serde(serialize_with = "serialize_step", deserialize_with = "deserialize_step")]
+    pub lot_size: Decimal,
+    #[serde(serialize_with = "serialize_step", deserialize_with = "deserialize_step")]
+    pub min_qty: Decimal,
+    /// quantity of the underlying per contract, 1 for spot
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    pub status: InstrumentStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security_type: Option<SecurityType>,
+    /// base currency, see `underlying`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underlying: Option<String>,
+    /// futures and options only
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "catalogue::serialize_expiry",
+        deserialize_with = "catalogue::deserialize_expiry"
+    )]
+    pub expiry: Option<NaiveDate>,
+    /// options only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strike: Option<f64>,
+    /// options only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub put_or_call: Option<PutOrCall>,
+}
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+// tick size, lot size and minimum quantity are numbers in the cache file, read back through their shortest decimal form
+fn serialize_step<S: Serializer>(step: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(step.to_f64())
+}
+
+fn deserialize_step<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    let value: f64 = f64::deserialize(deserializer)?;
+    value.to_string().parse().map_err(|error| serde::de::Error::custom(format!("step {value} is not valid: {error}")))
+}
+
+impl Instrument {
+    pub fn new(symbol: &str, tick_size: Decimal, lot_size: Decimal, min_qty: Decimal) -> Self {
+        Instrument {
+            symbol: symbol.to_string(),
+            tick_size,
+            lot_size,
+            min_qty,
+            multiplier: 1.0,
+            status: InstrumentStatus::Trading,
+            security_type: None,
+            underlying: None,
+            expiry: None,
+            strike: None,
+            put_or_call: None,
+        }
+    }
+
+    /// `underlying`
+    ///
+    /// UnderlyingSymbol [311] when known, the symbol up to the first '-' otherwise, e.g. 'SOL' for 'SOL-USD'
+    pub fn underlying(&self) -> &str {
+        match &self.underlying {
+            Some(underlying) => underlying,
+            None => self.symbol.split('-').next().unwrap_or_default(),
+        }
+    }
+
+    pub fn is_trading(&self) -> bool {
+        self.status == InstrumentStatus::Trading
+    }
+
+    /// `round_price`
+    ///
+    /// price rounded half away from zero to the nearest tick
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        match price.checked_div(self.tick_size) {
+            Some(ticks) if self.tick_size > Decimal::ZERO => ticks.rescale(0) * self.tick_size,
+            _ => price,
+        }
+    }
+
+    /// `format_price`
+    ///
+    /// price at the tick size precision, e.g. '388.5' -> '388.50' for a 0.01 tick, as given when the tick size is not known
+    /// n.b. digits beyond the tick precision are rounded away, `validate_order` rejects an off-tick price before it gets here
+    pub fn format_price(&self, price: Decimal) -> String {
+        at_step_precision(price, self.tick_size).to_string()
+    }
+
+    /// `format_quantity`
+    ///
+    /// quantity at the lot size precision, see `format_price`
+    pub fn format_quantity(&self, quantity: Decimal) -> String {
+        at_step_precision(quantity, self.lot_size).to_string()
+    }
+
+    /// `validate_order`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - instrument is not trading
+    /// - quantity is below minimum quantity or not a multiple of lot size
+    /// - a price is not a multiple of tick size
+    pub fn validate_order(&self, prices: &[Decimal], quantity: Decimal) -> Result<(), InstrumentError> {
+        if !self.is_trading() {
+            return Err(InstrumentError::NotTrading { symbol: self.symbol.clone(), status: self.status });
+        }
+        if quantity < self.min_qty {
+            return Err(InstrumentError::BelowMinQty { symbol: self.symbol.clone(), quantity, min_qty: self.min_qty });
+        }
+        if !is_multiple(quantity, self.lot_size) {
+            return Err(InstrumentError::OffLot { symbol: self.symbol.clone(), quantity, lot_size: self.lot_size });
+        }
+        match prices.iter().find(|price| !is_multiple(**price, self.tick_size)) {
+            Some(price) => Err(InstrumentError::OffTick { symbol: self.symbol.clone(), price: *price, tick_size: self.tick_size }),
+            None => Ok(()),
+        }
+    }
+}
+
+// value is an exact multiple of step, any value is when step is not known (not positive)
+fn is_multiple(value: Decimal, step: Decimal) -> bool {
+    step <= Decimal::ZERO || value.checked_rem(step).map_or(false, Decimal::is_zero)
+}
+
+// value with as many digits after the decimal point as step, as given when step is not known (not positive)
+fn at_step_precision(value: Decimal, step: Decimal) -> Decimal {
+    if step > Decimal::ZERO { value.rescale(step.normalize().scale()) } else { value.normalize() }
+}
+
+/// `InstrumentError`
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstrumentError {
+    /// symbol is not in the registry
+    UnknownSymbol(String),
+    NotTrading {
+        symbol: String,
+        status: InstrumentStatus,
+    },
+    OffTick {
+        symbol: String,
+        price: Decimal,
+        tick_size: Decimal,
+    },
+    OffLot {
+        symbol: String,
+        quantity: Decimal,
+        lot_size: Decimal,
+    },
+    BelowMinQty {
+        symbol: String,
+        quantity: Decimal,
+        min_qty: Decimal,
+    },
+    /// cache file cannot be read, written or parsed
+    Cache(String),
+}
+
+impl fmt::Display for InstrumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstrumentError::UnknownSymbol(symbol) => write!(f, "{symbol} is not a traded instrument"),
+            InstrumentError::NotTrading { symbol, status } => write!(f, "{symbol} is not trading, status {status}"),
+            InstrumentError::OffTick { symbol, price, tick_size } => {
+                write!(f, "{symbol} price {price} is not a multiple of tick size {tick_size}")
+            }
+            InstrumentError::OffLot { symbol, quantity, lot_size } => {
+                write!(f, "{symbol} quantity {quantity} is not a multiple of lot size {lot_size}")
+            }
+            InstrumentError::BelowMinQty { symbol, quantity, min_qty } => {
+                write!(f, "{symbol} quantity {quantity} is below minimum {min_qty}")
+            }
+            InstrumentError::Cache(reason) => write!(f, "instrument cache error: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for InstrumentError {}
+
+/// `InstrumentRegistry`
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    instruments: HashMap<String, Instrument>,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        InstrumentRegistry::default()
+    }
+
+    /// `insert`
+    ///
+    /// adds instrument or replaces the one with the same symbol
+    pub fn insert(&mut self, instrument: Instrument) {
+        self.instruments.insert(instrument.symbol.clone(), instrument);
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Instrument> {
+        self.instruments.get(symbol)
+    }
+
+    /// `instruments`
+    ///
+    /// every instrument sorted by symbol
+    pub fn instruments(&self) -> Vec<&Instrument> {
+        let mut instruments: Vec<&Instrument> = self.instruments.values().collect();
+        instruments.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        instruments
+    }
+
+    pub fn len(&self) -> usize {
+        self.instruments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+
+    /// `query`
+    ///
+    /// instruments selected by the query sorted by symbol, `today` is the first expiry date selected
+    pub fn query(&self, query: &InstrumentQuery, today: NaiveDate) -> Vec<&Instrument> {
+        self.instruments().into_iter().filter(|instrument| query.matches(instrument, today)).collect()
+    }
+
+    /// `validate_order`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - symbol is not known
+    /// - order is not valid for the instrument, see `Instrument::validate_order`
+    pub fn validate_order(&self, symbol: &str, prices: &[Decimal], quantity: Decimal) -> Result<&Instrument, InstrumentError> {
+        let instrument: &Instrument = self.get(symbol).ok_or_else(|| InstrumentError::UnknownSymbol(symbol.to_string()))?;
+        instrument.validate_order(prices, quantity)?;
+        Ok(instrument)
+    }
+
+    /// `load`
+    ///
+    /// reads cache file, CSV when the file name ends with '.csv', JSON array of instruments otherwise
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - file cannot be read
+    /// - content is not valid JSON / CSV, or a CSV value cannot be parsed
+    pub fn load(path: &Path) -> Result<InstrumentRegistry, InstrumentError> {
+        let text: String = fs::read_to_string(path).map_err(|error| InstrumentError::Cache(format!("{}: {error}", path.display())))?;
+        let is_csv: bool = path.extension().map_or(false, |extension| extension.eq_ignore_ascii_case("csv"));
+        let registry: InstrumentRegistry =
+            if is_csv { InstrumentRegistry::from_csv(&text)? } else { InstrumentRegistry::from_json(&text)? };
+        info!("Loaded {} instruments from {}", registry.len(), path.display());
+        Ok(registry)
+    }
+
+    /// `save`
+    ///
+    /// writes every instrument to a JSON cache file
+    ///
+    /// # Errors
+    ///
+    /// function will return error if file cannot be written
+    pub fn save(&self, path: &Path) -> Result<(), InstrumentError> {
+        let text: String = serde_json::to_string_pretty(&self.instruments()).map_err(|error| InstrumentError::Cache(error.to_string()))?;
+        fs::write(path, text).map_err(|error| InstrumentError::Cache(format!("{}: {error}", path.display())))
+    }
+
+    /// `from_json`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if text is not a JSON array of instruments
+    pub fn from_json(text: &str) -> Result<InstrumentRegistry, InstrumentError> {
+        let instruments: Vec<Instrument> = serde_json::from_str(text).map_err(|error| InstrumentError::Cache(error.to_string()))?;
+        let mut registry = InstrumentRegistry::new();
+        instruments.into_iter().for_each(|instrument| registry.insert(instrument));
+        Ok(registry)
+    }
+
+    /// `from_csv`
+    ///
+    /// header row `symbol,tick_size,lot_size,min_qty,multiplier,status,security_type,underlying,expiry,strike,put_call`,
+    /// every column after min_qty may be left empty or omitted
+    ///
+    /// # Errors
+    ///
+    /// function will return error if a row has fewer than 4 columns or a value cannot be parsed
+    pub fn from_csv(text: &str) -> Result<InstrumentRegistry, InstrumentError> {
+        let mut registry = InstrumentRegistry::new();
+        for (line_number, line) in text.lines().enumerate().skip(1).filter(|(_, line)| !line.trim().is_empty()) {
+            let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+            let invalid = |column: &str| InstrumentError::Cache(format!("line {}: {column} is not valid in '{line}'", line_number + 1));
+            if columns.len() < 4 {
+                return Err(invalid("row"));
+            }
+            let number = |index: usize, column: &str, default: f64| match columns.get(index).filter(|value| !value.is_empty()) {
+                Some(value) => value.parse::<f64>().map_err(|_| invalid(column)),
+                None => Ok(default),
+            };
+            let step = |index: usize, column: &str| match columns.get(index).filter(|value| !value.is_empty()) {
+                Some(value) => value.parse::<Decimal>().map_err(|_| invalid(column)),
+                None => Ok(Decimal::ZERO),
+            };
+            let text = |index: usize| columns.get(index).copied().filter(|value| !value.is_empty());
+            let status: InstrumentStatus = match text(5) {
+                Some(value) => value.parse().map_err(|_| invalid("status"))?,
+                None => InstrumentStatus::Trading,
+            };
+            registry.insert(Instrument {
+                symbol: columns[0].to_string(),
+                tick_size: step(1, "tick_size")?,
+                lot_size: step(2, "lot_size")?,
+                min_qty: step(3, "min_qty")?,
+                multiplier: number(4, "multiplier", 1.0)?,
+                status,
+                security_type: text(6).map(|value| value.parse().map_err(|_| invalid("security_type"))).transpose()?,
+                underlying: text(7).map(str::to_string),
+                expiry: text(8).map(|value| parse_maturity_date(value).ok_or_else(|| invalid("expiry"))).transpose()?,
+                strike: text(9).map(|value| value.parse::<f64>().map_err(|_| invalid("strike"))).transpose()?,
+                put_or_call: text(10).map(|value| value.parse().map_err(|_| invalid("put_call"))).transpose()?,
+            });
+        }
+        Ok(registry)
+    }
+
+    /// `apply_security_list`
+    ///
+    /// adds or replaces every instrument of a SecurityList (35=y), returns the number of instruments applied
+    /// n.b. entries without MinPriceIncrement [969] keep the tick size already known for the symbol,
+    /// the same for every other field
+    pub fn apply_security_list(&mut self, msg: &RawMessage) -> usize {
+        if msg.msg_type() != "y" {
+            return 0;
+        }
+        let mut entries: Vec<Vec<(u32, &str)>> = Vec::new();
+        for (tag, value) in msg.fields().iter().skip_while(|(tag, _)| *tag != 146).skip(1) {
+            match (tag, entries.last_mut()) {
+                (55, _) => entries.push(vec![(55, value.as_str())]),
+                (10, _) => break,
+                (_, Some(entry)) => entry.push((*tag, value.as_str())),
+                (_, None) => {}
+            }
+        }
+        for entry in &entries {
+            let get = |tag: u32| entry.iter().find(|(t, _)| *t == tag).map(|(_, value)| *value);
+            let number = |tag: u32| get(tag).and_then(|value| value.parse::<f64>().ok());
+            let step = |tag: u32| get(tag).and_then(|value| value.parse::<Decimal>().ok());
+            let symbol: &str = get(55).unwrap_or_default();
+            let known: Option<&Instrument> = self.get(symbol);
+            let instrument = Instrument {
+                symbol: symbol.to_string(),
+                tick_size: step(969).or(known.map(|i| i.tick_size)).unwrap_or(Decimal::ZERO),
+                lot_size: step(561).or(known.map(|i| i.lot_size)).unwrap_or(Decimal::ZERO),
+                min_qty: step(562).or(known.map(|i| i.min_qty)).unwrap_or(Decimal::ZERO),
+                multiplier: number(231).or(known.map(|i| i.multiplier)).unwrap_or(1.0),
+                status: get(326).and_then(InstrumentStatus::from_fix).or(known.map(|i| i.status)).unwrap_or(InstrumentStatus::Trading),
+                security_type: get(167).and_then(SecurityType::from_fix).or(known.and_then(|i| i.security_type)),
+                underlying: get(311).map(str::to_string).or(known.and_then(|i| i.underlying.clone())),
+                expiry: get(541).and_then(parse_maturity_date).or(known.and_then(|i| i.expiry)),
+                strike: number(202).or(known.and_then(|i| i.strike)),
+                put_or_call: get(201).and_then(PutOrCall::from_fix).or(known.and_then(|i| i.put_or_call)),
+            };
+            self.insert(instrument);
+        }
+        entries.len()
+    }
+}
+
+static REGISTRY: Mutex<Option<InstrumentRegistry>> = Mutex::new(None);
+
+/// `set_registry`
+///
+/// replaces the registry used by `validate_order`
+pub fn set_registry(registry: InstrumentRegistry) {
+    *REGISTRY.lock().unwrap() = Some(registry);
+}
+
+/// `update_registry`
+///
+/// changes the registry used by `validate_order` in place, e.g. with a SecurityList (35=y), an empty one is set if none was
+pub fn update_registry<R>(update: impl FnOnce(&mut InstrumentRegistry) -> R) -> R {
+    update(REGISTRY.lock().unwrap().get_or_insert_with(InstrumentRegistry::new))
+}
+
+/// `validate_order`
+///
+/// checks order against the registry set with `set_registry`, every order passes when none was set
+///
+/// # Errors
+///
+/// function will return error if order is not valid, see `InstrumentRegistry::validate_order`
+pub fn validate_order(symbol: &str, prices: &[Decimal], quantity: Decimal) -> Result<(), InstrumentError> {
+    match REGISTRY.lock().unwrap().as_ref() {
+        Some(registry) => registry.validate_order(symbol, prices, quantity).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// `format_price`
+///
+/// Price [44] / StopPx [99] as written on the wire, at the tick size precision of `symbol` in the registry
+/// set with `set_registry`, as given when the symbol is not known
+pub fn format_price(symbol: &str, price: Decimal) -> String {
+    match REGISTRY.lock().unwrap().as_ref().and_then(|registry| registry.get(symbol)) {
+        Some(instrument) => instrument.format_price(price),
+        None => price.to_string(),
+    }
+}
+
+/// `format_quantity`
+///
+/// OrderQty [38] as written on the wire, at the lot size precision of `symbol`, see `format_price`
+pub fn format_quantity(symbol: &str, quantity: Decimal) -> String {
+    match REGISTRY.lock().unwrap().as_ref().and_then(|registry| registry.get(symbol)) {
+        Some(instrument) => instrument.format_quantity(quantity),
+        None => quantity.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod instruments_tests {
+
+    use super::{Instrument, InstrumentError, InstrumentRegistry, InstrumentStatus, PutOrCall, SecurityType};
+    use crate::fix::{Decimal, RawMessage};
+    use chrono::NaiveDate;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn sol_usd() -> Instrument {
+        Instrument::new("SOL-USD", dec("0.01"), dec("0.01"), dec("0.1"))
+    }
+
+    #[test]
+    fn test_validate_order_against_tick_lot_and_status() {
+        let mut registry = InstrumentRegistry::new();
+        registry.insert(sol_usd());
+        assert!(registry.validate_order("SOL-USD", &[dec("388.12")], dec("2.5")).is_ok());
+        assert!(registry.validate_order("SOL-USD", &[dec("388.1200")], dec("0.1")).is_ok());
+        assert!(matches!(registry.validate_order("SOL-USD", &[dec("388.125")], dec("2.5")), Err(InstrumentError::OffTick { .. })));
+        assert!(matches!(
+            registry.validate_order("SOL-USD", &[dec("388.1200000001")], dec("2.5")),
+            Err(InstrumentError::OffTick { .. })
+        ));
+        assert!(matches!(registry.validate_order("SOL-USD", &[dec("388")], dec("2.505")), Err(InstrumentError::OffLot { .. })));
+        assert!(matches!(registry.validate_order("SOL-USD", &[dec("388")], dec("0.09")), Err(InstrumentError::BelowMinQty { .. })));
+        assert!(matches!(registry.validate_order("DOGE-USD", &[dec("1")], dec("1")), Err(InstrumentError::UnknownSymbol(_))));
+        let sol = registry.get("SOL-USD").unwrap();
+        assert_eq!(
+            (sol.format_price(Decimal::new(3885, 1)), sol.format_quantity(Decimal::from(2))),
+            ("388.50".to_string(), "2.00".to_string())
+        );
+        assert_eq!((sol.format_price(dec("388.1200")), sol.round_price(dec("388.125"))), ("388.12".to_string(), dec("388.13")));
+        assert_eq!(Instrument::new("DOGE-USD", Decimal::ZERO, Decimal::ZERO, Decimal::ZERO).format_price(dec("0.12340")), "0.1234");
+
+        let mut halted = sol_usd();
+        halted.status = InstrumentStatus::Halted;
+        registry.insert(halted);
+        assert!(matches!(registry.validate_order("SOL-USD", &[dec("388")], dec("1")), Err(InstrumentError::NotTrading { .. })));
+    }
+
+    #[test]
+    fn test_load_from_csv_json_and_security_list() {
+        let csv =
+            "symbol,tick_size,lot_size,min_qty,multiplier,status\nSOL-USD,0.01,0.01,0.1,,\nBTC-USD-PERPETUAL,0.5,0.0001,0.0001,1,halted\n";
+        let registry = InstrumentRegistry::from_csv(csv).unwrap();
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get("BTC-USD-PERPETUAL").unwrap().status, InstrumentStatus::Halted);
+        assert!(InstrumentRegistry::from_csv("symbol,tick_size,lot_size,min_qty\nSOL-USD,x,1,1").is_err());
+
+        let json = serde_json::to_string(&registry.instruments()).unwrap();
+        let mut registry = InstrumentRegistry::from_json(&json).unwrap();
+        assert_eq!(registry.get("SOL-USD"), Some(&sol_usd()));
+        assert!(json.contains("\"tick_size\":0.01"));
+
+        let mut list = RawMessage::new("y");
+        list.set(320, "REQ-1").set(146, 2);
+        list.push(55, "SOL-USD").push(969, 0.001).push(326, 17);
+        list.push(55, "ETH-USD").push(969, 0.05).push(561, 0.001).push(562, 0.01).push(326, 2);
+        list.push(55, "SOL-20240628-150C").push(167, "OPT").push(311, "SOL").push(541, "20240628").push(202, 150).push(201, 1);
+        assert_eq!(registry.apply_security_list(&list), 3);
+        assert_eq!((registry.get("SOL-USD").unwrap().tick_size, registry.get("SOL-USD").unwrap().lot_size), (dec("0.001"), dec("0.01")));
+        assert_eq!(registry.get("ETH-USD").unwrap().status, InstrumentStatus::Halted);
+        let option = registry.get("SOL-20240628-150C").unwrap();
+        assert_eq!(
+            (option.security_type, option.strike, option.put_or_call),
+            (Some(SecurityType::Option), Some(150.0), Some(PutOrCall::Call))
+        );
+        assert_eq!(option.expiry, NaiveDate::from_ymd_opt(2024, 6, 28));
+
+        let json = serde_json::to_string(&registry.instruments()).unwrap();
+        assert!(json.contains("\"expiry\":\"2024-06-28\""));
+        assert_eq!(InstrumentRegistry::from_json(&json).unwrap().get("SOL-20240628-150C"), Some(option));
+    }
+}
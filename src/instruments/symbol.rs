@@ -0,0 +1,211 @@
+// src/instruments/symbol.rs
+//
+// power.trade instrument names, parsed into their parts and formatted back
+//  - spot 'SOL-USD' - base and quote currency
+//  - perpetual 'BTC-USD-PERPETUAL'
+//  - future 'BTC-USD-20240628' - expiry as YYYYMMDD
+//  - option 'SOL-20240628-150C' - base, expiry, strike and 'C' (call) or 'P' (put), quoted in USD
+//
+
+use super::{PutOrCall, SecurityType};
+use chrono::NaiveDate;
+use std::{fmt, str::FromStr};
+
+// quote currency of options, their names carry the base currency only
+const OPTION_QUOTE: &str = "USD";
+
+/// `SymbolError`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolError {
+    /// name does not have the parts of a spot, perpetual, future or option symbol
+    Format(String),
+    /// currency is not 2 to 10 upper case letters or digits
+    Currency { symbol: String, currency: String },
+    /// expiry is not a date as YYYYMMDD
+    Expiry { symbol: String, expiry: String },
+    /// strike is not a price above zero
+    Strike { symbol: String, strike: String },
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolError::Format(symbol) => write!(f, "'{symbol}' is not a spot, perpetual, future or option symbol"),
+            SymbolError::Currency { symbol, currency } => write!(f, "'{symbol}' currency '{currency}' is not valid"),
+            SymbolError::Expiry { symbol, expiry } => write!(f, "'{symbol}' expiry '{expiry}' is not a date as YYYYMMDD"),
+            SymbolError::Strike { symbol, strike } => write!(f, "'{symbol}' strike '{strike}' is not a price above zero"),
+        }
+    }
+}
+
+impl std::error::Error for SymbolError {}
+
+/// `Symbol`
+///
+/// validated power.trade instrument name, see `FromStr` for the formats accepted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    base: String,
+    quote: String,
+    security_type: SecurityType,
+    expiry: Option<NaiveDate>,
+    strike: Option<f64>,
+    put_or_call: Option<PutOrCall>,
+}
+
+impl Symbol {
+    /// `spot`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if base or quote is not a valid currency
+    pub fn spot(base: &str, quote: &str) -> Result<Symbol, SymbolError> {
+        Symbol::build(base, quote, SecurityType::Spot, None, None, None)
+    }
+
+    /// `perpetual`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if base or quote is not a valid currency
+    pub fn perpetual(base: &str, quote: &str) -> Result<Symbol, SymbolError> {
+        Symbol::build(base, quote, SecurityType::Perpetual, None, None, None)
+    }
+
+    /// `future`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if base or quote is not a valid currency
+    pub fn future(base: &str, quote: &str, expiry: NaiveDate) -> Result<Symbol, SymbolError> {
+        Symbol::build(base, quote, SecurityType::Future, Some(expiry), None, None)
+    }
+
+    /// `option`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - base is not a valid currency
+    /// - strike is zero or less
+    pub fn option(base: &str, expiry: NaiveDate, strike: f64, put_or_call: PutOrCall) -> Result<Symbol, SymbolError> {
+        Symbol::build(base, OPTION_QUOTE, SecurityType::Option, Some(expiry), Some(strike), Some(put_or_call))
+    }
+
+    fn build(
+        base: &str, quote: &str, security_type: SecurityType, expiry: Option<NaiveDate>, strike: Option<f64>,
+        put_or_call: Option<PutOrCall>,
+    ) -> Result<Symbol, SymbolError> {
+        let symbol = Symbol { base: base.to_string(), quote: quote.to_string(), security_type, expiry, strike, put_or_call };
+        if let Some(currency) = [base, quote].into_iter().find(|currency| !is_currency(currency)) {
+            return Err(SymbolError::Currency { symbol: symbol.to_string(), currency: currency.to_string() });
+        }
+        if let Some(strike) = strike.filter(|strike| !strike.is_finite() || *strike <= 0.0) {
+            return Err(SymbolError::Strike { symbol: symbol.to_string(), strike: strike.to_string() });
+        }
+        Ok(symbol)
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
+
+    pub fn security_type(&self) -> SecurityType {
+        self.security_type
+    }
+
+    /// futures and options only
+    pub fn expiry(&self) -> Option<NaiveDate> {
+        self.expiry
+    }
+
+    /// options only
+    pub fn strike(&self) -> Option<f64> {
+        self.strike
+    }
+
+    /// options only
+    pub fn put_or_call(&self) -> Option<PutOrCall> {
+        self.put_or_call
+    }
+}
+
+fn is_currency(currency: &str) -> bool {
+    (2..=10).contains(&currency.len()) && currency.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+impl FromStr for Symbol {
+    type Err = SymbolError;
+
+    /// parses 'SOL-USD', 'BTC-USD-PERPETUAL', 'BTC-USD-20240628' or 'SOL-20240628-150C'
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |value: &str| SymbolError::Expiry { symbol: s.to_string(), expiry: value.to_string() };
+        let expiry = |value: &str| match value.len() {
+            8 if value.chars().all(|c| c.is_ascii_digit()) => NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid(value)),
+            _ => Err(invalid(value)),
+        };
+        let parts: Vec<&str> = s.split('-').collect();
+        match parts.as_slice() {
+            [base, quote] => Symbol::spot(base, quote),
+            [base, quote, "PERPETUAL"] => Symbol::perpetual(base, quote),
+            [base, date, strike] if date.starts_with(|c: char| c.is_ascii_digit()) => {
+                let (strike, put_or_call) = match strike.split_at(strike.len().saturating_sub(1)) {
+                    (strike, "C") => (strike, PutOrCall::Call),
+                    (strike, "P") => (strike, PutOrCall::Put),
+                    _ => return Err(SymbolError::Format(s.to_string())),
+                };
+                let strike: f64 = strike.parse().map_err(|_| SymbolError::Strike { symbol: s.to_string(), strike: strike.to_string() })?;
+                Symbol::option(base, expiry(date)?, strike, put_or_call)
+            }
+            [base, quote, date] => Symbol::future(base, quote, expiry(date)?),
+            _ => Err(SymbolError::Format(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expiry: String = self.expiry.map(|expiry| expiry.format("%Y%m%d").to_string()).unwrap_or_default();
+        match (self.security_type, self.strike, self.put_or_call) {
+            (SecurityType::Option, Some(strike), Some(put_or_call)) => {
+                let suffix: char = if put_or_call == PutOrCall::Call { 'C' } else { 'P' };
+                write!(f, "{}-{expiry}-{strike}{suffix}", self.base)
+            }
+            (SecurityType::Perpetual, _, _) => write!(f, "{}-{}-PERPETUAL", self.base, self.quote),
+            (SecurityType::Future, _, _) => write!(f, "{}-{}-{expiry}", self.base, self.quote),
+            _ => write!(f, "{}-{}", self.base, self.quote),
+        }
+    }
+}
+
+#[cfg(test)]
+mod symbol_tests {
+
+    use super::{Symbol, SymbolError};
+    use crate::instruments::{PutOrCall, SecurityType};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_parse_and_format_power_trade_symbols() {
+        for name in ["SOL-USD", "BTC-USD-PERPETUAL", "BTC-USD-20240628", "SOL-20240628-150C", "ETH-20241227-2500.5P"] {
+            assert_eq!(name.parse::<Symbol>().unwrap().to_string(), name);
+        }
+        let option: Symbol = "SOL-20240628-150C".parse().unwrap();
+        assert_eq!((option.base(), option.quote(), option.security_type()), ("SOL", "USD", SecurityType::Option));
+        assert_eq!(
+            (option.expiry(), option.strike(), option.put_or_call()),
+            (NaiveDate::from_ymd_opt(2024, 6, 28), Some(150.0), Some(PutOrCall::Call))
+        );
+        assert_eq!("BTC-USD-20240628".parse::<Symbol>().unwrap().security_type(), SecurityType::Future);
+
+        assert!(matches!("SOLUSD".parse::<Symbol>(), Err(SymbolError::Format(_))));
+        assert!(matches!("sol-USD".parse::<Symbol>(), Err(SymbolError::Currency { .. })));
+        assert!(matches!("BTC-USD-20241345".parse::<Symbol>(), Err(SymbolError::Expiry { .. })));
+        assert!(matches!("SOL-20240628-0C".parse::<Symbol>(), Err(SymbolError::Strike { .. })));
+        assert!(matches!("SOL-20240628-150X".parse::<Symbol>(), Err(SymbolError::Format(_))));
+    }
+}
@@ -1,5 +1,13 @@
 // src/lib.rs 
 
+pub mod fix;
+pub mod instruments;
+pub mod orders;
+pub mod positions;
+pub mod risk;
+pub mod session;
+pub mod sim;
+
 pub mod common {
 
     use std::sync::{Arc, Mutex};
@@ -16,4 +24,4 @@ pub mod common {
         info!("Seqnum incremented to {}", *num);
         *num
     }
-}
\ No newline at end of file
+}
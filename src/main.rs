@@ -1,4 +1,4 @@
-#[allow(clippy::too_many_lines)]
+#![allow(clippy::too_many_lines)]
 
 #[path = "messages/factory.rs"]
 mod factory;
@@ -6,9 +6,21 @@ mod factory;
 #[path = "scenarios/rfq_listen.rs"]
 mod listen;
 
+#[path = "scenarios/mass_cancel.rs"]
+mod mass_cancel;
+
+#[path = "scenarios/multi_leg_order_add_cancel.rs"]
+mod multi_leg;
+
+#[path = "scenarios/order_status.rs"]
+mod order_status;
+
 #[path = "scenarios/rfq_publish.rs"]
 mod publish;
 
+#[path = "scenarios/security_list.rs"]
+mod security_list;
+
 #[path = "scenarios/single_leg_order.rs"]
 mod single_leg_order;
 
@@ -17,25 +29,30 @@ mod utils;
 
 pub(crate) mod setup;
 
-use clap::ValueEnum;
-use client_rust_fix::common::increment_seqnum;
+use client_rust_fix::fix::Decimal;
+use client_rust_fix::instruments::{self, Instrument, InstrumentQuery, Symbol};
+use client_rust_fix::orders::{MassCancelScope, OrderManager, SharedOrderManager};
+use client_rust_fix::session::{MessageClass, Session, SessionConfig};
+use client_rust_fix::sim::Simulator;
 use factory::FixMessageFactory;
 use log::{error,info};
+use mass_cancel::cancel_all;
+use multi_leg::send_multi_leg_order;
 use native_tls::TlsStream;
+use order_status::{reconcile_orders, request_order_status};
 use publish::rfq_publish_fix;
-use quickfix::Message;
-use quickfix_msg44::field_types::{OrdType, Side};
-use setup::{setup_env, setup_heartbeat, setup_keys, setup_logging, setup_rfq, setup_session, setup_trading};
-use single_leg_order::{send_single_order, send_multiple_orders};
-use std::{io::Write, net::TcpStream, option::Option::Some, process::ExitCode, thread::sleep, time::Duration};
-use utils::setup_tls_connection;
-
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-enum Environment {
-    Development,
-    Test,
-    Production
-}
+use security_list::request_security_list;
+use quickfix::{FieldMap, Message};
+use quickfix_msg44::field_types::{OrdType, Side, TimeInForce};
+use setup::{
+    setup_cl_ord_id, setup_drop_copy, setup_env, setup_heartbeat, setup_instruments, setup_keys, setup_kill_switch, setup_logging,
+    setup_paper, setup_positions, setup_rfq, setup_risk, setup_session, setup_throttle, setup_trading,
+};
+use setup::setup_env::Environment;
+use single_leg_order::{amend_order, cancel_single_order, send_single_order, send_multiple_orders};
+use std::{env::var, net::TcpStream, option::Option::Some, path::Path, process::ExitCode, sync::{Arc, Mutex}, time::Duration};
+use utils::{connect_tls, setup_tls_connection, Leg, OrderOptions, OrderTimeInForce, StopTrigger, TriggerPriceType};
+
 pub fn main() -> ExitCode {
     let version = "version 0.1.9 built on 1/6/2024";
     info!("Starting Fix client for power.trade [{version}]");
@@ -43,7 +60,7 @@ pub fn main() -> ExitCode {
     const FAILURE: u8 = 1;
 
     // read env vars and default settings
-    let (status, scenario) = setup_env::exec().unwrap();
+    let (status, scenario, pt_env, cancel_scope, instrument_query) = setup_env::exec().unwrap();
     if !status {
         println!("Error while setting up 'env'");
         return ExitCode::from(FAILURE);
@@ -61,6 +78,18 @@ pub fn main() -> ExitCode {
         return ExitCode::from(FAILURE);
     }
 
+    // select ClOrdID [11] generation strategy
+    if !setup_cl_ord_id::exec().unwrap() {
+        println!("Error while setting up 'ClOrdID generator'");
+        return ExitCode::from(FAILURE);
+    }
+
+    // load instrument reference data used to validate orders before they are created
+    if !setup_instruments::exec().unwrap() {
+        println!("Error while setting up 'instruments'");
+        return ExitCode::from(FAILURE);
+    }
+
     // read and initialize keys used for Fix session and power.trade trading
     let (status, apikey, pkey ) = setup_keys::exec().unwrap();
     if !status {
@@ -68,10 +97,40 @@ pub fn main() -> ExitCode {
         return ExitCode::from(FAILURE);
     }
 
-    // Initiate TLS Stream to handle messaging to/from power.trade server
-    let mut tls_stream: TlsStream<TcpStream> = setup_tls_connection();
-
-    let (status, seqnum) = setup_session::exec(&apikey, pkey.clone(), &mut tls_stream).unwrap();
+    // start session reader/writer threads and logon
+    // n.b. MsgSeqNum [34] is (re)assigned by the session writer thread when each message is sent
+    // n.b. ExecutionReports from order-entry and drop-copy sessions are applied to `order_state`
+    let order_state: SharedOrderManager = OrderManager::shared();
+    let mut config: SessionConfig = SessionConfig::new(&apikey, "PT-OE");
+    let (status, throttle) = setup_throttle::exec().unwrap();
+    if !status {
+        println!("Error while setting up 'throttle'");
+        return ExitCode::from(FAILURE);
+    }
+    config.throttle = throttle;
+    let (status, kill_switch, kill_timeout, cancel_on_reconnect) = setup_kill_switch::exec().unwrap();
+    if !status {
+        println!("Error while setting up 'kill switch'");
+        return ExitCode::from(FAILURE);
+    }
+    config.cancel_on_reconnect = cancel_on_reconnect;
+    let mut simulator: Option<Simulator> = None;
+    let (status, seqnum, session): (bool, Arc<Mutex<u32>>, Session) = if pt_env == Environment::Paper {
+        // paper trading - connect to in-process simulator, orders are matched locally
+        let (status, paper_simulator, tcp_stream) = setup_paper::exec().unwrap();
+        if !status {
+            println!("Error while setting up 'paper'");
+            return ExitCode::from(FAILURE);
+        }
+        let address = paper_simulator.local_addr();
+        simulator = Some(paper_simulator);
+        setup_session::exec(config, pkey.clone(), tcp_stream, Arc::clone(&order_state), move || TcpStream::connect(address)).unwrap()
+    } else {
+        // Initiate TLS Stream to handle messaging to/from power.trade server
+        let port: String = var("PT_PORT").unwrap_or_else(|_| "2021".to_string());
+        let tls_stream: TlsStream<TcpStream> = setup_tls_connection(&port);
+        setup_session::exec(config, pkey.clone(), tls_stream, Arc::clone(&order_state), move || connect_tls(&port)).unwrap()
+    };
     if !status {
         println!("Error while setting up 'session'");
         return ExitCode::from(FAILURE);
@@ -79,75 +138,255 @@ pub fn main() -> ExitCode {
         println!("Seqnum initialized with value {:?}", seqnum.lock().unwrap() );
     }
 
+    // positions and PnL from fills on order-entry and drop-copy sessions
+    let (status, positions) = setup_positions::exec().unwrap();
+    if !status {
+        println!("Error while setting up 'positions'");
+        return ExitCode::from(FAILURE);
+    }
+    session.outbound.set_position_keeper(Arc::clone(&positions));
+
+    // pre-trade risk checks, every order sent on the session must pass them, rejected orders are never written
+    let (status, risk_gate) = setup_risk::exec(&order_state, &positions).unwrap();
+    if !status {
+        println!("Error while setting up 'risk'");
+        return ExitCode::from(FAILURE);
+    }
+    session.outbound.set_risk_gate(Arc::clone(&risk_gate));
+
+    // once tripped, no new order is sent, open orders are mass-cancelled and the session logs out
+    session.outbound.set_kill_switch(kill_switch.clone());
+    risk_gate.lock().unwrap().set_kill_switch(kill_switch.clone());
+    let kill_watch = kill_switch.watch(
+        session.outbound.clone(),
+        session.config().clone(),
+        Arc::clone(&order_state),
+        kill_timeout,
+        Duration::from_millis(200),
+    );
+
+    // start drop-copy session (if enabled) beside order-entry session
+    let (status, drop_copy) =
+        setup_drop_copy::exec(&apikey, pkey.clone(), simulator.as_ref(), &order_state, &risk_gate, &positions).unwrap();
+    if !status {
+        println!("Error while setting up 'drop-copy'");
+        return ExitCode::from(FAILURE);
+    }
+
     // setup common trading settings and defaults
     if !setup_trading::exec().unwrap() {
         println!("Error while setting up 'trading'");
         return ExitCode::from(FAILURE);
     }
 
+    // reconcile order state with open orders reported by venue (OrderMassStatusRequest 35=AF)
+    if var("PT_RECONCILE_ON_LOGON").map_or(false, |v| v.eq_ignore_ascii_case("true")) {
+        let seqnum_latest = *seqnum.lock().unwrap();
+        if let Err(error) = reconcile_orders(&apikey, &session.outbound, &session.inbound, &order_state, None, seqnum_latest) {
+            error!("Order reconciliation failed: {error}");
+            println!("Order reconciliation failed: {error}");
+        }
+    }
+
     //
     // Execute assigned scenario now session is opened
     //
     match scenario.as_str()  {
         "ORDER" => {
             // TODO - take these values from setup_trading call
-            const PRICE: f64 = 388.00; 
-            const QUANTITY: f64 = 2.00; 
+            const PRICE: Option<Decimal> = Some(Decimal::new(38800, 2)); 
+            const QUANTITY: Decimal = Decimal::new(200, 2); 
             const SIDE: Side = Side::Sell;
             const ORDERTYPE: OrdType = OrdType::Limit;
-            let symbol: String = "SOL-USD".to_string();
+            const TIF: TimeInForce = TimeInForce::GoodTillCancel;
+            let symbol: Symbol = "SOL-USD".parse().unwrap();
 
             //
             // publish new limit single leg order, listen for response msg and cancel (if cancel_order == 'true')
             // use current seqnum(latest) for new order
-            let mut seqnum_latest = *seqnum.lock().unwrap() ;
-            let order_msg = FixMessageFactory::new_single_leg_order(apikey.clone(), PRICE, QUANTITY, symbol, SIDE,ORDERTYPE, seqnum_latest).unwrap();
+            let seqnum_latest = *seqnum.lock().unwrap() ;
+            let order_msg = FixMessageFactory::new_single_leg_order(
+                apikey.clone(), PRICE, QUANTITY, symbol, SIDE, ORDERTYPE, None, OrderTimeInForce::new(TIF), OrderOptions::default(), seqnum_latest,
+            )
+            .unwrap();
+            info!("Sending New Order message {:?}", order_msg);
+            send_single_order(&apikey.clone(), &session.outbound, &session.inbound, &order_state, order_msg.clone(), seqnum_latest, Some(true));
+        },
+        "MULTI_LEG" => {
+            //
+            // publish buy SOL / sell BTC package at a net price which will not be executed, listen for response msg and cancel it
+            //
+            const NET_PRICE: Option<Decimal> = Some(Decimal::new(100, 2));
+            const QUANTITY: Decimal = Decimal::new(100, 2);
+            const SIDE: Side = Side::Buy;
+            const ORDERTYPE: OrdType = OrdType::Limit;
+            const TIF: TimeInForce = TimeInForce::GoodTillCancel;
+            let legs: Vec<Leg> = vec![
+                Leg::new("SOL-USD".parse().unwrap(), Side::Buy, Decimal::from(1)),
+                Leg::new("BTC-USD".parse().unwrap(), Side::Sell, Decimal::new(1, 2)),
+            ];
+
+            let seqnum_latest = *seqnum.lock().unwrap();
+            let order_msg =
+                FixMessageFactory::new_order_multi(&apikey, &legs, SIDE, QUANTITY, NET_PRICE, ORDERTYPE, OrderTimeInForce::new(TIF), seqnum_latest)
+                    .unwrap();
+            match send_multi_leg_order(&apikey, &session.outbound, &session.inbound, &order_state, order_msg, seqnum_latest, true) {
+                Ok(order) => info!("Multi Leg Order {} done with status {}", order.cl_ord_id, order.ord_status),
+                Err(reason) => {
+                    error!("Multi Leg Order failed: {reason}");
+                    println!("Multi Leg Order failed: {reason}");
+                }
+            }
+        },
+        "STOP" => {
+            //
+            // publish stop-limit sell below the market, listen for response msg and cancel it before it is triggered
+            //
+            const PRICE: Option<Decimal> = Some(Decimal::new(37900, 2));
+            const STOP_PX: Decimal = Decimal::new(38000, 2);
+            const QUANTITY: Decimal = Decimal::new(200, 2);
+            const SIDE: Side = Side::Sell;
+            const ORDERTYPE: OrdType = OrdType::StopLimit;
+            const TIF: TimeInForce = TimeInForce::GoodTillCancel;
+            let symbol: Symbol = "SOL-USD".parse().unwrap();
 
-            // - increment seqnum for use in cancel order
-            seqnum_latest = increment_seqnum(seqnum.clone()); 
-            info!("Sequence number incremented to {:?} after sending New Order message {:?}", seqnum_latest, order_msg);
-            send_single_order(&apikey.clone(),  &mut tls_stream, order_msg.clone(), seqnum_latest, Some(true));
+            let seqnum_latest = *seqnum.lock().unwrap();
+            let stop: StopTrigger = StopTrigger::new(STOP_PX).triggered_by(TriggerPriceType::LastTrade);
+            let order_msg = FixMessageFactory::new_single_leg_order(
+                apikey.clone(), PRICE, QUANTITY, symbol, SIDE, ORDERTYPE, Some(stop), OrderTimeInForce::new(TIF), OrderOptions::default(), seqnum_latest,
+            )
+            .unwrap();
+            info!("Sending New Stop Order message {:?}", order_msg);
+            send_single_order(&apikey, &session.outbound, &session.inbound, &order_state, order_msg, seqnum_latest, Some(true));
         },
         "ORDERS" => {
             //
-            // publish new set of limit single leg orders, listen for response msg and cancel (if cancel_order == 'true')
+            // publish new set of limit single leg orders as one batch, collect their acks and cancel (if cancel_order == 'true')
             //
-            const PRICE: f64 = 388.00; 
-            const QUANTITY: f64 = 2.00; 
+            const PRICES: [Decimal; 3] = [Decimal::new(38800, 2), Decimal::new(38850, 2), Decimal::new(38900, 2)];
+            const QUANTITY: Decimal = Decimal::new(200, 2); 
             const SIDE: Side = Side::Sell;
             const ORDERTYPE: OrdType = OrdType::Limit;
-            let symbol: String = "SOL-USD".to_string();
+            const TIF: TimeInForce = TimeInForce::GoodTillCancel;
+            const ACK_TIMEOUT: Duration = Duration::from_secs(5);
+            let symbol: Symbol = "SOL-USD".parse().unwrap();
 
-            // use current seqnum(latest) for new order
-            let mut seqnum_latest = *seqnum.lock().unwrap() ;
-            let order_msg = FixMessageFactory::new_single_leg_order(apikey.clone(), PRICE, QUANTITY, symbol, SIDE,ORDERTYPE, seqnum_latest).unwrap();
-            let orders: Vec<Message> = vec![order_msg.clone()];
+            // MsgSeqNum [34] of each order is assigned by the session writer, consecutive for the batch
+            let seqnum_latest = *seqnum.lock().unwrap() ;
+            let orders: Vec<Message> = PRICES
+                .iter()
+                .map(|price| {
+                    FixMessageFactory::new_single_leg_order(
+                        apikey.clone(), Some(*price), QUANTITY, symbol.clone(), SIDE, ORDERTYPE, None, OrderTimeInForce::new(TIF),
+                        OrderOptions::default(), seqnum_latest,
+                    )
+                    .unwrap()
+                })
+                .collect();
+            info!("Sending New Order messages {:?}", orders);
+
+            send_multiple_orders(&apikey, &session.outbound, &session.inbound, &order_state, orders, seqnum_latest, true, ACK_TIMEOUT);
+        },
+        "AMEND" => {
+            //
+            // publish new limit single leg order, amend price/quantity with cancel/replace (35=G), check its status (35=H), then cancel it
+            //
+            const PRICE: Option<Decimal> = Some(Decimal::new(38800, 2));
+            const QUANTITY: Decimal = Decimal::new(200, 2);
+            const NEW_PRICE: Decimal = Decimal::new(38900, 2);
+            const NEW_QUANTITY: Decimal = Decimal::new(300, 2);
+            const SIDE: Side = Side::Sell;
+            const ORDERTYPE: OrdType = OrdType::Limit;
+            const TIF: TimeInForce = TimeInForce::GoodTillCancel;
+            let symbol: Symbol = "SOL-USD".parse().unwrap();
 
-            // increment seqnum for cancel of new order 
-            seqnum_latest = increment_seqnum(seqnum.clone()); 
-            info!("Sequence number incremented to {:?} after sending New Order message {:?}", seqnum_latest, order_msg.clone());
+            let seqnum_latest = *seqnum.lock().unwrap();
+            let order_msg = FixMessageFactory::new_single_leg_order(
+                apikey.clone(), PRICE, QUANTITY, symbol, SIDE, ORDERTYPE, None, OrderTimeInForce::new(TIF), OrderOptions::default(), seqnum_latest,
+            )
+            .unwrap();
+            let cl_ord_id: String = order_msg.get_field(11).unwrap();
+            send_single_order(&apikey, &session.outbound, &session.inbound, &order_state, order_msg, seqnum_latest, Some(false));
 
-            // TODO - enhance send_nultiple to manage seqnums
-            send_multiple_orders(&apikey, tls_stream, orders, seqnum_latest, true);
+            match amend_order(&apikey, &session.outbound, &session.inbound, &order_state, &cl_ord_id, NEW_PRICE, NEW_QUANTITY, seqnum_latest) {
+                Ok(order) => info!("Order {cl_ord_id} amended, now ClOrdID {} price {:?} quantity {}", order.cl_ord_id, order.price, order.order_qty),
+                Err(reason) => {
+                    error!("Order {cl_ord_id} not amended: {reason}");
+                    println!("Order {cl_ord_id} not amended: {reason}");
+                }
+            }
+            if let Err(reason) = request_order_status(&apikey, &session.outbound, &session.inbound, &order_state, &cl_ord_id, seqnum_latest) {
+                error!("Order {cl_ord_id} status not received: {reason}");
+                println!("Order {cl_ord_id} status not received: {reason}");
+            }
+            cancel_single_order(&apikey, &session.outbound, &session.inbound, &order_state, &cl_ord_id, seqnum_latest);
+        },
+        "CANCEL_ALL" => {
+            //
+            // emergency - cancel open orders in one OrderMassCancelRequest (35=q), scope from --cancel-all (default all orders)
+            //
+            let scope: MassCancelScope = cancel_scope.unwrap_or(MassCancelScope::All);
+            let seqnum_latest = *seqnum.lock().unwrap();
+            if let Err(reason) = cancel_all(&apikey, &session.outbound, &session.inbound, &order_state, &scope, seqnum_latest) {
+                error!("Mass cancel ({scope}) failed: {reason}");
+                println!("Mass cancel ({scope}) failed: {reason}");
+            }
+        },
+        "SECURITY_LIST" => {
+            //
+            // request instrument reference data (35=x / 35=y) and print instruments matching query from --instruments or
+            // PT_INSTRUMENT_QUERY, e.g. "underlying=SOL type=option expires=week" lists SOL options expiring this week
+            //
+            let query: InstrumentQuery = match instrument_query {
+                Some(query) => query,
+                None => match var("PT_INSTRUMENT_QUERY").unwrap_or_default().parse() {
+                    Ok(query) => query,
+                    Err(reason) => {
+                        error!("PT_INSTRUMENT_QUERY is not valid: {reason}");
+                        println!("PT_INSTRUMENT_QUERY is not valid: {reason}");
+                        return ExitCode::from(FAILURE);
+                    }
+                },
+            };
+            let seqnum_latest = *seqnum.lock().unwrap();
+            let security_type: Option<&str> = query.security_type.map(|security_type| security_type.as_fix());
+            match request_security_list(&apikey, &session.outbound, &session.inbound, security_type, seqnum_latest) {
+                Ok(_) => {
+                    let today = chrono::Utc::now().date_naive();
+                    let listed: Vec<Instrument> = instruments::update_registry(|registry| registry.query(&query, today).into_iter().cloned().collect());
+                    println!("{} instrument(s) matching {query:?}", listed.len());
+                    for instrument in listed {
+                        let expiry: String = instrument.expiry.map(|expiry| expiry.to_string()).unwrap_or_default();
+                        let strike: String = instrument.strike.map(|strike| strike.to_string()).unwrap_or_default();
+                        let put_or_call: String = instrument.put_or_call.map(|put_or_call| put_or_call.to_string()).unwrap_or_default();
+                        let security_type: &str = instrument.security_type.map(|t| t.as_fix()).unwrap_or("-");
+                        println!(
+                            "{:<24} {:<5} {expiry:<10} {strike:>10} {put_or_call:<4} tick {} lot {} {}",
+                            instrument.symbol, security_type, instrument.tick_size, instrument.lot_size, instrument.status
+                        );
+                    }
+                },
+                Err(reason) => {
+                    error!("Security list failed: {reason}");
+                    println!("Security list failed: {reason}");
+                }
+            }
         },
         "RFQ_QUOTE" => {
             //
             // publish RFQ quote request & listen for response msgs
             //
             // use current seqnum(latest) for new quote
-            let mut seqnum_latest = *seqnum.lock().unwrap();
+            let seqnum_latest = *seqnum.lock().unwrap();
 
             let (status, rfq_quote_msg ) = setup_rfq::exec(&apikey, seqnum_latest).unwrap();
             if !status {
                 error!("Error while setting up 'rfq'");
                 return ExitCode::from(FAILURE);
-            } else {
-                seqnum_latest = increment_seqnum(seqnum.clone()); 
-                info!("Sequence number incremented to {:?} after sending RFQ message {:?}", seqnum_latest, rfq_quote_msg);
             }
             info!("Sending RFQ Quote {:?}", rfq_quote_msg);
             println!("Sending RFQ Quote {:?}", rfq_quote_msg);
-            rfq_publish_fix(tls_stream, rfq_quote_msg);
+            rfq_publish_fix(&session.outbound, &session.inbound, rfq_quote_msg);
         }, 
         "RFQ_LISTEN" => {
             //
@@ -157,43 +396,85 @@ pub fn main() -> ExitCode {
             //
 
             // use current seqnum(latest) for new quote
-            let mut seqnum_latest = *seqnum.lock().unwrap();
+            let seqnum_latest = *seqnum.lock().unwrap();
 
             let (status, rfq_subscribe_msg) = setup_rfq::exec(&apikey, seqnum_latest).unwrap();
             if !status {
                 println!("Error while setting up 'rfq'");
                 return ExitCode::from(FAILURE);
-            } else {
-                seqnum_latest = increment_seqnum(seqnum.clone()); 
-                info!("Sequence number incremented to {:?} after sending RFQ message {:?}", seqnum_latest, rfq_subscribe_msg);
             }
             info!("Sending RFQ Listen {:?}", rfq_subscribe_msg);
             println!("Sending RFQ Listen {:?}", rfq_subscribe_msg);
-            rfq_publish_fix(tls_stream, rfq_subscribe_msg);
+            rfq_publish_fix(&session.outbound, &session.inbound, rfq_subscribe_msg);
             },
         _ => {
             panic!("Error - no valid scenario defined to execute. Value provided was '{scenario}'");
         }
     }
-    ExitCode::from(SUCCESS) // return SUCCESS(0) status to calling exvironment
-}
-
-fn _send_heartbeat(apikey: String, seqnum: u32, mut tls_stream: TlsStream<TcpStream>) {
 
-    println!("Hello from spawned thread for seqnum {seqnum}");
-    let heartbeat_msg: Message = match FixMessageFactory::heartbeat(apikey, seqnum, "PT-OE") {
-        Ok(heartbeat_msg) => {
-            info!("Created new Fix heartbeat msg : {:?}", heartbeat_msg);
-            heartbeat_msg
-        },
-        Err(error) => {
-            error!("Error creating Fix Logon message -> {error:?}");
-            return;
+    // stop drop-copy session, then send Logout and stop session reader/writer threads
+    if let Some(drop_copy) = drop_copy {
+        drop_copy.stop();
+    }
+    kill_switch.disarm();
+    if kill_watch.join().is_err() {
+        error!("Kill switch thread panicked");
+    }
+    let outbound = session.outbound.clone();
+    session.logout();
+    let metrics = outbound.throttle_metrics();
+    for class in [MessageClass::New, MessageClass::Cancel, MessageClass::Amend, MessageClass::Other] {
+        info!("Outbound {class} messages {}", metrics.stats(class));
+        println!("Outbound {class} messages {}", metrics.stats(class));
+    }
+    // round trips from order written to first response, PT_LATENCY_CSV=<file> exports one line per order
+    let latency = outbound.latency_tracker();
+    let latency = latency.lock().unwrap();
+    for (msg_type, stats) in latency.summary() {
+        info!("Round trip 35={msg_type} {stats}");
+        println!("Round trip 35={msg_type} {stats}");
+    }
+    if let Some(file) = var("PT_LATENCY_CSV").ok().filter(|file| !file.is_empty()) {
+        if let Err(error) = latency.write_csv(Path::new(&file)) {
+            error!("Latency CSV not written to {file}: {error}");
+            println!("Latency CSV not written to {file}: {error}");
         }
-    };
-    match tls_stream.write( heartbeat_msg.to_fix_string().expect("Error converting Logon Msg to Bytes").as_bytes()) {
-        Ok(byte_count) => { println!("Sent {byte_count} bytes for heartbeat"); }
-        Err(error) => { println!("Error while sending msg {error} "); }
-    };
-    sleep(Duration::from_millis(500));
+    }
+    drop(latency);
+    for order in order_state.lock().unwrap().orders() {
+        let origin: &str = if order.external { ", placed outside this session" } else { "" };
+        let summary: String = format!(
+            "Order {} ClOrdID {} {} OrdStatus {} CumQty {} LeavesQty {} AvgPx {} (last report via {}{origin})",
+            order.order_id.as_deref().unwrap_or("-"),
+            order.cl_ord_id,
+            order.symbol,
+            order.ord_status,
+            order.cum_qty,
+            order.leaves_qty,
+            order.avg_px,
+            order.source
+        );
+        info!("{summary}");
+        println!("{summary}");
+    }
+    let positions = positions.lock().unwrap();
+    for position in positions.positions() {
+        let unrealized: String = positions.unrealized_pnl(position).map_or("-".to_string(), |pnl| pnl.to_string());
+        let summary: String = format!(
+            "Position {} {} quantity {} avg entry {} realized PnL {} fees {} unrealized PnL {unrealized}",
+            if position.account.is_empty() { "-" } else { &position.account },
+            position.symbol,
+            position.quantity,
+            position.avg_px,
+            position.realized_pnl,
+            position.fees
+        );
+        info!("{summary}");
+        println!("{summary}");
+    }
+    drop(positions);
+    if let Some(simulator) = simulator {
+        simulator.stop();
+    }
+    ExitCode::from(SUCCESS) // return SUCCESS(0) status to calling exvironment
 }
@@ -1,21 +1,24 @@
 #![allow(clippy::needless_return)]
 
-use quickfix_msg44::{field_types::{ClOrdID, OrdType, OrderQty, Price, Side, SubscriptionRequestType, Symbol, TransactTime}, NewOrderMultileg, OrderCancelRequest, RFQRequest};
+use client_rust_fix::fix::Decimal;
+use client_rust_fix::instruments::{self, Symbol};
+use client_rust_fix::orders::{cl_ord_id::next_cl_ord_id, MassCancelScope, MULTILEG_SYMBOL};
+use quickfix_msg44::{field_types::{ClOrdID, OrdType, Side, SubscriptionRequestType}, OrderCancelRequest, RFQRequest};
 use log::{error, info};
 use quickfix::{Message, QuickFixError};
 use jwtk::ecdsa::EcdsaPrivateKey;
 use std::{env::var, time::{SystemTime, UNIX_EPOCH}};
-use crate::utils::{get_now, side_as_int, order_type_to_char, generate_access_token, generate_order_id, generate_ts};
+use crate::utils::{validate_order_prices, Leg, OrderOptions, OrderTimeInForce, StopTrigger, side_as_int, order_type_to_char, generate_access_token, generate_order_id, generate_ts};
 
 #[allow(dead_code)]
 #[allow(unused)]
 pub struct WSMessageFactory;
 impl WSMessageFactory {
     #[allow(dead_code)]
-    pub fn new_rfq_request(cl_ord_id: ClOrdID, symbol: Symbol, side: Side , order_qty: OrderQty, price: Price, order_type: OrdType ,text: &str ) -> String {
+    pub fn new_rfq_request(cl_ord_id: ClOrdID, symbol: Symbol, side: Side , order_qty: Decimal, price: Decimal, order_type: OrdType ,text: &str ) -> String {
         info!("Cient Order Id -> {}", cl_ord_id);
         info!("Side -> {:?}", side);
-        info!("Symbol -> {:?}", symbol);
+        info!("Symbol -> {}", symbol);
         info!("Cient Order Type -> {:?}", order_type);
         let json_str: String = format!(
             r#"{{
@@ -35,8 +38,8 @@ impl WSMessageFactory {
             }}"#,
             side,
             order_type,
-            order_qty,
-            price,
+            instruments::format_quantity(&symbol.to_string(), order_qty),
+            instruments::format_price(&symbol.to_string(), price),
             cl_ord_id,
             generate_ts(0),
             symbol,
@@ -62,34 +65,70 @@ impl FixMessageFactory {
     ///
     /// function will return error if 
     /// - price is zero or less on Limit order
-    /// - price is not aligned to power.trade price levels 
-    /// - quantity is zero or less
+    ///   n.b. price, stop price and quantity are written at the instrument tick / lot size precision, see `instruments::format_price`
+    /// - price or stop price is not aligned to the instrument tick size
+    /// - quantity is zero or less, below the instrument minimum or not a multiple of its lot size
     /// - side is not valie (1 - buy or 2 - sell)
-    /// - symbol is not a traded coin or instrument @ power.trade
-    /// - order type is not valid (Limit or Market)
+    /// - symbol is not a traded coin or instrument @ power.trade, or is not trading
+    ///   n.b. instrument checks apply once a registry was set with `instruments::set_registry`
+    /// - order type is not valid (Limit, Market, Stop or StopLimit)
+    /// - price is set on Market or Stop order
+    /// - stop price is missing or zero or less on Stop or StopLimit order, or set on any other order type
+    /// - time in force is not allowed for order type, or GoodTillDate expiry is missing or in the past
+    /// - post-only option is set on Market / Stop order or with IOC / FOK
     /// - seqnum is less than 2 (1 is seqnum for login message)
-    pub fn new_single_leg_order( apikey: String, price: f64, quantity: f64, symbol: Symbol,side: Side, order_type: OrdType, seqnum: u32) -> Result<Message, QuickFixError> {
+    pub fn new_single_leg_order(
+        apikey: String, price: Option<Decimal>, quantity: Decimal, symbol: Symbol, side: Side, order_type: OrdType, stop: Option<StopTrigger>,
+        time_in_force: OrderTimeInForce, options: OrderOptions, seqnum: u32,
+    ) -> Result<Message, QuickFixError> {
+
+        if let Err(reason) = validate_order_prices(order_type, price, stop.as_ref()) {
+            error!("Single Leg Order prices are not valid: {reason}");
+            return Err(QuickFixError::InvalidArgument(reason));
+        }
+        if let Err(reason) = time_in_force.validate(order_type) {
+            error!("Single Leg Order time in force is not valid: {reason}");
+            return Err(QuickFixError::InvalidArgument(reason));
+        }
+        if let Err(reason) = options.validate(order_type, &time_in_force) {
+            error!("Single Leg Order options are not valid: {reason}");
+            return Err(QuickFixError::InvalidArgument(reason));
+        }
+        let prices: Vec<Decimal> = price.into_iter().chain(stop.as_ref().map(|stop| stop.stop_px)).collect();
+        if let Err(error) = instruments::validate_order(&symbol.to_string(), &prices, quantity) {
+            error!("Single Leg Order is not valid for instrument: {error}");
+            return Err(QuickFixError::InvalidArgument(error.to_string()));
+        }
 
         let begin_string: String = "FIX.4.4".to_string();  // BeginString    [8]
         let message_type: char = 'D';                      // MsgType       [35]
-        let client_order_id = get_now();              // ClOrdId       [11]
+        let client_order_id: String = next_cl_ord_id();    // ClOrdId       [11]
         let order_type: char = order_type_to_char(order_type); // OrdType   [40]
         let side_int:u32 = side_as_int(side);              // Side          [54]
         let ts: String = generate_ts(0);        // SendingTime   [52]
         let target_comp_id = "PT-OE";                // TargetCompID  [56] - use config value TODO
-        let time_in_force: u32 = 1;                        // TimeInForce   [59] - '1' = GTC
+        let tif_fields: String = time_in_force.fields();   // TimeInForce   [59] + ExpireTime [126] / ExpireDate [432]
+        let quantity: String = instruments::format_quantity(&symbol.to_string(), quantity); // OrderQty [38]
+        let price_field: String =
+            price.map(|price| format!("44={}\x01", instruments::format_price(&symbol.to_string(), price))).unwrap_or_default(); // Price [44]
+        let stop_fields: String = stop.map(|stop| stop.fields(&symbol.to_string())).unwrap_or_default(); // StopPx [99] + TriggerPriceType [1107]
+        let option_fields: String = options.fields();      // ExecInst [18] + SelfMatchPreventionInstruction [2964]
 
         // Body
         // msg.append_pair(11,   now)         -> ClOrdID
         // msg.append_pair(38,   quantity)
-        // msg.append_pair(40,   2)           -> order type ["market" = 1, "limit" = 2]
-        // msg.append_pair(44,   price)
+        // msg.append_pair(40,   2)           -> order type ["market" = 1, "limit" = 2, "stop" = 3, "stop limit" = 4]
+        // msg.append_pair(44,   price)       -> Limit and StopLimit only
+        // msg.append_pair(99,   stop price)  -> Stop and StopLimit only
+        // msg.append_pair(18,   ExecInst)    -> "6" = post-only, "E" = reduce-only, space separated
+        // msg.append_pair(2964, STP)         -> "1" = cancel aggressive, "2" = cancel passive, "3" = cancel both
         // msg.append_pair(54,   1)           -> Side ["1" = "Buy", "2" = "Sell"]
         // msg.append_pair(55,   symbol)
-        // msg.append_pair(59,   TimeInForce) -> TimeInforce ["1" = "GTC"]
+        // msg.append_pair(59,   TimeInForce) -> TimeInforce ["0" = "Day", "1" = "GTC", "3" = "IOC", "4" = "FOK", "6" = "GTD"]
+        // msg.append_pair(126,  ExpireTime)  -> GTD only, or ExpireDate [432]
         // msg.append_pair(60,   format_epoch_time(now))
 
-        let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0111={client_order_id}\x0138={quantity}\x0140={order_type}\x0144={price}\x0149={apikey}\x0152={ts}\x0154={side_int}\x0155={symbol}\x0156={target_comp_id}\x0159={time_in_force}\x0160={ts}\x01");
+        let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0111={client_order_id}\x0138={quantity}\x0140={order_type}\x01{price_field}49={apikey}\x0152={ts}\x0154={side_int}\x0155={symbol}\x0156={target_comp_id}\x01{tif_fields}{stop_fields}{option_fields}60={ts}\x01");
         println!("Single Order Msg as string: {template}");
 
         // 
@@ -146,7 +185,7 @@ impl FixMessageFactory {
         let ts: String = generate_ts(0);        // SendingTime   [52]
         let side_int:u32 = side_as_int(side);              // Side          [54]
         let target_comp_id = "PT-OE";                // TargetCompID  [56] - use config value TODO
-        let cl_order_id: u64 = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards!!").as_secs();
+        let cl_order_id: String = next_cl_ord_id();        // ClOrdID       [11]
        
         let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0137={exch_order_id}\x0149={apikey}\x0156={target_comp_id}\x0111={cl_order_id}\x0141={orig_cl_order_id}\x0152={ts}\x0154={side_int}\x0155={symbol}\x0158={text}\x0160={ts}\x01");
         info!("Order Cancel Msg as string: {:?}", template.to_string());
@@ -165,6 +204,221 @@ impl FixMessageFactory {
         }
         msg
     }
+    /// `cancel_replace_order`
+    ///
+    /// OrderCancelReplaceRequest (35=G) amending price/quantity/TIF of a working order, keeps queue priority where venue allows
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - price is zero or less on Limit order
+    /// - quantity is zero or less
+    /// - price / quantity is not valid for the instrument (tick size, lot size, minimum quantity, status)
+    /// - time in force is not allowed for order type, or GoodTillDate expiry is missing or in the past
+    /// - message cannot be created from template
+    pub fn cancel_replace_order(
+        apikey: &str, orig_cl_order_id: &str, exch_order_id: &str, side: Side, symbol: &str, order_type: OrdType, price: Decimal, quantity: Decimal,
+        time_in_force: OrderTimeInForce, seqnum: u32,
+    ) -> Result<Message, QuickFixError> {
+
+        if let Err(reason) = time_in_force.validate(order_type) {
+            error!("Cancel/Replace time in force is not valid: {reason}");
+            return Err(QuickFixError::InvalidArgument(reason));
+        }
+
+        let begin_string: String = "FIX.4.4".to_string();  // BeginString    [8]
+        let message_type: char = 'G';                      // MsgType       [35]
+        let ts: String = generate_ts(0);        // SendingTime   [52]
+        let side_int:u32 = side_as_int(side);              // Side          [54]
+        let order_type: char = order_type_to_char(order_type); // OrdType   [40]
+        let tif_fields: String = time_in_force.fields();   // TimeInForce   [59] + ExpireTime [126] / ExpireDate [432]
+        let target_comp_id = "PT-OE";                // TargetCompID  [56] - use config value TODO
+        let cl_order_id: String = next_cl_ord_id();        // ClOrdID       [11]
+
+        if quantity <= Decimal::ZERO {
+            error!("Cancel/Replace quantity must be greater than zero, was {quantity}");
+            return Err(QuickFixError::InvalidArgument(format!("OrderQty [38] must be greater than zero, was {quantity}")));
+        }
+        if order_type == '2' && price <= Decimal::ZERO {
+            error!("Cancel/Replace price must be greater than zero for Limit order, was {price}");
+            return Err(QuickFixError::InvalidArgument(format!("Price [44] must be greater than zero, was {price}")));
+        }
+        if let Err(error) = instruments::validate_order(symbol, &[price], quantity) {
+            error!("Cancel/Replace is not valid for instrument: {error}");
+            return Err(QuickFixError::InvalidArgument(error.to_string()));
+        }
+        let quantity: String = instruments::format_quantity(symbol, quantity); // OrderQty [38]
+        let price: String = instruments::format_price(symbol, price);         // Price    [44]
+
+        let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0137={exch_order_id}\x0149={apikey}\x0156={target_comp_id}\x0111={cl_order_id}\x0141={orig_cl_order_id}\x0138={quantity}\x0140={order_type}\x0144={price}\x0152={ts}\x0154={side_int}\x0155={symbol}\x01{tif_fields}60={ts}\x01");
+        info!("Order Cancel/Replace Msg as string: {:?}", template);
+
+        // two fields are generated when Message is created
+        // body length -> BodyLength  [9]
+        // check sum   -> CheckSum   [10]
+        let msg: Result<Message, QuickFixError> = Message::try_from_text(&template);
+        match msg {
+            Ok(ref msg) => {
+                info!("Created order Cancel/Replace message -> {:?}", msg.to_fix_string());
+            }
+            Err(ref error) => {
+                error!("Error while creating Order Cancel/Replace {:?}", error);
+            }
+        }
+        msg
+    }
+    /// `order_status_request`
+    ///
+    /// OrderStatusRequest (35=H) for one order, answered with an ExecutionReport (35=8) with ExecType [150] = 'I'
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - message cannot be created from template
+    pub fn order_status_request(apikey: &str, cl_order_id: &str, exch_order_id: &str, side: Side, symbol: &str, seqnum: u32) -> Result<Message, QuickFixError> {
+
+        let begin_string: String = "FIX.4.4".to_string();  // BeginString    [8]
+        let message_type: char = 'H';                      // MsgType       [35]
+        let ts: String = generate_ts(0);        // SendingTime   [52]
+        let side_int:u32 = side_as_int(side);              // Side          [54]
+        let target_comp_id = "PT-OE";                // TargetCompID  [56] - use config value TODO
+        // OrdStatusReqID [790] - echoed on the ExecutionReport
+        let ord_status_req_id: u128 = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards!!").as_millis();
+        let exch_order_id: &str = if exch_order_id.is_empty() { "NONE" } else { exch_order_id }; // OrderID [37]
+
+        let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0137={exch_order_id}\x0149={apikey}\x0156={target_comp_id}\x0111={cl_order_id}\x0152={ts}\x0154={side_int}\x0155={symbol}\x01790={ord_status_req_id}\x01");
+        info!("Order Status Request Msg as string: {:?}", template);
+
+        // two fields are generated when Message is created
+        // body length -> BodyLength  [9]
+        // check sum   -> CheckSum   [10]
+        let msg: Result<Message, QuickFixError> = Message::try_from_text(&template);
+        match msg {
+            Ok(ref msg) => {
+                info!("Created Order Status Request message -> {:?}", msg.to_fix_string());
+            }
+            Err(ref error) => {
+                error!("Error while creating Order Status Request {:?}", error);
+            }
+        }
+        msg
+    }
+    /// `order_mass_status_request`
+    ///
+    /// OrderMassStatusRequest (35=AF) for all open orders, or the open orders for one symbol,
+    /// answered with one ExecutionReport (35=8) per order carrying MassStatusReqID [584] and TotNumReports [911]
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - message cannot be created from template
+    pub fn order_mass_status_request(apikey: &str, mass_status_req_id: &str, symbol: Option<&str>, seqnum: u32) -> Result<Message, QuickFixError> {
+
+        let begin_string: String = "FIX.4.4".to_string();  // BeginString    [8]
+        let message_type: &str = "AF";                     // MsgType       [35]
+        let ts: String = generate_ts(0);        // SendingTime   [52]
+        let target_comp_id = "PT-OE";                // TargetCompID  [56] - use config value TODO
+        // MassStatusReqType [585] - '1' orders for Symbol [55], '7' all orders
+        let scope: String = match symbol {
+            Some(symbol) => format!("585=1\x0155={symbol}\x01"),
+            None => "585=7\x01".to_string(),
+        };
+
+        let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0149={apikey}\x0156={target_comp_id}\x0152={ts}\x01584={mass_status_req_id}\x01{scope}");
+        info!("Order Mass Status Request Msg as string: {:?}", template);
+
+        // two fields are generated when Message is created
+        // body length -> BodyLength  [9]
+        // check sum   -> CheckSum   [10]
+        let msg: Result<Message, QuickFixError> = Message::try_from_text(&template);
+        match msg {
+            Ok(ref msg) => {
+                info!("Created Order Mass Status Request message -> {:?}", msg.to_fix_string());
+            }
+            Err(ref error) => {
+                error!("Error while creating Order Mass Status Request {:?}", error);
+            }
+        }
+        msg
+    }
+    /// `security_list_request`
+    ///
+    /// SecurityListRequest (35=x) for all instruments, or the instruments of one SecurityType [167] e.g. 'OPT',
+    /// answered with one or more SecurityList (35=y) messages, the last one with LastFragment [893] = 'Y'
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - message cannot be created from template
+    pub fn security_list_request(apikey: &str, security_req_id: &str, security_type: Option<&str>, seqnum: u32) -> Result<Message, QuickFixError> {
+
+        let begin_string: String = "FIX.4.4".to_string();  // BeginString    [8]
+        let message_type: char = 'x';                      // MsgType       [35]
+        let ts: String = generate_ts(0);        // SendingTime   [52]
+        let target_comp_id = "PT-OE";                // TargetCompID  [56] - use config value TODO
+        // SecurityListRequestType [559] - '1' instruments for SecurityType [167], '4' all instruments
+        let scope: String = match security_type {
+            Some(security_type) => format!("559=1\x01167={security_type}\x01"),
+            None => "559=4\x01".to_string(),
+        };
+
+        let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0149={apikey}\x0156={target_comp_id}\x0152={ts}\x01320={security_req_id}\x01{scope}");
+        info!("Security List Request Msg as string: {:?}", template);
+
+        // two fields are generated when Message is created
+        // body length -> BodyLength  [9]
+        // check sum   -> CheckSum   [10]
+        let msg: Result<Message, QuickFixError> = Message::try_from_text(&template);
+        match msg {
+            Ok(ref msg) => {
+                info!("Created Security List Request message -> {:?}", msg.to_fix_string());
+            }
+            Err(ref error) => {
+                error!("Error while creating Security List Request {:?}", error);
+            }
+        }
+        msg
+    }
+    /// `order_mass_cancel_request`
+    ///
+    /// OrderMassCancelRequest (35=q) for all orders, orders for one symbol or orders for one security type,
+    /// answered with an OrderMassCancelReport (35=r) after the ExecutionReports for the cancelled orders
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - message cannot be created from template
+    pub fn order_mass_cancel_request(apikey: &str, scope: &MassCancelScope, seqnum: u32) -> Result<Message, QuickFixError> {
+
+        let begin_string: String = "FIX.4.4".to_string();  // BeginString    [8]
+        let message_type: char = 'q';                      // MsgType       [35]
+        let ts: String = generate_ts(0);        // SendingTime   [52]
+        let target_comp_id = "PT-OE";                // TargetCompID  [56] - use config value TODO
+        let cl_order_id: String = next_cl_ord_id();        // ClOrdID       [11]
+        let request_type: char = scope.request_type();    // MassCancelRequestType [530]
+        let scope_field: String = match scope {
+            MassCancelScope::All => String::new(),
+            MassCancelScope::Symbol(symbol) => format!("55={symbol}\x01"),                 // Symbol [55]
+            MassCancelScope::SecurityType(security_type) => format!("167={security_type}\x01"), // SecurityType [167]
+        };
+
+        let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0149={apikey}\x0156={target_comp_id}\x0111={cl_order_id}\x0152={ts}\x01530={request_type}\x01{scope_field}60={ts}\x01");
+        info!("Order Mass Cancel Request Msg as string: {:?}", template);
+
+        // two fields are generated when Message is created
+        // body length -> BodyLength  [9]
+        // check sum   -> CheckSum   [10]
+        let msg: Result<Message, QuickFixError> = Message::try_from_text(&template);
+        match msg {
+            Ok(ref msg) => {
+                info!("Created Order Mass Cancel Request message -> {:?}", msg.to_fix_string());
+            }
+            Err(ref error) => {
+                error!("Error while creating Order Mass Cancel Request {:?}", error);
+            }
+        }
+        msg
+    }
     #[allow(dead_code)]
     pub fn new_cancel_order_single(orig_cl_ord_id: ClOrdID, cl_ord_id: &str, side: Side, symbol: String, text: String ) -> Result<OrderCancelRequest, QuickFixError> {
         let result: Result<OrderCancelRequest, QuickFixError> = OrderCancelRequest::try_new(
@@ -190,21 +444,102 @@ impl FixMessageFactory {
             }
         }       
     }
-    #[allow(dead_code)]
-    pub fn new_order_multi(cl_ord_id: ClOrdID, side: Side, transact_time: TransactTime,  order_type: OrdType) -> Result<NewOrderMultileg, QuickFixError> {
-        NewOrderMultileg::try_new(cl_ord_id, side, transact_time, order_type)
+    /// `new_order_multi`
+    ///
+    /// NewOrderMultileg (35=AB) for a package of legs traded at one net price, Side [54] and OrderQty [38] are those
+    /// of the package, a Sell package sells every leg's LegSide [624] the other way
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - fewer than two legs, a leg ratio is zero or less, or the same leg symbol and side appears twice
+    /// - a leg symbol is not a traded instrument, or leg quantity is below its minimum or not a multiple of its lot size
+    /// - order type is not Limit or Market, net price is missing on Limit order or set on Market order
+    /// - quantity is zero or less
+    /// - time in force is not allowed for order type, or GoodTillDate expiry is missing or in the past
+    /// - message cannot be created from template
+    pub fn new_order_multi(
+        apikey: &str, legs: &[Leg], side: Side, quantity: Decimal, price: Option<Decimal>, order_type: OrdType, time_in_force: OrderTimeInForce,
+        seqnum: u32,
+    ) -> Result<Message, QuickFixError> {
+
+        let invalid = |reason: String| {
+            error!("Multi Leg Order is not valid: {reason}");
+            Err(QuickFixError::InvalidArgument(reason))
+        };
+        if legs.len() < 2 {
+            return invalid(format!("NoLegs [555] must be at least 2, was {}", legs.len()));
+        }
+        if let Some(leg) = legs.iter().find(|leg| leg.ratio_qty <= Decimal::ZERO) {
+            return invalid(format!("LegRatioQty [623] must be greater than zero, was {} for {}", leg.ratio_qty, leg.symbol));
+        }
+        if let Some(leg) = legs
+            .iter()
+            .enumerate()
+            .find(|(i, leg)| legs[..*i].iter().any(|other| other.symbol == leg.symbol && other.side == leg.side))
+            .map(|(_, leg)| leg)
+        {
+            return invalid(format!("leg {} {:?} appears more than once", leg.symbol, leg.side));
+        }
+        match (order_type, price) {
+            (OrdType::Limit, Some(_)) | (OrdType::Market, None) => {}
+            (OrdType::Limit, None) => return invalid("Price [44] (net price) is required for Limit order".to_string()),
+            (OrdType::Market, Some(_)) => return invalid("Price [44] is not allowed for Market order".to_string()),
+            (other, _) => return invalid(format!("OrdType [40] {other:?} is not supported for multi-leg order")),
+        }
+        if quantity <= Decimal::ZERO {
+            return invalid(format!("OrderQty [38] must be greater than zero, was {quantity}"));
+        }
+        if let Err(reason) = time_in_force.validate(order_type) {
+            return invalid(reason);
+        }
+        // legs are checked with their own quantity, the net price is not a price of any leg
+        if let Some(error) =
+            legs.iter().find_map(|leg| instruments::validate_order(&leg.symbol.to_string(), &[], quantity * leg.ratio_qty).err())
+        {
+            return invalid(error.to_string());
+        }
+
+        let begin_string: String = "FIX.4.4".to_string();  // BeginString    [8]
+        let message_type: &str = "AB";                     // MsgType       [35]
+        let client_order_id: String = next_cl_ord_id();    // ClOrdId       [11]
+        let order_type: char = order_type_to_char(order_type); // OrdType   [40]
+        let side_int:u32 = side_as_int(side);              // Side          [54]
+        let ts: String = generate_ts(0);        // SendingTime   [52]
+        let target_comp_id = "PT-OE";                // TargetCompID  [56] - use config value TODO
+        let price_field: String = price.map(|price| format!("44={price}\x01")).unwrap_or_default(); // Price [44] - net price of the package, as given
+        let tif_fields: String = time_in_force.fields();   // TimeInForce   [59] + ExpireTime [126] / ExpireDate [432]
+        let no_legs: usize = legs.len();                   // NoLegs       [555]
+        let leg_fields: String = legs.iter().map(Leg::fields).collect(); // LegSymbol [600] + LegSide [624] + LegRatioQty [623]
+
+        let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0111={client_order_id}\x0138={quantity}\x0140={order_type}\x01{price_field}49={apikey}\x0152={ts}\x0154={side_int}\x0155={MULTILEG_SYMBOL}\x0156={target_comp_id}\x01{tif_fields}60={ts}\x01555={no_legs}\x01{leg_fields}");
+        info!("Multi Leg Order Msg as string: {:?}", template);
+
+        // two fields are generated when Message is created
+        // body length -> BodyLength  [9]
+        // check sum   -> CheckSum   [10]
+        let msg: Result<Message, QuickFixError> = Message::try_from_text(&template);
+        match msg {
+            Ok(ref msg) => {
+                info!("Created Multi Leg Order message -> {:?}", msg.to_fix_string());
+            }
+            Err(ref error) => {
+                error!("Error while creating Multi Leg Order {:?}", error);
+            }
+        }
+        msg
     }
 
     #[allow(dead_code)]
-    pub fn new_rfq_quote(apikey: &str, symbol: Symbol, side: Side, order_qty: OrderQty, order_type: OrdType, seqnum: u32) -> Result<Message, QuickFixError> {
+    pub fn new_rfq_quote(apikey: &str, symbol: Symbol, side: Side, order_qty: Decimal, order_type: OrdType, seqnum: u32) -> Result<Message, QuickFixError> {
         //
         // RFQ fields based on FIX 4.4 specification for new single order(MsgType='D') with 
         //
         let begin_string: String = "FIX.4.4".to_string();          // BeginString       [8] TODO - take this from .env file (or faster lookup TBD)
         let message_type: String = String::from("D");              // MsgType 'R'      [35]
         let seqnum: u32 = seqnum;                                  // SeqNum           [34]
-        let client_order_id: String = generate_ts(0);             // ClOrdID          [11]       
-        let order_quantity: OrderQty = order_qty;                  // Order Qty        [38] TODO - check alignment with qty values based on instrument rules
+        let client_order_id: String = next_cl_ord_id();           // ClOrdID          [11]
+        let order_quantity: String = instruments::format_quantity(&symbol.to_string(), order_qty); // Order Qty [38] at the instrument lot size precision
         let order_type: char = order_type_to_char(order_type);     // OrdType          [40]
         let sender_comp_id: String = apikey.to_string();           // SendCompId       [49]
         let sending_time: String = generate_ts(0);       // ts               [52]
@@ -216,7 +551,7 @@ impl FixMessageFactory {
         let symbols_sfx: String = "none".to_string();              // SymbolSfx        [65]
     
         let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0111={client_order_id}\x0138={order_quantity}\x0140={order_type}\x0149={sender_comp_id}\x0152={sending_time}\x0154={side_int}\x0155={symbol}\x0156={target_comp_id}\x0159={time_in_force}\x0160={transact_time}\x0165={symbols_sfx}\x01");
-        info!("RFQ Msg as string: {}", template.to_string());
+        info!("RFQ Msg as string: {}", template);
     
         // 
         // two fields are generated when QuickFix Message is generated from string
@@ -265,7 +600,7 @@ impl FixMessageFactory {
 
         // 8=FIX.4.2|9=49|35=0|34=4|49=SENDER_COMP_ID|56=TARGET_COMP_ID|52=20230624-14:30:00.000|10=128|
         let template: String = format!("8={begin_string}\x0135={message_type}\x0134={seqnum}\x0149={apikey}\x0152={ts}\x0156={target_comp_id}\x01");
-        info!("Heartbeat Msg as string: {}", template.to_string());
+        info!("Heartbeat Msg as string: {}", template);
 
         // 
         // two fields are generated when Message is created
@@ -1,9 +1,11 @@
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use client_rust_fix::fix::Decimal;
+use client_rust_fix::instruments::{self, Symbol};
 use jwtk::{ecdsa::{EcdsaPrivateKey, EcdsaPublicKey}, sign, HeaderAndClaims};
 use log::{info, error};
 use native_tls::{Certificate, TlsConnector, TlsStream};
-use quickfix_msg44::field_types::{OrdType, Side};
-use std::{env::var, fs::File, io::Read, net::TcpStream, thread::sleep, time::{Duration, SystemTime, UNIX_EPOCH}};
+use quickfix_msg44::field_types::{OrdType, Side, TimeInForce};
+use std::{env::var, fs::File, io::{self, ErrorKind, Read}, net::TcpStream, thread::sleep, time::{Duration, SystemTime, UNIX_EPOCH}};
 use serde_json::{Value, Map};
 use tungstenite::{client::IntoClientRequest, connect, http::HeaderValue, Message};
 use url::Url;
@@ -133,81 +135,83 @@ pub fn execute_ws_request(msg: &str) {
 
 /// connect
 ///
-/// used to carry out low level setup for opening TLS connection to power.trade server
+/// used to carry out low level setup for opening TLS connection to power.trade server on `port`
+/// e.g. order-entry and drop-copy sessions connect to their own ports
 ///
 /// # Panics
 ///
 /// Panics if certificates or settings missing
 ///
 #[allow(dead_code)]
-pub fn setup_tls_connection() -> TlsStream<TcpStream> {
+pub fn setup_tls_connection(port: &str) -> TlsStream<TcpStream> {
+    match connect_tls(port) {
+        Ok(tls_stream) => tls_stream,
+        Err(error) => {
+            error!("Error opening TLS connection on port {port} -> {error}");
+            panic!("Error opening TLS connection on port {port} -> {error}");
+        }
+    }
+}
+
+/// `connect_tls`
+///
+/// as `setup_tls_connection`, used again to reconnect a session once it has dropped
+///
+/// # Errors
+///
+/// function will return error if
+/// - PT_SERVER or PT_PUBKEY_FILE is not set, or the certificate file cannot be read or is not valid PEM
+/// - the TCP connection or TLS handshake fails
+pub fn connect_tls(port: &str) -> io::Result<TlsStream<TcpStream>> {
+
+    let failed = |context: &str, error: &dyn std::fmt::Display| io::Error::new(ErrorKind::Other, format!("{context}: {error}"));
 
     //+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
-   //
-   // >> Assign/Load Settings
-   //
-   let host: String = var("PT_SERVER").expect("Error while retrieving PT_SERVER from .env file");
-   let port: String = String::from("2021"); // TODO - move this to .env vonfig
-   info!("Connecting to Host {:?}", host);
-   let server: String = format!("{host}:{port}");
-   info!("Connecting to Endpoint {server:?}");
-
-   //+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
-   //
-   // Load the public certificate from a file
-   //
-   let pubkey_path = var("PT_PUBKEY_FILE").expect("Error while retrieving PT_PUBKEY_FILE from .env file"); //"private_cert.pem"; // TODO - take from env
-   info!("PUBKEY Path: {pubkey_path}");
-   let mut cert_file: File = File::open(pubkey_path).expect("Unable to open certificate file"); // TODO - from .env
-   let mut cert_data: Vec<u8> = Vec::new();
-   cert_file
-       .read_to_end(&mut cert_data)
-       .expect("Unable to read certificate file");
-
-   //
-   // Create Certificate object from the certificate data loaded from file
-   //
-   let cert = match Certificate::from_pem(&cert_data) {
-       Ok(cert) => {
-           info!("Found valid cert in file {cert_file:?}");
-           cert
-       }
-       Err(err) => {
-           error!("Error loading cert from PEM -> {err:?}");
-           panic!("Error loading cert from PEM -> {err:?}");
-       }
-   };
-
-   //
-   // Build instance of TLS connector
-   //
-   let connector: TlsConnector = TlsConnector::builder()
-       .danger_accept_invalid_certs(true)
-       .add_root_certificate(cert)
-       .build()
-       .expect("Failed to build TLS connector");
-   info!("TLS Connection -> {connector:?}");
-
-   //
-   // Connect to power.trade server over TCP
-   //
-   let stream = TcpStream::connect(&server).expect("Failed to connect to server");
-   info!("TLS Stream connecting to -> {server}");
-
-   //
-   // Setup TLS channel on top of TCP connection
-   //
-   let tls_stream = connector
-       .connect(&server, stream)
-       .expect("Failed to establish TLS session");
-
-   // set 5 second timerout on reads
-   tls_stream.get_ref().set_read_timeout(Some(Duration::new(5, 0))).expect("Failed to set read timeout");
-
-   info!("TLS Stream -> {tls_stream:?}");
-
-   tls_stream
+    //
+    // >> Assign/Load Settings
+    //
+    let host: String = var("PT_SERVER").map_err(|error| failed("Error while retrieving PT_SERVER from .env file", &error))?;
+    info!("Connecting to Host {:?}", host);
+    let server: String = format!("{host}:{port}");
+    info!("Connecting to Endpoint {server:?}");
+
+    //+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+    //
+    // Load the public certificate from a file
+    //
+    let pubkey_path = var("PT_PUBKEY_FILE").map_err(|error| failed("Error while retrieving PT_PUBKEY_FILE from .env file", &error))?;
+    info!("PUBKEY Path: {pubkey_path}");
+    let mut cert_data: Vec<u8> = Vec::new();
+    File::open(pubkey_path)?.read_to_end(&mut cert_data)?;
+
+    //
+    // Create Certificate object from the certificate data loaded from file
+    //
+    let cert = Certificate::from_pem(&cert_data).map_err(|error| failed("Error loading cert from PEM", &error))?;
+
+    //
+    // Build instance of TLS connector
+    //
+    let connector: TlsConnector = TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .add_root_certificate(cert)
+        .build()
+        .map_err(|error| failed("Failed to build TLS connector", &error))?;
+    info!("TLS Connection -> {connector:?}");
+
+    //
+    // Connect to power.trade server over TCP, then setup TLS channel on top of TCP connection
+    //
+    let stream = TcpStream::connect(&server)?;
+    info!("TLS Stream connecting to -> {server}");
+    let tls_stream = connector.connect(&server, stream).map_err(|error| failed("Failed to establish TLS session", &error))?;
+
+    // set 5 second timerout on reads
+    tls_stream.get_ref().set_read_timeout(Some(Duration::new(5, 0)))?;
+
+    info!("TLS Stream -> {tls_stream:?}");
 
+    Ok(tls_stream)
 }
 
 /// `get_pkey`
@@ -392,6 +396,322 @@ pub(crate) fn order_type_to_char(order_type: OrdType) -> char {
     }
 }
 
+#[allow(dead_code)]
+pub fn time_in_force_to_char(time_in_force: TimeInForce) -> char {
+    match time_in_force {
+        TimeInForce::Day => '0',
+        TimeInForce::GoodTillCancel => '1',
+        TimeInForce::AtTheOpening => '2',
+        TimeInForce::ImmediateOrCancel => '3',
+        TimeInForce::FillOrKill => '4',
+        TimeInForce::GoodTillCrossing => '5',
+        TimeInForce::GoodTillDate => '6',
+        TimeInForce::AtTheClose => '7',
+    }
+}
+
+/// `Expiry`
+///
+/// end of a GoodTillDate order - ExpireTime [126] (UTC) or ExpireDate [432]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expiry {
+    Time(DateTime<Utc>),
+    Date(NaiveDate),
+}
+
+/// `OrderTimeInForce`
+///
+/// TimeInForce [59] of an order, with the expiry for GoodTillDate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderTimeInForce {
+    pub time_in_force: TimeInForce,
+    pub expiry: Option<Expiry>,
+}
+
+impl OrderTimeInForce {
+    pub fn new(time_in_force: TimeInForce) -> Self {
+        OrderTimeInForce { time_in_force, expiry: None }
+    }
+
+    #[allow(dead_code)]
+    pub fn good_till_date(expiry: Expiry) -> Self {
+        OrderTimeInForce { time_in_force: TimeInForce::GoodTillDate, expiry: Some(expiry) }
+    }
+
+    /// `from_fix`
+    ///
+    /// TimeInForce [59] and ExpireTime [126] / ExpireDate [432] values as tracked for an order,
+    /// None when a value is not supported or cannot be parsed
+    pub fn from_fix(time_in_force: &str, expire_time: Option<&str>, expire_date: Option<&str>) -> Option<Self> {
+        let time_in_force: TimeInForce = match time_in_force {
+            "0" => TimeInForce::Day,
+            "" | "1" => TimeInForce::GoodTillCancel,
+            "3" => TimeInForce::ImmediateOrCancel,
+            "4" => TimeInForce::FillOrKill,
+            "6" => TimeInForce::GoodTillDate,
+            _ => return None,
+        };
+        let expiry: Option<Expiry> = match (expire_time, expire_date) {
+            (Some(time), _) => Some(Expiry::Time(NaiveDateTime::parse_from_str(time, "%Y%m%d-%H:%M:%S%.f").ok()?.and_utc())),
+            (None, Some(date)) => Some(Expiry::Date(NaiveDate::parse_from_str(date, "%Y%m%d").ok()?)),
+            (None, None) => None,
+        };
+        Some(OrderTimeInForce { time_in_force, expiry })
+    }
+
+    /// `validate`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - TimeInForce is not Day, GTC, IOC, FOK or GTD (not supported by power.trade)
+    /// - GoodTillDate has no expiry, or expiry is in the past
+    /// - expiry is set for any other TimeInForce
+    /// - Market order is not IOC or FOK
+    pub fn validate(&self, order_type: OrdType) -> Result<(), String> {
+        match self.time_in_force {
+            TimeInForce::Day
+            | TimeInForce::GoodTillCancel
+            | TimeInForce::ImmediateOrCancel
+            | TimeInForce::FillOrKill
+            | TimeInForce::GoodTillDate => {}
+            other => return Err(format!("TimeInForce [59] {other:?} is not supported")),
+        }
+        match (self.time_in_force, self.expiry) {
+            (TimeInForce::GoodTillDate, None) => return Err("GoodTillDate requires ExpireTime [126] or ExpireDate [432]".to_string()),
+            (TimeInForce::GoodTillDate, Some(Expiry::Time(time))) if time <= Utc::now() => {
+                return Err(format!("ExpireTime [126] {time} is in the past"))
+            }
+            (TimeInForce::GoodTillDate, Some(Expiry::Date(date))) if date < Utc::now().date_naive() => {
+                return Err(format!("ExpireDate [432] {date} is in the past"))
+            }
+            (TimeInForce::GoodTillDate, Some(_)) => {}
+            (other, Some(_)) => {
+                return Err(format!("ExpireTime [126] / ExpireDate [432] is only allowed with GoodTillDate, not {other:?}"))
+            }
+            (_, None) => {}
+        }
+        if order_type == OrdType::Market && !matches!(self.time_in_force, TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill) {
+            return Err(format!("Market order must be ImmediateOrCancel or FillOrKill, not {:?}", self.time_in_force));
+        }
+        Ok(())
+    }
+
+    /// `fields`
+    ///
+    /// TimeInForce [59] and ExpireTime [126] / ExpireDate [432] as FIX template text
+    pub fn fields(&self) -> String {
+        let time_in_force: char = time_in_force_to_char(self.time_in_force);
+        match self.expiry {
+            None => format!("59={time_in_force}\x01"),
+            Some(Expiry::Time(time)) => format!("59={time_in_force}\x01126={}\x01", time.format("%Y%m%d-%H:%M:%S%.3f")),
+            Some(Expiry::Date(date)) => format!("59={time_in_force}\x01432={}\x01", date.format("%Y%m%d")),
+        }
+    }
+}
+
+/// `TriggerPriceType`
+///
+/// TriggerPriceType [1107] - price a stop order is compared with
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerPriceType {
+    BestOffer,
+    LastTrade,
+    BestBid,
+    BestMid,
+}
+
+impl TriggerPriceType {
+    pub fn as_fix(self) -> char {
+        match self {
+            TriggerPriceType::BestOffer => '1',
+            TriggerPriceType::LastTrade => '2',
+            TriggerPriceType::BestBid => '3',
+            TriggerPriceType::BestMid => '6',
+        }
+    }
+}
+
+/// `StopTrigger`
+///
+/// StopPx [99] of a Stop or StopLimit order, with the price it is triggered by (last trade when not set)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StopTrigger {
+    pub stop_px: Decimal,
+    pub trigger_price_type: Option<TriggerPriceType>,
+}
+
+impl StopTrigger {
+    pub fn new(stop_px: Decimal) -> Self {
+        StopTrigger { stop_px, trigger_price_type: None }
+    }
+
+    pub fn triggered_by(self, trigger_price_type: TriggerPriceType) -> Self {
+        StopTrigger { trigger_price_type: Some(trigger_price_type), ..self }
+    }
+
+    /// `fields`
+    ///
+    /// StopPx [99] at the tick size precision of `symbol` and TriggerType [1100] = '4' (price movement) /
+    /// TriggerPriceType [1107] as FIX template text
+    pub fn fields(&self, symbol: &str) -> String {
+        let stop_px: String = instruments::format_price(symbol, self.stop_px);
+        match self.trigger_price_type {
+            None => format!("99={stop_px}\x01"),
+            Some(trigger_price_type) => format!("99={stop_px}\x011100=4\x011107={}\x01", trigger_price_type.as_fix()),
+        }
+    }
+}
+
+/// `SelfTradePrevention`
+///
+/// SelfMatchPreventionInstruction [2964] - what happens when an order would trade against an order of the same account
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTradePrevention {
+    /// cancel the incoming order
+    Aggressive,
+    /// cancel the resting order
+    Passive,
+    /// cancel both orders
+    Both,
+}
+
+impl SelfTradePrevention {
+    pub fn as_fix(self) -> char {
+        match self {
+            SelfTradePrevention::Aggressive => '1',
+            SelfTradePrevention::Passive => '2',
+            SelfTradePrevention::Both => '3',
+        }
+    }
+}
+
+/// `OrderOptions`
+///
+/// execution flags of a single leg order
+/// - post-only, ExecInst [18] '6' (participate don't initiate) - order is rejected instead of taking liquidity
+/// - reduce-only, ExecInst [18] 'E' (do not increase) - order is rejected when it would increase the position
+/// - self-trade prevention, SelfMatchPreventionInstruction [2964]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrderOptions {
+    pub post_only: bool,
+    pub reduce_only: bool,
+    pub self_trade_prevention: Option<SelfTradePrevention>,
+}
+
+impl OrderOptions {
+    #[allow(dead_code)]
+    pub fn post_only(self) -> Self {
+        OrderOptions { post_only: true, ..self }
+    }
+
+    #[allow(dead_code)]
+    pub fn reduce_only(self) -> Self {
+        OrderOptions { reduce_only: true, ..self }
+    }
+
+    #[allow(dead_code)]
+    pub fn self_trade_prevention(self, self_trade_prevention: SelfTradePrevention) -> Self {
+        OrderOptions { self_trade_prevention: Some(self_trade_prevention), ..self }
+    }
+
+    /// `validate`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - post-only is set on a Market or Stop order, or with ImmediateOrCancel / FillOrKill
+    pub fn validate(&self, order_type: OrdType, time_in_force: &OrderTimeInForce) -> Result<(), String> {
+        if self.post_only && matches!(order_type, OrdType::Market | OrdType::Stop) {
+            return Err(format!("post-only ExecInst [18] is not allowed for {order_type:?} order"));
+        }
+        if self.post_only && matches!(time_in_force.time_in_force, TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill) {
+            return Err(format!("post-only ExecInst [18] is not allowed with {:?}", time_in_force.time_in_force));
+        }
+        Ok(())
+    }
+
+    /// `fields`
+    ///
+    /// ExecInst [18] (space separated when both flags are set) and SelfMatchPreventionInstruction [2964] as FIX template text,
+    /// empty when no option is set
+    pub fn fields(&self) -> String {
+        let exec_inst: Vec<&str> =
+            [(self.post_only, "6"), (self.reduce_only, "E")].iter().filter(|(set, _)| *set).map(|(_, value)| *value).collect();
+        let mut fields: String = String::new();
+        if !exec_inst.is_empty() {
+            fields.push_str(&format!("18={}\x01", exec_inst.join(" ")));
+        }
+        if let Some(self_trade_prevention) = self.self_trade_prevention {
+            fields.push_str(&format!("2964={}\x01", self_trade_prevention.as_fix()));
+        }
+        fields
+    }
+}
+
+/// `Leg`
+///
+/// one leg of a multi-leg order (35=AB) - LegSymbol [600], LegSide [624] and LegRatioQty [623]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leg {
+    pub symbol: Symbol,
+    pub side: Side,
+    pub ratio_qty: Decimal,
+}
+
+impl Leg {
+    pub fn new(symbol: Symbol, side: Side, ratio_qty: Decimal) -> Self {
+        Leg { symbol, side, ratio_qty }
+    }
+
+    /// `fields`
+    ///
+    /// one entry of the NoLegs [555] group as FIX template text, LegRatioQty at the lot precision of the leg symbol
+    pub fn fields(&self) -> String {
+        let symbol: String = self.symbol.to_string();
+        let ratio_qty: String = instruments::format_quantity(&symbol, self.ratio_qty);
+        format!("600={symbol}\x01624={}\x01623={ratio_qty}\x01", side_as_int(self.side))
+    }
+}
+
+/// `validate_order_prices`
+///
+/// checks Price [44] and StopPx [99] against the order type
+///
+/// # Errors
+///
+/// function will return error if
+/// - order type is not Limit, Market, Stop or StopLimit
+/// - Price is missing or zero or less on Limit and StopLimit orders
+/// - Price is set on Market or Stop orders
+/// - StopPx is missing or zero or less on Stop and StopLimit orders
+/// - StopPx is set on Limit or Market orders
+pub fn validate_order_prices(order_type: OrdType, price: Option<Decimal>, stop: Option<&StopTrigger>) -> Result<(), String> {
+    let (needs_price, needs_stop): (bool, bool) = match order_type {
+        OrdType::Limit => (true, false),
+        OrdType::Market => (false, false),
+        OrdType::Stop => (false, true),
+        OrdType::StopLimit => (true, true),
+        other => return Err(format!("OrdType [40] {other:?} is not supported, use Limit, Market, Stop or StopLimit")),
+    };
+    match (needs_price, price) {
+        (true, Some(price)) if price <= Decimal::ZERO => {
+            return Err(format!("Price [44] must be greater than zero for {order_type:?} order, was {price}"))
+        }
+        (true, None) => return Err(format!("Price [44] is required for {order_type:?} order")),
+        (false, Some(_)) => return Err(format!("Price [44] is only allowed for Limit and StopLimit orders, not {order_type:?}")),
+        _ => {}
+    }
+    match (needs_stop, stop) {
+        (true, Some(stop)) if stop.stop_px <= Decimal::ZERO => Err(format!("StopPx [99] must be greater than zero, was {}", stop.stop_px)),
+        (true, None) => Err(format!("StopPx [99] is required for {order_type:?} order")),
+        (false, Some(_)) => Err(format!("StopPx [99] is only allowed for Stop and StopLimit orders, not {order_type:?}")),
+        _ => Ok(()),
+    }
+}
+
 #[allow(dead_code)]
 pub fn side_as_int(side: Side) -> u32 {
     match side {
@@ -439,8 +759,13 @@ mod fix_msg_tests {
 #[cfg(test)]
 mod fix_msg_enum_tests {
 
+    use chrono::{Duration, Utc};
+    use client_rust_fix::fix::Decimal;
     use quickfix_msg44::field_types::{OrdType, Side, TimeInForce};
-    use crate::utils::{order_type_to_char, side_as_int};
+    use crate::utils::{
+        order_type_to_char, side_as_int, time_in_force_to_char, validate_order_prices, Expiry, Leg, OrderOptions, OrderTimeInForce,
+        SelfTradePrevention, StopTrigger, TriggerPriceType,
+    };
 
     #[test]
     fn test_time_in_force_day() {
@@ -503,6 +828,27 @@ mod fix_msg_enum_tests {
         assert_eq!(tif, tif_clone);
     }
 
+    #[test]
+    fn test_time_in_force_validation() {
+        assert!(OrderTimeInForce::new(TimeInForce::ImmediateOrCancel).validate(OrdType::Market).is_ok());
+        assert!(OrderTimeInForce::new(TimeInForce::GoodTillCancel).validate(OrdType::Market).is_err());
+        assert!(OrderTimeInForce::new(TimeInForce::AtTheOpening).validate(OrdType::Limit).is_err());
+        assert!(OrderTimeInForce::new(TimeInForce::GoodTillDate).validate(OrdType::Limit).is_err());
+        assert!(OrderTimeInForce::good_till_date(Expiry::Time(Utc::now() - Duration::minutes(1))).validate(OrdType::Limit).is_err());
+
+        let gtd = OrderTimeInForce::good_till_date(Expiry::Date(Utc::now().date_naive() + Duration::days(1)));
+        assert!(gtd.validate(OrdType::Limit).is_ok());
+        let fields: String = gtd.fields();
+        assert!(fields.starts_with("59=6\x01432="));
+        assert_eq!(OrderTimeInForce::from_fix("6", None, Some(&fields[9..17])), Some(gtd));
+    }
+
+    #[test]
+    fn test_time_in_force_to_char() {
+        assert_eq!(time_in_force_to_char(TimeInForce::GoodTillCancel), '1');
+        assert_eq!(time_in_force_to_char(TimeInForce::GoodTillDate), '6');
+    }
+
     #[test]
     fn test_order_type_market() {
         let order_type: OrdType = OrdType::Market;
@@ -530,4 +876,40 @@ mod fix_msg_enum_tests {
         let side_int = side_as_int(side);
         assert_eq!(side_int, 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_stop_order_prices() {
+        let stop = StopTrigger::new(Decimal::from(380));
+        assert!(validate_order_prices(OrdType::Stop, None, Some(&stop)).is_ok());
+        assert!(validate_order_prices(OrdType::StopLimit, Some(Decimal::from(379)), Some(&stop)).is_ok());
+        assert!(validate_order_prices(OrdType::Stop, Some(Decimal::from(379)), Some(&stop)).is_err());
+        assert!(validate_order_prices(OrdType::StopLimit, None, Some(&stop)).is_err());
+        assert!(validate_order_prices(OrdType::Stop, None, None).is_err());
+        assert!(validate_order_prices(OrdType::Limit, Some(Decimal::from(388)), Some(&stop)).is_err());
+        assert!(validate_order_prices(OrdType::Stop, None, Some(&StopTrigger::new(Decimal::ZERO))).is_err());
+        assert_eq!(stop.triggered_by(TriggerPriceType::LastTrade).fields("SOL-USD"), "99=380\x011100=4\x011107=2\x01");
+    }
+
+    #[test]
+    fn test_order_options_exec_inst() {
+        let gtc = OrderTimeInForce::new(TimeInForce::GoodTillCancel);
+        assert_eq!(OrderOptions::default().fields(), "");
+        assert_eq!(OrderOptions::default().post_only().reduce_only().fields(), "18=6 E\x01");
+        assert_eq!(
+            OrderOptions::default().reduce_only().self_trade_prevention(SelfTradePrevention::Passive).fields(),
+            "18=E\x012964=2\x01"
+        );
+        assert!(OrderOptions::default().post_only().validate(OrdType::Limit, &gtc).is_ok());
+        assert!(OrderOptions::default()
+            .post_only()
+            .validate(OrdType::Market, &OrderTimeInForce::new(TimeInForce::ImmediateOrCancel))
+            .is_err());
+        assert!(OrderOptions::default().post_only().validate(OrdType::Limit, &OrderTimeInForce::new(TimeInForce::FillOrKill)).is_err());
+    }
+
+    #[test]
+    fn test_leg_ratio_qty_fields() {
+        let leg = Leg::new("BTC-USD".parse().unwrap(), Side::Sell, Decimal::new(1, 1) + Decimal::new(2, 1));
+        assert_eq!(leg.fields(), "600=BTC-USD\x01624=2\x01623=0.3\x01");
+    }
+}
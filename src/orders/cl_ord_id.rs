@@ -0,0 +1,178 @@
+// src/orders/cl_ord_id.rs
+//
+// ClOrdID [11] generation
+//  - `CounterGenerator` - monotonic counter persisted to a file, prefixed with a tag and the UTC date
+//  - `UuidGenerator` - UUID v4 (random) or v7 (time ordered)
+//  - `PrefixGenerator` - caller supplied prefix, process start time and an in-process counter
+//  - the generator used by the message factory is set once at startup with `set_generator`
+//
+
+use log::error;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use uuid::Uuid;
+
+/// `ClOrdIdGenerator`
+///
+/// source of ClOrdID [11] values, an id is never returned twice
+pub trait ClOrdIdGenerator: Send {
+    fn next_id(&mut self) -> String;
+}
+
+/// `CounterGenerator`
+///
+/// `<tag>-<yyyymmdd>-<n>`, last n is stored in `path` so ids do not repeat after a restart
+#[derive(Debug)]
+pub struct CounterGenerator {
+    path: PathBuf,
+    tag: String,
+    last: u64,
+}
+
+impl CounterGenerator {
+    /// `open`
+    ///
+    /// continues from the counter stored in `path`, starts from 1 when the file does not exist
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - file exists but cannot be read or does not hold a number
+    pub fn open(path: impl AsRef<Path>, tag: &str) -> io::Result<Self> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let last: u64 = match fs::read_to_string(&path) {
+            Ok(text) => text.trim().parse().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("invalid ClOrdID counter '{}' in {}", text.trim(), path.display()))
+            })?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => 0,
+            Err(error) => return Err(error),
+        };
+        Ok(CounterGenerator { path, tag: tag.to_string(), last })
+    }
+
+    pub fn last(&self) -> u64 {
+        self.last
+    }
+}
+
+impl ClOrdIdGenerator for CounterGenerator {
+    fn next_id(&mut self) -> String {
+        self.last += 1;
+        // n.b. counter is written before the id is used, a failed write is logged and the id is still unique in this run
+        if let Err(error) = fs::write(&self.path, self.last.to_string()) {
+            error!("ClOrdID counter {} not saved to {}: {error}", self.last, self.path.display());
+        }
+        format!("{}-{}-{}", self.tag, chrono::Utc::now().format("%Y%m%d"), self.last)
+    }
+}
+
+/// `UuidVersion`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UuidVersion {
+    /// random
+    V4,
+    /// unix time ordered, sorts in creation order
+    V7,
+}
+
+/// `UuidGenerator`
+#[derive(Debug, Clone, Copy)]
+pub struct UuidGenerator {
+    version: UuidVersion,
+}
+
+impl UuidGenerator {
+    pub fn new(version: UuidVersion) -> Self {
+        UuidGenerator { version }
+    }
+}
+
+impl ClOrdIdGenerator for UuidGenerator {
+    fn next_id(&mut self) -> String {
+        match self.version {
+            UuidVersion::V4 => Uuid::new_v4().to_string(),
+            UuidVersion::V7 => Uuid::now_v7().to_string(),
+        }
+    }
+}
+
+/// `PrefixGenerator`
+///
+/// `<prefix><start millis>-<n>`, start millis tells restarts of the same prefix apart
+#[derive(Debug, Clone)]
+pub struct PrefixGenerator {
+    prefix: String,
+    start: u128,
+    last: u64,
+}
+
+impl PrefixGenerator {
+    pub fn new(prefix: &str) -> Self {
+        let start: u128 = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards!!").as_millis();
+        PrefixGenerator { prefix: prefix.to_string(), start, last: 0 }
+    }
+}
+
+impl ClOrdIdGenerator for PrefixGenerator {
+    fn next_id(&mut self) -> String {
+        self.last += 1;
+        format!("{}{}-{}", self.prefix, self.start, self.last)
+    }
+}
+
+static GENERATOR: Mutex<Option<Box<dyn ClOrdIdGenerator>>> = Mutex::new(None);
+
+/// `set_generator`
+///
+/// replaces the generator used by `next_cl_ord_id`
+pub fn set_generator(generator: Box<dyn ClOrdIdGenerator>) {
+    *GENERATOR.lock().unwrap() = Some(generator);
+}
+
+/// `next_cl_ord_id`
+///
+/// next id from the generator set with `set_generator`, UUID v7 when none was set
+pub fn next_cl_ord_id() -> String {
+    GENERATOR.lock().unwrap().get_or_insert_with(|| Box::new(UuidGenerator::new(UuidVersion::V7))).next_id()
+}
+
+#[cfg(test)]
+mod cl_ord_id_tests {
+
+    use super::{ClOrdIdGenerator, CounterGenerator, PrefixGenerator, UuidGenerator, UuidVersion};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_counter_continues_after_restart() {
+        let path = std::env::temp_dir().join(format!("cl_ord_id_test_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut generator = CounterGenerator::open(&path, "S1").unwrap();
+        let first: String = generator.next_id();
+        assert!(first.starts_with("S1-") && first.ends_with("-1"));
+        generator.next_id();
+
+        let mut generator = CounterGenerator::open(&path, "S1").unwrap();
+        assert_eq!(generator.last(), 2);
+        assert!(generator.next_id().ends_with("-3"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_ids_are_unique() {
+        let mut generators: Vec<Box<dyn ClOrdIdGenerator>> = vec![
+            Box::new(UuidGenerator::new(UuidVersion::V4)),
+            Box::new(UuidGenerator::new(UuidVersion::V7)),
+            Box::new(PrefixGenerator::new("BOT-")),
+        ];
+        for generator in &mut generators {
+            let ids: HashSet<String> = (0..1000).map(|_| generator.next_id()).collect();
+            assert_eq!(ids.len(), 1000);
+        }
+        assert!(PrefixGenerator::new("BOT-").next_id().starts_with("BOT-"));
+    }
+}
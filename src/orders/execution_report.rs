@@ -0,0 +1,254 @@
+// src/orders/execution_report.rs
+//
+// ExecutionReport (35=8) decoded into typed fields
+//  - ExecType [150] and OrdStatus [39] are required, every other field is optional as reports differ by ExecType,
+//    e.g. a mass status report for no open orders carries no ClOrdID [11], Symbol [55] or Side [54]
+//  - Side [54], OrdType [40] and TimeInForce [59] decode into the quickfix enums orders are created from,
+//    the reverse of `side_as_int` / `order_type_to_char`
+//  - quantities and prices are `Decimal`s, TransactTime [60] / SendingTime [52] / ExpireTime [126] UTC timestamps
+//  - a tag holding a value that cannot be decoded is reported as `OrderError::InvalidReport` with its tag
+//
+
+use super::{parse_char, parse_decimal, ExecType, OrdRejReason, OrdStatus, OrderError};
+use crate::fix::{parse_utc_timestamp, Decimal, RawMessage};
+use chrono::{DateTime, NaiveDate, Utc};
+use quickfix_msg44::field_types::{OrdType, Side, TimeInForce};
+
+/// `ExecutionReport`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionReport {
+    /// ClOrdID [11]
+    pub cl_ord_id: Option<String>,
+    /// OrigClOrdID [41] of a cancel or cancel/replace
+    pub orig_cl_ord_id: Option<String>,
+    /// OrderID [37], 'NONE' for orders rejected before being accepted
+    pub order_id: Option<String>,
+    /// ExecID [17]
+    pub exec_id: Option<String>,
+    pub exec_type: ExecType,
+    pub ord_status: OrdStatus,
+    pub symbol: Option<String>,
+    pub side: Option<Side>,
+    pub ord_type: Option<OrdType>,
+    pub time_in_force: Option<TimeInForce>,
+    /// OrderQty [38]
+    pub order_qty: Option<Decimal>,
+    /// Price [44]
+    pub price: Option<Decimal>,
+    /// StopPx [99]
+    pub stop_px: Option<Decimal>,
+    /// LastQty [32] of a fill
+    pub last_qty: Option<Decimal>,
+    /// LastPx [31] of a fill
+    pub last_px: Option<Decimal>,
+    /// CumQty [14]
+    pub cum_qty: Option<Decimal>,
+    /// LeavesQty [151]
+    pub leaves_qty: Option<Decimal>,
+    /// AvgPx [6]
+    pub avg_px: Option<Decimal>,
+    /// OrdRejReason [103]
+    pub ord_rej_reason: Option<OrdRejReason>,
+    /// Text [58]
+    pub text: Option<String>,
+    /// TransactTime [60]
+    pub transact_time: Option<DateTime<Utc>>,
+    /// SendingTime [52]
+    pub sending_time: Option<DateTime<Utc>>,
+    /// ExpireTime [126] of a GoodTillDate order
+    pub expire_time: Option<DateTime<Utc>>,
+    /// ExpireDate [432] of a GoodTillDate order
+    pub expire_date: Option<NaiveDate>,
+}
+
+impl ExecutionReport {
+    /// `decode`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - msg is not an ExecutionReport, the error names MsgType [35]
+    /// - ExecType [150] or OrdStatus [39] is missing
+    /// - a tag holds a value that cannot be decoded, e.g. Side [54] = 'X', LastPx [31] = 'abc' or
+    ///   TransactTime [60] not a UTC timestamp
+    pub fn decode(msg: &RawMessage) -> Result<ExecutionReport, OrderError> {
+        if msg.msg_type() != "8" {
+            return Err(OrderError::InvalidReport { tag: 35, value: Some(msg.msg_type().to_string()) });
+        }
+        let text = |tag: u32| msg.get(tag).filter(|value| !value.is_empty()).map(str::to_string);
+        let timestamp = |tag: u32| match msg.get(tag) {
+            None => Ok(None),
+            Some(value) => parse_utc_timestamp(value).map(Some).ok_or(OrderError::InvalidReport { tag, value: Some(value.to_string()) }),
+        };
+        Ok(ExecutionReport {
+            cl_ord_id: text(11),
+            orig_cl_ord_id: text(41),
+            order_id: text(37),
+            exec_id: text(17),
+            exec_type: parse_char(msg, 150, ExecType::from_fix)?.ok_or(OrderError::InvalidReport { tag: 150, value: None })?,
+            ord_status: parse_char(msg, 39, OrdStatus::from_fix)?.ok_or(OrderError::InvalidReport { tag: 39, value: None })?,
+            symbol: text(55),
+            side: parse_char(msg, 54, side_from_fix)?,
+            ord_type: parse_char(msg, 40, ord_type_from_fix)?,
+            time_in_force: parse_char(msg, 59, time_in_force_from_fix)?,
+            order_qty: parse_decimal(msg, 38)?,
+            price: parse_decimal(msg, 44)?,
+            stop_px: parse_decimal(msg, 99)?,
+            last_qty: parse_decimal(msg, 32)?,
+            last_px: parse_decimal(msg, 31)?,
+            cum_qty: parse_decimal(msg, 14)?,
+            leaves_qty: parse_decimal(msg, 151)?,
+            avg_px: parse_decimal(msg, 6)?,
+            ord_rej_reason: match msg.get(103) {
+                None => None,
+                Some(value) => Some(OrdRejReason::from_fix(
+                    value.parse().map_err(|_| OrderError::InvalidReport { tag: 103, value: Some(value.to_string()) })?,
+                )),
+            },
+            text: text(58),
+            transact_time: timestamp(60)?,
+            sending_time: timestamp(52)?,
+            expire_time: timestamp(126)?,
+            expire_date: match msg.get(432) {
+                None => None,
+                Some(value) => Some(
+                    NaiveDate::parse_from_str(value, "%Y%m%d")
+                        .map_err(|_| OrderError::InvalidReport { tag: 432, value: Some(value.to_string()) })?,
+                ),
+            },
+        })
+    }
+
+    /// true for fills and partial fills, ExecType [150] = 'F'
+    pub fn is_fill(&self) -> bool {
+        self.exec_type == ExecType::Trade
+    }
+}
+
+/// `side_from_fix`
+///
+/// Side [54] from its FIX char, '1' = Buy, '2' = Sell
+pub fn side_from_fix(value: char) -> Option<Side> {
+    match value {
+        '1' => Some(Side::Buy),
+        '2' => Some(Side::Sell),
+        '3' => Some(Side::BuyMinus),
+        '4' => Some(Side::SellPlus),
+        '5' => Some(Side::SellShort),
+        '6' => Some(Side::SellShortExempt),
+        '7' => Some(Side::Undisclosed),
+        '8' => Some(Side::Cross),
+        '9' => Some(Side::CrossShort),
+        'A' => Some(Side::CrossShortExempt),
+        'B' => Some(Side::AsDefined),
+        'C' => Some(Side::Opposite),
+        'D' => Some(Side::Subscribe),
+        'E' => Some(Side::Redeem),
+        'F' => Some(Side::Lend),
+        'G' => Some(Side::Borrow),
+        _ => None,
+    }
+}
+
+/// `ord_type_from_fix`
+///
+/// OrdType [40] from its FIX char, '1' = Market, '2' = Limit, '3' = Stop, '4' = StopLimit
+pub fn ord_type_from_fix(value: char) -> Option<OrdType> {
+    match value {
+        '1' => Some(OrdType::Market),
+        '2' => Some(OrdType::Limit),
+        '3' => Some(OrdType::Stop),
+        '4' => Some(OrdType::StopLimit),
+        '6' => Some(OrdType::WithOrWithout),
+        '7' => Some(OrdType::LimitOrBetter),
+        '8' => Some(OrdType::LimitWithOrWithout),
+        '9' => Some(OrdType::OnBasis),
+        'D' => Some(OrdType::PreviouslyQuoted),
+        'E' => Some(OrdType::PreviouslyIndicated),
+        'G' => Some(OrdType::ForexSwap),
+        'I' => Some(OrdType::Funari),
+        'J' => Some(OrdType::MarketIfTouched),
+        'K' => Some(OrdType::MarketWithLeftOverAsLimit),
+        'L' => Some(OrdType::PreviousFundValuationPoint),
+        'M' => Some(OrdType::NextFundValuationPoint),
+        'P' => Some(OrdType::Pegged),
+        _ => None,
+    }
+}
+
+/// `time_in_force_from_fix`
+///
+/// TimeInForce [59] from its FIX char, '0' = Day, '1' = GTC, '3' = IOC, '4' = FOK, '6' = GTD
+pub fn time_in_force_from_fix(value: char) -> Option<TimeInForce> {
+    match value {
+        '0' => Some(TimeInForce::Day),
+        '1' => Some(TimeInForce::GoodTillCancel),
+        '2' => Some(TimeInForce::AtTheOpening),
+        '3' => Some(TimeInForce::ImmediateOrCancel),
+        '4' => Some(TimeInForce::FillOrKill),
+        '5' => Some(TimeInForce::GoodTillCrossing),
+        '6' => Some(TimeInForce::GoodTillDate),
+        '7' => Some(TimeInForce::AtTheClose),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod execution_report_tests {
+
+    use super::ExecutionReport;
+    use crate::fix::{Decimal, RawMessage};
+    use crate::orders::{ExecType, OrdStatus, OrderError};
+    use quickfix_msg44::field_types::{OrdType, Side, TimeInForce};
+
+    fn fill() -> RawMessage {
+        let mut msg = RawMessage::new("8");
+        msg.set(11, "C1")
+            .set(37, "O1")
+            .set(17, "E1")
+            .set(150, 'F')
+            .set(39, '1')
+            .set(55, "SOL-USD")
+            .set(54, 2)
+            .set(40, 2)
+            .set(59, 1)
+            .set(38, "2.00")
+            .set(44, "388.50")
+            .set(32, "0.5")
+            .set(31, "388.5")
+            .set(14, "0.5")
+            .set(151, "1.50")
+            .set(6, "388.5")
+            .set(58, "partial")
+            .set(60, "20240628-12:30:00.123456789");
+        msg
+    }
+
+    #[test]
+    fn test_decode_typed_fields_and_name_offending_tag() {
+        let report = ExecutionReport::decode(&fill()).unwrap();
+        assert_eq!((report.exec_type, report.ord_status), (ExecType::Trade, OrdStatus::PartiallyFilled));
+        assert_eq!(
+            (report.side, report.ord_type, report.time_in_force),
+            (Some(Side::Sell), Some(OrdType::Limit), Some(TimeInForce::GoodTillCancel))
+        );
+        assert_eq!((report.last_qty, report.leaves_qty), (Some(Decimal::new(5, 1)), Some(Decimal::new(150, 2))));
+        assert_eq!(report.price.map(|price| price.to_string()), Some("388.50".to_string()));
+        assert_eq!(report.transact_time.map(|time| time.timestamp_subsec_nanos()), Some(123_456_789));
+        assert_eq!((report.text.as_deref(), report.orig_cl_ord_id.as_deref()), (Some("partial"), None));
+        assert!(report.is_fill());
+
+        let decode_with = |tag: u32, value: &str| {
+            let mut msg = fill();
+            msg.set(tag, value);
+            ExecutionReport::decode(&msg)
+        };
+        assert_eq!(decode_with(54, "X"), Err(OrderError::InvalidReport { tag: 54, value: Some("X".to_string()) }));
+        assert_eq!(decode_with(31, "abc"), Err(OrderError::InvalidReport { tag: 31, value: Some("abc".to_string()) }));
+        assert_eq!(decode_with(60, "yesterday"), Err(OrderError::InvalidReport { tag: 60, value: Some("yesterday".to_string()) }));
+        assert!(matches!(ExecutionReport::decode(&RawMessage::new("9")), Err(OrderError::InvalidReport { tag: 35, .. })));
+        let mut missing = RawMessage::new("8");
+        missing.set(150, '0');
+        assert_eq!(ExecutionReport::decode(&missing), Err(OrderError::InvalidReport { tag: 39, value: None }));
+    }
+}
@@ -0,0 +1,185 @@
+// src/orders/mass_cancel.rs
+//
+// OrderMassCancelRequest (35=q) scopes and OrderMassCancelReport (35=r)
+//  - MassCancelRequestType [530] '7' all orders, '1' orders for Symbol [55], '5' orders for SecurityType [167]
+//  - every cancelled order is also reported with its own ExecutionReport (35=8), which updates order state
+//
+
+use super::OrderError;
+use crate::fix::RawMessage;
+use std::{fmt, str::FromStr};
+
+/// `MassCancelScope`
+///
+/// orders cancelled by one OrderMassCancelRequest (35=q)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MassCancelScope {
+    All,
+    Symbol(String),
+    SecurityType(String),
+}
+
+impl MassCancelScope {
+    /// MassCancelRequestType [530]
+    pub fn request_type(&self) -> char {
+        match self {
+            MassCancelScope::All => '7',
+            MassCancelScope::Symbol(_) => '1',
+            MassCancelScope::SecurityType(_) => '5',
+        }
+    }
+
+    /// `apply_to`
+    ///
+    /// sets MassCancelRequestType [530] and Symbol [55] / SecurityType [167] on request
+    pub fn apply_to(&self, msg: &mut RawMessage) {
+        msg.set(530, self.request_type());
+        match self {
+            MassCancelScope::All => {}
+            MassCancelScope::Symbol(symbol) => {
+                msg.set(55, symbol);
+            }
+            MassCancelScope::SecurityType(security_type) => {
+                msg.set(167, security_type);
+            }
+        }
+    }
+}
+
+impl FromStr for MassCancelScope {
+    type Err = String;
+
+    /// parses `all`, `symbol=<symbol>` or `type=<security type>`
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        match scope.trim().split_once('=') {
+            None if scope.trim().eq_ignore_ascii_case("all") => Ok(MassCancelScope::All),
+            Some(("symbol", symbol)) if !symbol.trim().is_empty() => Ok(MassCancelScope::Symbol(symbol.trim().to_string())),
+            Some(("type", security_type)) if !security_type.trim().is_empty() => {
+                Ok(MassCancelScope::SecurityType(security_type.trim().to_string()))
+            }
+            _ => Err(format!("invalid mass cancel scope '{scope}', expected all, symbol=<symbol> or type=<security type>")),
+        }
+    }
+}
+
+impl fmt::Display for MassCancelScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MassCancelScope::All => write!(f, "all"),
+            MassCancelScope::Symbol(symbol) => write!(f, "symbol={symbol}"),
+            MassCancelScope::SecurityType(security_type) => write!(f, "type={security_type}"),
+        }
+    }
+}
+
+/// `AffectedOrder`
+///
+/// one entry of the NoAffectedOrders [534] group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AffectedOrder {
+    /// OrigClOrdID [41]
+    pub orig_cl_ord_id: Option<String>,
+    /// AffectedOrderID [535]
+    pub order_id: Option<String>,
+}
+
+/// `MassCancelReport`
+///
+/// OrderMassCancelReport (35=r)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MassCancelReport {
+    /// ClOrdID [11] of the request
+    pub cl_ord_id: String,
+    /// OrderID [37] of the request as assigned by the venue
+    pub order_id: Option<String>,
+    /// MassCancelResponse [531] - '0' when request was rejected, otherwise the MassCancelRequestType [530]
+    pub response: char,
+    /// MassCancelRejectReason [532]
+    pub reject_reason: Option<String>,
+    /// TotalAffectedOrders [533]
+    pub total_affected: usize,
+    pub affected: Vec<AffectedOrder>,
+    pub text: Option<String>,
+}
+
+impl MassCancelReport {
+    /// `from_raw`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - msg is not an OrderMassCancelReport (35=r)
+    /// - ClOrdID [11] or MassCancelResponse [531] is missing
+    /// - MassCancelResponse [531] or TotalAffectedOrders [533] is not valid
+    pub fn from_raw(msg: &RawMessage) -> Result<Self, OrderError> {
+        if msg.msg_type() != "r" {
+            return Err(OrderError::InvalidReport { tag: 35, value: Some(msg.msg_type().to_string()) });
+        }
+        let cl_ord_id: &str = msg.get(11).ok_or(OrderError::InvalidReport { tag: 11, value: None })?;
+        let response: &str = msg.get(531).ok_or(OrderError::InvalidReport { tag: 531, value: None })?;
+        let response: char = match response.as_bytes() {
+            [c @ (b'0' | b'1' | b'2' | b'3' | b'4' | b'5' | b'6' | b'7')] => char::from(*c),
+            _ => return Err(OrderError::InvalidReport { tag: 531, value: Some(response.to_string()) }),
+        };
+        let total_affected: usize = match msg.get(533) {
+            None => 0,
+            Some(value) => value.parse().map_err(|_| OrderError::InvalidReport { tag: 533, value: Some(value.to_string()) })?,
+        };
+
+        // NoAffectedOrders [534] - each entry starts with OrigClOrdID [41] or AffectedOrderID [535]
+        let mut affected: Vec<AffectedOrder> = Vec::new();
+        let group = msg.fields().iter().skip_while(|(tag, _)| *tag != 534).skip(1);
+        for (tag, value) in group {
+            match tag {
+                41 => affected.push(AffectedOrder { orig_cl_ord_id: Some(value.clone()), order_id: None }),
+                535 => match affected.last_mut() {
+                    Some(entry) if entry.order_id.is_none() => entry.order_id = Some(value.clone()),
+                    _ => affected.push(AffectedOrder { orig_cl_ord_id: None, order_id: Some(value.clone()) }),
+                },
+                _ => {}
+            }
+        }
+
+        Ok(MassCancelReport {
+            cl_ord_id: cl_ord_id.to_string(),
+            order_id: msg.get(37).map(str::to_string),
+            response,
+            reject_reason: msg.get(532).map(str::to_string),
+            total_affected,
+            affected,
+            text: msg.get(58).map(str::to_string),
+        })
+    }
+
+    pub fn is_rejected(&self) -> bool {
+        self.response == '0'
+    }
+}
+
+#[cfg(test)]
+mod mass_cancel_tests {
+
+    use super::{AffectedOrder, MassCancelReport, MassCancelScope};
+    use crate::fix::RawMessage;
+
+    #[test]
+    fn test_scope_and_report_with_affected_orders() {
+        assert_eq!("symbol=SOL-USD".parse::<MassCancelScope>(), Ok(MassCancelScope::Symbol("SOL-USD".to_string())));
+        assert_eq!("ALL".parse::<MassCancelScope>(), Ok(MassCancelScope::All));
+        assert!("symbol=".parse::<MassCancelScope>().is_err());
+
+        let mut msg = RawMessage::new("r");
+        msg.set(11, "MC-1").set(37, "MC-SIM-1").set(530, 7).set(531, 7).set(533, 2).set(534, 2);
+        msg.push(41, "C1").push(535, "O1").push(535, "O2");
+        let report = MassCancelReport::from_raw(&msg).unwrap();
+        assert!(!report.is_rejected());
+        assert_eq!(report.total_affected, 2);
+        assert_eq!(
+            report.affected,
+            vec![
+                AffectedOrder { orig_cl_ord_id: Some("C1".to_string()), order_id: Some("O1".to_string()) },
+                AffectedOrder { orig_cl_ord_id: None, order_id: Some("O2".to_string()) },
+            ]
+        );
+    }
+}
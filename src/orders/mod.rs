@@ -0,0 +1,979 @@
+// src/orders/mod.rs
+//
+// Order state machine shared by the order-entry and drop-copy sessions
+//  - ExecutionReports (35=8) from either session are applied to one `Order` per OrderID [37] / ClOrdID [11]
+//  - OrderCancelReject (35=9) restores the status reported for the order
+//  - cancel/replace (35=G) keeps one `Order`, reachable by every ClOrdID it had
+//  - OrdStatus [39] transitions are checked against FIX 4.4 rules, illegal transitions are rejected
+//  - reports seen on both sessions (same ExecID [17]) are applied once
+//  - orders placed from other tools or accounts appear through drop-copy only
+//  - every change is published to listeners as an `OrderUpdate`
+//  - status reports (ExecType [150] = 'I') are applied like any other report, see `reconcile` for mass status
+//  - mass cancel reports (35=r) are not applied, the ExecutionReports for each cancelled order are
+//  - multi-leg orders (35=AB) keep their legs, leg fills (MultiLegReportingType [442] = '2') update the leg only
+//  - orders stopped before being sent (e.g. by the pre-trade `RiskGate`) are marked Rejected with `reject_local`
+//  - quantities and prices, e.g. CumQty [14] and AvgPx [6], are kept as reported in `Decimal`s
+//  - `ExecutionReport` decodes a 35=8 into typed fields for callers outside order state, see execution_report.rs
+//
+
+pub mod cl_ord_id;
+mod execution_report;
+mod mass_cancel;
+mod multileg;
+mod status;
+
+pub use execution_report::{ord_type_from_fix, side_from_fix, time_in_force_from_fix, ExecutionReport};
+pub use mass_cancel::{AffectedOrder, MassCancelReport, MassCancelScope};
+pub use multileg::{OrderLeg, MULTILEG_SYMBOL};
+pub use status::MassStatusSnapshot;
+
+use crate::fix::{Decimal, RawMessage};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+};
+
+/// order state shared between session reader threads and scenarios
+pub type SharedOrderManager = Arc<Mutex<OrderManager>>;
+
+/// `OrdStatus`
+///
+/// OrdStatus [39] values tracked by `OrderManager`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrdStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    /// no longer sent by FIX 4.4 venues, treated as working
+    Replaced,
+    PendingCancel,
+    Rejected,
+    PendingNew,
+    Expired,
+    PendingReplace,
+}
+
+impl OrdStatus {
+    pub fn from_fix(value: char) -> Option<OrdStatus> {
+        match value {
+            '0' => Some(OrdStatus::New),
+            '1' => Some(OrdStatus::PartiallyFilled),
+            '2' => Some(OrdStatus::Filled),
+            '4' => Some(OrdStatus::Canceled),
+            '5' => Some(OrdStatus::Replaced),
+            '6' => Some(OrdStatus::PendingCancel),
+            '8' => Some(OrdStatus::Rejected),
+            'A' => Some(OrdStatus::PendingNew),
+            'C' => Some(OrdStatus::Expired),
+            'E' => Some(OrdStatus::PendingReplace),
+            _ => None,
+        }
+    }
+
+    pub fn as_fix(self) -> char {
+        match self {
+            OrdStatus::New => '0',
+            OrdStatus::PartiallyFilled => '1',
+            OrdStatus::Filled => '2',
+            OrdStatus::Canceled => '4',
+            OrdStatus::Replaced => '5',
+            OrdStatus::PendingCancel => '6',
+            OrdStatus::Rejected => '8',
+            OrdStatus::PendingNew => 'A',
+            OrdStatus::Expired => 'C',
+            OrdStatus::PendingReplace => 'E',
+        }
+    }
+
+    /// `is_terminal`
+    ///
+    /// true when no further ExecutionReports are expected for the order
+    pub fn is_terminal(self) -> bool {
+        matches!(self, OrdStatus::Filled | OrdStatus::Canceled | OrdStatus::Rejected | OrdStatus::Expired)
+    }
+
+    /// `can_transition_to`
+    ///
+    /// FIX 4.4 OrdStatus transitions
+    /// - terminal states are final, a repeated terminal status (e.g. status request) is allowed
+    /// - Rejected only before the order was acknowledged as New
+    /// - New only from pending/replaced states, never after a fill
+    pub fn can_transition_to(self, next: OrdStatus) -> bool {
+        if self == next {
+            return true;
+        }
+        if self.is_terminal() {
+            return false;
+        }
+        match next {
+            OrdStatus::PendingNew => false,
+            OrdStatus::Rejected => matches!(self, OrdStatus::PendingNew | OrdStatus::New),
+            OrdStatus::New => {
+                matches!(self, OrdStatus::PendingNew | OrdStatus::PendingCancel | OrdStatus::PendingReplace | OrdStatus::Replaced)
+            }
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for OrdStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `ExecType`
+///
+/// ExecType [150] values tracked by `OrderManager`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecType {
+    New,
+    Canceled,
+    Replaced,
+    PendingCancel,
+    Rejected,
+    PendingNew,
+    Expired,
+    PendingReplace,
+    /// fill or partial fill, see LastQty [32] and LastPx [31]
+    Trade,
+    /// response to a status request, no change to the order
+    OrderStatus,
+    /// Stop / StopLimit order reached its StopPx [99] and is now working in the book
+    Triggered,
+}
+
+impl ExecType {
+    pub fn from_fix(value: char) -> Option<ExecType> {
+        match value {
+            '0' => Some(ExecType::New),
+            '4' => Some(ExecType::Canceled),
+            '5' => Some(ExecType::Replaced),
+            '6' => Some(ExecType::PendingCancel),
+            '8' => Some(ExecType::Rejected),
+            'A' => Some(ExecType::PendingNew),
+            'C' => Some(ExecType::Expired),
+            'E' => Some(ExecType::PendingReplace),
+            'F' => Some(ExecType::Trade),
+            'I' => Some(ExecType::OrderStatus),
+            'L' => Some(ExecType::Triggered),
+            _ => None,
+        }
+    }
+
+    pub fn as_fix(self) -> char {
+        match self {
+            ExecType::New => '0',
+            ExecType::Canceled => '4',
+            ExecType::Replaced => '5',
+            ExecType::PendingCancel => '6',
+            ExecType::Rejected => '8',
+            ExecType::PendingNew => 'A',
+            ExecType::Expired => 'C',
+            ExecType::PendingReplace => 'E',
+            ExecType::Trade => 'F',
+            ExecType::OrderStatus => 'I',
+            ExecType::Triggered => 'L',
+        }
+    }
+}
+
+/// `CxlRejResponseTo`
+///
+/// CxlRejResponseTo [434] of an OrderCancelReject (35=9)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CxlRejResponseTo {
+    /// '1' - OrderCancelRequest (35=F) was rejected
+    OrderCancelRequest,
+    /// '2' - OrderCancelReplaceRequest (35=G) was rejected
+    OrderCancelReplaceRequest,
+}
+
+impl CxlRejResponseTo {
+    pub fn from_fix(value: char) -> Option<CxlRejResponseTo> {
+        match value {
+            '1' => Some(CxlRejResponseTo::OrderCancelRequest),
+            '2' => Some(CxlRejResponseTo::OrderCancelReplaceRequest),
+            _ => None,
+        }
+    }
+}
+
+/// `OrdRejReason`
+///
+/// OrdRejReason [103] of a rejected order, values from 100 are power.trade specific rejections of order options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrdRejReason {
+    BrokerOption,
+    UnknownSymbol,
+    ExchangeClosed,
+    OrderExceedsLimit,
+    TooLateToEnter,
+    UnknownOrder,
+    DuplicateOrder,
+    UnsupportedOrderCharacteristic,
+    IncorrectQuantity,
+    /// '100' - post-only order would have taken liquidity
+    PostOnlyWouldTake,
+    /// '101' - reduce-only order would have increased the position
+    ReduceOnlyWouldIncrease,
+    /// '102' - order would have traded against an order of the same account
+    SelfTradePrevented,
+    /// '99' or any value not listed above
+    Other(u32),
+}
+
+impl OrdRejReason {
+    pub fn from_fix(value: u32) -> OrdRejReason {
+        match value {
+            0 => OrdRejReason::BrokerOption,
+            1 => OrdRejReason::UnknownSymbol,
+            2 => OrdRejReason::ExchangeClosed,
+            3 => OrdRejReason::OrderExceedsLimit,
+            4 => OrdRejReason::TooLateToEnter,
+            5 => OrdRejReason::UnknownOrder,
+            6 => OrdRejReason::DuplicateOrder,
+            11 => OrdRejReason::UnsupportedOrderCharacteristic,
+            13 => OrdRejReason::IncorrectQuantity,
+            100 => OrdRejReason::PostOnlyWouldTake,
+            101 => OrdRejReason::ReduceOnlyWouldIncrease,
+            102 => OrdRejReason::SelfTradePrevented,
+            other => OrdRejReason::Other(other),
+        }
+    }
+
+    pub fn as_fix(self) -> u32 {
+        match self {
+            OrdRejReason::BrokerOption => 0,
+            OrdRejReason::UnknownSymbol => 1,
+            OrdRejReason::ExchangeClosed => 2,
+            OrdRejReason::OrderExceedsLimit => 3,
+            OrdRejReason::TooLateToEnter => 4,
+            OrdRejReason::UnknownOrder => 5,
+            OrdRejReason::DuplicateOrder => 6,
+            OrdRejReason::UnsupportedOrderCharacteristic => 11,
+            OrdRejReason::IncorrectQuantity => 13,
+            OrdRejReason::PostOnlyWouldTake => 100,
+            OrdRejReason::ReduceOnlyWouldIncrease => 101,
+            OrdRejReason::SelfTradePrevented => 102,
+            OrdRejReason::Other(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for OrdRejReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrdRejReason::Other(value) => write!(f, "Other({value})"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+/// `ReportSource`
+///
+/// session an ExecutionReport was received on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportSource {
+    OrderEntry,
+    DropCopy,
+    /// order registered locally before it was sent
+    Local,
+}
+
+impl fmt::Display for ReportSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportSource::OrderEntry => write!(f, "order-entry"),
+            ReportSource::DropCopy => write!(f, "drop-copy"),
+            ReportSource::Local => write!(f, "local"),
+        }
+    }
+}
+
+/// `OrderError`
+///
+/// ExecutionReports that cannot be applied to order state
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderError {
+    /// tag missing or holding a value that is not supported
+    InvalidReport { tag: u32, value: Option<String> },
+    /// OrdStatus [39] change not allowed by FIX 4.4, e.g. Filled -> New
+    IllegalTransition { cl_ord_id: String, from: OrdStatus, to: OrdStatus },
+    /// CumQty [14] lower than already reported
+    CumQtyDecreased { cl_ord_id: String, from: Decimal, to: Decimal },
+    /// no order known for ClOrdID / OrderID
+    UnknownOrder(String),
+    /// ClOrdID [11] of a new or replace request already used by an order
+    DuplicateClOrdId(String),
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::InvalidReport { tag, value: Some(value) } => {
+                write!(f, "invalid ExecutionReport - unsupported value '{value}' for tag {tag}")
+            }
+            OrderError::InvalidReport { tag, value: None } => write!(f, "invalid ExecutionReport - tag {tag} is missing"),
+            OrderError::IllegalTransition { cl_ord_id, from, to } => {
+                write!(f, "illegal OrdStatus transition {from} -> {to} for ClOrdID {cl_ord_id}")
+            }
+            OrderError::CumQtyDecreased { cl_ord_id, from, to } => {
+                write!(f, "CumQty decreased from {from} to {to} for ClOrdID {cl_ord_id}")
+            }
+            OrderError::UnknownOrder(id) => write!(f, "no order known for id {id}"),
+            OrderError::DuplicateClOrdId(id) => write!(f, "ClOrdID {id} is already used by another order"),
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+/// `Order`
+///
+/// latest known state of one order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    /// ClOrdID [11] of the latest request, changes on cancel/replace
+    pub cl_ord_id: String,
+    /// OrigClOrdID [41] of the latest request, if any
+    pub orig_cl_ord_id: Option<String>,
+    /// OrderID [37] assigned by power.trade, None until acknowledged
+    pub order_id: Option<String>,
+    pub symbol: String,
+    /// Side [54] e.g. '1' = Buy
+    pub side: String,
+    /// OrdType [40] e.g. '2' = Limit
+    pub ord_type: String,
+    /// TimeInForce [59] e.g. '1' = GTC, empty when not reported
+    pub time_in_force: String,
+    /// ExpireTime [126] of a GoodTillDate order
+    pub expire_time: Option<String>,
+    /// ExpireDate [432] of a GoodTillDate order
+    pub expire_date: Option<String>,
+    /// OrderQty [38]
+    pub order_qty: Decimal,
+    /// Price [44], None for Market orders
+    pub price: Option<Decimal>,
+    /// StopPx [99] of Stop and StopLimit orders
+    pub stop_px: Option<Decimal>,
+    /// true once a Stop / StopLimit order was reported with ExecType [150] = 'L'
+    pub triggered: bool,
+    pub ord_status: OrdStatus,
+    pub last_exec_type: Option<ExecType>,
+    pub cum_qty: Decimal,
+    pub leaves_qty: Decimal,
+    pub avg_px: Decimal,
+    /// Text [58] of the latest report, e.g. reject reason
+    pub text: Option<String>,
+    /// OrdRejReason [103] when the order was rejected
+    pub reject_reason: Option<OrdRejReason>,
+    /// legs of a multi-leg order (35=AB), empty for single leg orders
+    pub legs: Vec<OrderLeg>,
+    /// session the latest report was received on
+    pub source: ReportSource,
+    /// true when the order was first reported by drop-copy, i.e. placed outside this session
+    pub external: bool,
+}
+
+impl Order {
+    pub fn is_working(&self) -> bool {
+        !self.ord_status.is_terminal()
+    }
+}
+
+/// `OrderUpdate`
+///
+/// published to listeners on every change to an order
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderUpdate {
+    pub order: Order,
+    /// status before the change, None for orders first seen in this report
+    pub previous: Option<OrdStatus>,
+    pub exec_type: Option<ExecType>,
+    /// set when the change was an OrderCancelReject (35=9)
+    pub cancel_reject: Option<CxlRejResponseTo>,
+}
+
+/// `OrderManager`
+#[derive(Debug, Default)]
+pub struct OrderManager {
+    orders: Vec<Order>,
+    by_cl_ord_id: HashMap<String, usize>,
+    by_order_id: HashMap<String, usize>,
+    /// ExecIDs [17] applied to each order, same index as orders
+    exec_ids: Vec<HashSet<String>>,
+    listeners: Vec<Sender<OrderUpdate>>,
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        OrderManager::default()
+    }
+
+    pub fn shared() -> SharedOrderManager {
+        Arc::new(Mutex::new(OrderManager::new()))
+    }
+
+    /// `subscribe`
+    ///
+    /// returns receiver for every `OrderUpdate` from now on
+    pub fn subscribe(&mut self) -> Receiver<OrderUpdate> {
+        let (tx, rx) = channel::<OrderUpdate>();
+        self.listeners.push(tx);
+        rx
+    }
+
+    /// `track_new`
+    ///
+    /// registers order being sent (NewOrderSingle 35=D or NewOrderMultileg 35=AB) as PendingNew,
+    /// so it is known before it is acknowledged
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - ClOrdID [11], Symbol [55] (single leg only), Side [54] or OrderQty [38] is missing
+    /// - ClOrdID is already used by a known order
+    /// - NoLegs [555] group of a multi-leg order is not valid
+    pub fn track_new(&mut self, msg: &RawMessage) -> Result<&Order, OrderError> {
+        let required = |tag: u32| msg.get(tag).filter(|v| !v.is_empty()).ok_or(OrderError::InvalidReport { tag, value: None });
+        let cl_ord_id: &str = required(11)?;
+        if self.by_cl_ord_id.contains_key(cl_ord_id) {
+            return Err(OrderError::DuplicateClOrdId(cl_ord_id.to_string()));
+        }
+        let order_qty: Decimal = parse_decimal(msg, 38)?.ok_or(OrderError::InvalidReport { tag: 38, value: None })?;
+        let legs: Vec<OrderLeg> = OrderLeg::from_raw(msg)?;
+        let symbol: &str = if msg.msg_type() == "AB" { msg.get(55).unwrap_or(MULTILEG_SYMBOL) } else { required(55)? };
+        let order = Order {
+            cl_ord_id: cl_ord_id.to_string(),
+            orig_cl_ord_id: None,
+            order_id: None,
+            symbol: symbol.to_string(),
+            side: required(54)?.to_string(),
+            ord_type: msg.get(40).unwrap_or("2").to_string(),
+            time_in_force: msg.get(59).unwrap_or_default().to_string(),
+            expire_time: msg.get(126).map(str::to_string),
+            expire_date: msg.get(432).map(str::to_string),
+            order_qty,
+            price: parse_decimal(msg, 44)?,
+            stop_px: parse_decimal(msg, 99)?,
+            triggered: false,
+            ord_status: OrdStatus::PendingNew,
+            last_exec_type: None,
+            cum_qty: Decimal::ZERO,
+            leaves_qty: order_qty,
+            avg_px: Decimal::ZERO,
+            text: None,
+            reject_reason: None,
+            legs,
+            source: ReportSource::Local,
+            external: false,
+        };
+        let index: usize = self.orders.len();
+        self.orders.push(order);
+        self.exec_ids.push(HashSet::new());
+        self.by_cl_ord_id.insert(cl_ord_id.to_string(), index);
+        self.notify(index, None, None, None);
+        Ok(&self.orders[index])
+    }
+
+    /// `track_replace`
+    ///
+    /// registers ClOrdID [11] of an OrderCancelReplaceRequest (35=G) for the order with OrigClOrdID [41],
+    /// order state changes when the PendingReplace / Replaced ExecutionReports arrive
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - ClOrdID [11] or OrigClOrdID [41] is missing
+    /// - ClOrdID is already used by a known order
+    /// - no order is known for OrigClOrdID
+    pub fn track_replace(&mut self, msg: &RawMessage) -> Result<&Order, OrderError> {
+        let cl_ord_id: &str = msg.get(11).filter(|v| !v.is_empty()).ok_or(OrderError::InvalidReport { tag: 11, value: None })?;
+        if self.by_cl_ord_id.contains_key(cl_ord_id) {
+            return Err(OrderError::DuplicateClOrdId(cl_ord_id.to_string()));
+        }
+        let orig_cl_ord_id: &str = msg.get(41).filter(|v| !v.is_empty()).ok_or(OrderError::InvalidReport { tag: 41, value: None })?;
+        let index: usize = *self.by_cl_ord_id.get(orig_cl_ord_id).ok_or_else(|| OrderError::UnknownOrder(orig_cl_ord_id.to_string()))?;
+        self.by_cl_ord_id.insert(cl_ord_id.to_string(), index);
+        Ok(&self.orders[index])
+    }
+
+    /// `reject_local`
+    ///
+    /// marks an order still PendingNew as Rejected with Text [58], used when it was stopped before being sent,
+    /// returns None when the order is not known or was already acknowledged
+    pub fn reject_local(&mut self, cl_ord_id: &str, text: &str) -> Option<&Order> {
+        let index: usize = *self.by_cl_ord_id.get(cl_ord_id)?;
+        if self.orders[index].ord_status != OrdStatus::PendingNew {
+            return None;
+        }
+        let order: &mut Order = &mut self.orders[index];
+        order.ord_status = OrdStatus::Rejected;
+        order.last_exec_type = Some(ExecType::Rejected);
+        order.leaves_qty = Decimal::ZERO;
+        order.text = Some(text.to_string());
+        order.source = ReportSource::Local;
+        self.notify(index, Some(OrdStatus::PendingNew), Some(ExecType::Rejected), None);
+        Some(&self.orders[index])
+    }
+
+    /// `apply`
+    ///
+    /// applies ExecutionReport to order state, returns updated order
+    /// Ok(None) when message is not an ExecutionReport, was already applied (same ExecID [17])
+    /// or names no order (e.g. the 'no orders' report answering an OrderMassStatusRequest)
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - OrdStatus [39] or ExecType [150] is missing or not supported
+    /// - CumQty [14], LeavesQty [151] or AvgPx [6] is not a number
+    /// - OrdStatus transition is not allowed, e.g. Filled -> New
+    /// - CumQty decreases
+    pub fn apply(&mut self, source: ReportSource, msg: &RawMessage) -> Result<Option<&Order>, OrderError> {
+        match msg.msg_type() {
+            "8" => {}
+            "9" => return self.apply_cancel_reject(source, msg),
+            _ => return Ok(None),
+        }
+        if msg.get(442) == Some("2") {
+            return self.apply_leg_report(source, msg);
+        }
+        let ord_status: OrdStatus = parse_char(msg, 39, OrdStatus::from_fix)?.ok_or(OrderError::InvalidReport { tag: 39, value: None })?;
+        let exec_type: Option<ExecType> = parse_char(msg, 150, ExecType::from_fix)?;
+        let cum_qty: Option<Decimal> = parse_decimal(msg, 14)?;
+        let leaves_qty: Option<Decimal> = parse_decimal(msg, 151)?;
+        let avg_px: Option<Decimal> = parse_decimal(msg, 6)?;
+        let legs: Vec<OrderLeg> = OrderLeg::from_raw(msg)?;
+        let ord_rej_reason: Option<OrdRejReason> = match msg.get(103) {
+            None => None,
+            Some(value) => Some(OrdRejReason::from_fix(
+                value.parse().map_err(|_| OrderError::InvalidReport { tag: 103, value: Some(value.to_string()) })?,
+            )),
+        };
+
+        let order_id: Option<&str> = msg.get(37).filter(|id| !id.is_empty() && *id != "NONE");
+        let cl_ord_id: &str = msg.get(11).unwrap_or_default();
+        let orig_cl_ord_id: Option<&str> = msg.get(41).filter(|id| !id.is_empty());
+        let found: Option<usize> = order_id
+            .and_then(|id| self.by_order_id.get(id))
+            .or_else(|| self.by_cl_ord_id.get(cl_ord_id))
+            .or_else(|| orig_cl_ord_id.and_then(|id| self.by_cl_ord_id.get(id)))
+            .copied();
+        if found.is_none() && order_id.is_none() && cl_ord_id.is_empty() {
+            return Ok(None);
+        }
+        if let (Some(index), Some(exec_id)) = (found, msg.get(17)) {
+            if self.exec_ids[index].contains(exec_id) {
+                return Ok(None);
+            }
+        }
+
+        let (index, previous): (usize, Option<OrdStatus>) = match found {
+            Some(index) => {
+                let order: &Order = &self.orders[index];
+                if !order.ord_status.can_transition_to(ord_status) {
+                    return Err(OrderError::IllegalTransition {
+                        cl_ord_id: order.cl_ord_id.clone(),
+                        from: order.ord_status,
+                        to: ord_status,
+                    });
+                }
+                if let Some(cum_qty) = cum_qty {
+                    if cum_qty < order.cum_qty {
+                        return Err(OrderError::CumQtyDecreased { cl_ord_id: order.cl_ord_id.clone(), from: order.cum_qty, to: cum_qty });
+                    }
+                }
+                (index, Some(order.ord_status))
+            }
+            None => {
+                self.orders.push(Order {
+                    cl_ord_id: cl_ord_id.to_string(),
+                    orig_cl_ord_id: None,
+                    order_id: None,
+                    symbol: String::new(),
+                    side: String::new(),
+                    ord_type: String::new(),
+                    time_in_force: String::new(),
+                    expire_time: None,
+                    expire_date: None,
+                    order_qty: Decimal::ZERO,
+                    price: None,
+                    stop_px: None,
+                    triggered: false,
+                    ord_status,
+                    last_exec_type: None,
+                    cum_qty: Decimal::ZERO,
+                    leaves_qty: Decimal::ZERO,
+                    avg_px: Decimal::ZERO,
+                    text: None,
+                    reject_reason: None,
+                    legs: Vec::new(),
+                    source,
+                    external: source == ReportSource::DropCopy,
+                });
+                self.exec_ids.push(HashSet::new());
+                (self.orders.len() - 1, None)
+            }
+        };
+
+        if let Some(exec_id) = msg.get(17) {
+            self.exec_ids[index].insert(exec_id.to_string());
+        }
+        if !cl_ord_id.is_empty() {
+            self.by_cl_ord_id.insert(cl_ord_id.to_string(), index);
+        }
+        if let Some(order_id) = order_id {
+            self.by_order_id.insert(order_id.to_string(), index);
+        }
+
+        let order: &mut Order = &mut self.orders[index];
+        if !cl_ord_id.is_empty() {
+            order.cl_ord_id = cl_ord_id.to_string();
+        }
+        if orig_cl_ord_id.is_some() {
+            order.orig_cl_ord_id = orig_cl_ord_id.map(str::to_string);
+        }
+        if order_id.is_some() {
+            order.order_id = order_id.map(str::to_string);
+        }
+        if let Some(symbol) = msg.get(55) {
+            order.symbol = symbol.to_string();
+        }
+        if let Some(side) = msg.get(54) {
+            order.side = side.to_string();
+        }
+        if let Some(ord_type) = msg.get(40) {
+            order.ord_type = ord_type.to_string();
+        }
+        if let Some(time_in_force) = msg.get(59) {
+            order.time_in_force = time_in_force.to_string();
+            order.expire_time = msg.get(126).map(str::to_string);
+            order.expire_date = msg.get(432).map(str::to_string);
+        }
+        order.order_qty = parse_decimal(msg, 38)?.unwrap_or(order.order_qty);
+        order.price = parse_decimal(msg, 44)?.or(order.price);
+        order.stop_px = parse_decimal(msg, 99)?.or(order.stop_px);
+        order.triggered |= exec_type == Some(ExecType::Triggered);
+        order.ord_status = ord_status;
+        order.last_exec_type = exec_type;
+        order.cum_qty = cum_qty.unwrap_or(order.cum_qty);
+        order.leaves_qty = leaves_qty.unwrap_or(order.leaves_qty);
+        order.avg_px = avg_px.unwrap_or(order.avg_px);
+        order.text = msg.get(58).map(str::to_string);
+        if ord_status == OrdStatus::Rejected {
+            order.reject_reason = ord_rej_reason;
+        }
+        if order.legs.is_empty() {
+            order.legs = legs;
+        }
+        order.source = source;
+
+        self.notify(index, previous, exec_type, None);
+        Ok(Some(&self.orders[index]))
+    }
+
+    /// `apply_leg_report`
+    ///
+    /// applies the fill of one leg, ExecutionReport with MultiLegReportingType [442] = '2', to the leg with the same
+    /// LegSymbol [600] and LegSide [624], the package status is left to the package reports
+    /// Ok(None) when report was already applied or the order is not known
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - LegSymbol [600] is missing
+    /// - LastQty [32] or LastPx [31] is not a number, or the fill is too large to add to the leg
+    fn apply_leg_report(&mut self, source: ReportSource, msg: &RawMessage) -> Result<Option<&Order>, OrderError> {
+        let leg_symbol: &str = msg.get(600).ok_or(OrderError::InvalidReport { tag: 600, value: None })?;
+        let leg_side: &str = msg.get(624).unwrap_or_default();
+        let last_qty: Decimal = parse_decimal(msg, 32)?.unwrap_or_default();
+        let last_px: Decimal = parse_decimal(msg, 31)?.unwrap_or_default();
+        let exec_type: Option<ExecType> = parse_char(msg, 150, ExecType::from_fix)?;
+        let found: Option<usize> =
+            msg.get(37).and_then(|id| self.by_order_id.get(id)).or_else(|| msg.get(11).and_then(|id| self.by_cl_ord_id.get(id))).copied();
+        let Some(index) = found else {
+            return Ok(None);
+        };
+        if let Some(exec_id) = msg.get(17) {
+            if !self.exec_ids[index].insert(exec_id.to_string()) {
+                return Ok(None);
+            }
+        }
+
+        let order: &mut Order = &mut self.orders[index];
+        let position: Option<usize> =
+            order.legs.iter().position(|leg| leg.symbol == leg_symbol && (leg_side.is_empty() || leg.side == leg_side));
+        let leg: &mut OrderLeg = match position {
+            Some(position) => &mut order.legs[position],
+            None => {
+                // leg not known, e.g. order first seen in this report
+                let ratio_qty: Decimal = parse_decimal(msg, 623)?.unwrap_or_default();
+                order.legs.push(OrderLeg::new(leg_symbol, leg_side, ratio_qty));
+                order.legs.last_mut().expect("leg added")
+            }
+        };
+        if last_qty > Decimal::ZERO {
+            leg.apply_fill(last_qty, last_px)?;
+        }
+        order.source = source;
+        let previous: OrdStatus = order.ord_status;
+        self.notify(index, Some(previous), exec_type, None);
+        Ok(Some(&self.orders[index]))
+    }
+
+    /// `apply_cancel_reject`
+    ///
+    /// OrderCancelReject (35=9) - order keeps (or returns to) the OrdStatus [39] reported with it,
+    /// status '8' means the venue does not know the order and is not applied
+    fn apply_cancel_reject(&mut self, source: ReportSource, msg: &RawMessage) -> Result<Option<&Order>, OrderError> {
+        let response_to: Option<CxlRejResponseTo> = parse_char(msg, 434, CxlRejResponseTo::from_fix)?;
+        let ord_status: Option<OrdStatus> = parse_char(msg, 39, OrdStatus::from_fix)?.filter(|status| *status != OrdStatus::Rejected);
+        let index: usize = msg
+            .get(41)
+            .and_then(|id| self.by_cl_ord_id.get(id))
+            .or_else(|| msg.get(37).and_then(|id| self.by_order_id.get(id)))
+            .copied()
+            .ok_or_else(|| OrderError::UnknownOrder(msg.get(41).unwrap_or_default().to_string()))?;
+
+        let order: &mut Order = &mut self.orders[index];
+        let previous: OrdStatus = order.ord_status;
+        if let Some(ord_status) = ord_status {
+            if !previous.can_transition_to(ord_status) {
+                return Err(OrderError::IllegalTransition { cl_ord_id: order.cl_ord_id.clone(), from: previous, to: ord_status });
+            }
+            order.ord_status = ord_status;
+        }
+        order.text = msg.get(58).map(str::to_string);
+        order.source = source;
+        self.notify(index, Some(previous), None, response_to);
+        Ok(Some(&self.orders[index]))
+    }
+
+    /// `get`
+    ///
+    /// looks up order by ClOrdID [11] (current or earlier) or OrderID [37]
+    pub fn get(&self, id: &str) -> Option<&Order> {
+        self.by_cl_ord_id.get(id).or_else(|| self.by_order_id.get(id)).map(|index| &self.orders[*index])
+    }
+
+    pub fn orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.iter()
+    }
+
+    /// `open_orders`
+    ///
+    /// orders not yet in a terminal state
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.iter().filter(|order| order.is_working())
+    }
+
+    /// `reconcile`
+    ///
+    /// working orders in the scope of a complete mass status snapshot that the venue did not report,
+    /// i.e. orders whose final ExecutionReport was missed, e.g. while disconnected
+    ///
+    /// n.b. reports in the snapshot are applied by the session reader as they arrive, not here
+    pub fn reconcile<'a>(&'a self, snapshot: &'a MassStatusSnapshot) -> impl Iterator<Item = &'a Order> {
+        self.open_orders().filter(move |order| snapshot.is_complete() && snapshot.covers(order) && !snapshot.contains(order))
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    fn notify(&mut self, index: usize, previous: Option<OrdStatus>, exec_type: Option<ExecType>, cancel_reject: Option<CxlRejResponseTo>) {
+        let update = OrderUpdate { order: self.orders[index].clone(), previous, exec_type, cancel_reject };
+        // listeners that went away are dropped
+        self.listeners.retain(|listener| listener.send(update.clone()).is_ok());
+    }
+}
+
+fn parse_char<T>(msg: &RawMessage, tag: u32, from_fix: fn(char) -> Option<T>) -> Result<Option<T>, OrderError> {
+    match msg.get(tag) {
+        None => Ok(None),
+        Some(value) => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => from_fix(c).map(Some).ok_or(OrderError::InvalidReport { tag, value: Some(value.to_string()) }),
+                _ => Err(OrderError::InvalidReport { tag, value: Some(value.to_string()) }),
+            }
+        }
+    }
+}
+
+fn parse_decimal(msg: &RawMessage, tag: u32) -> Result<Option<Decimal>, OrderError> {
+    match msg.get(tag) {
+        None => Ok(None),
+        Some(value) => value.parse::<Decimal>().map(Some).map_err(|_| OrderError::InvalidReport { tag, value: Some(value.to_string()) }),
+    }
+}
+
+#[cfg(test)]
+mod order_manager_tests {
+
+    use super::{ExecType, MassStatusSnapshot, OrdStatus, OrderError, OrderManager, ReportSource};
+    use crate::fix::{Decimal, RawMessage};
+
+    fn report(exec_id: &str, cl_ord_id: &str, exec_type: char, ord_status: char, cum_qty: f64) -> RawMessage {
+        let mut msg = RawMessage::new("8");
+        msg.set(17, exec_id)
+            .set(37, "O1")
+            .set(11, cl_ord_id)
+            .set(150, exec_type)
+            .set(39, ord_status)
+            .set(14, cum_qty)
+            .set(151, 2.0 - cum_qty)
+            .set(6, 388)
+            .set(55, "SOL-USD")
+            .set(54, 2);
+        msg
+    }
+
+    #[test]
+    fn test_new_partial_fill_lifecycle_with_listener() {
+        let mut orders = OrderManager::new();
+        let updates = orders.subscribe();
+        let mut new_order = RawMessage::new("D");
+        new_order.set(11, "C1").set(55, "SOL-USD").set(54, 2).set(38, 2).set(44, 388);
+        assert_eq!(orders.track_new(&new_order).unwrap().ord_status, OrdStatus::PendingNew);
+
+        orders.apply(ReportSource::OrderEntry, &report("E1", "C1", '0', '0', 0.0)).unwrap();
+        orders.apply(ReportSource::OrderEntry, &report("E2", "C1", 'F', '1', 1.0)).unwrap();
+        let order = orders.apply(ReportSource::OrderEntry, &report("E3", "C1", 'F', '2', 2.0)).unwrap().unwrap();
+        assert_eq!(order.ord_status, OrdStatus::Filled);
+        assert_eq!((order.cum_qty, order.leaves_qty, order.avg_px), (Decimal::from(2), Decimal::ZERO, Decimal::from(388)));
+        assert_eq!(order.order_id.as_deref(), Some("O1"));
+        assert_eq!(orders.len(), 1);
+
+        let seen: Vec<(Option<OrdStatus>, Option<ExecType>)> = updates.try_iter().map(|u| (u.previous, u.exec_type)).collect();
+        assert_eq!(
+            seen,
+            vec![
+                (None, None),
+                (Some(OrdStatus::PendingNew), Some(ExecType::New)),
+                (Some(OrdStatus::New), Some(ExecType::Trade)),
+                (Some(OrdStatus::PartiallyFilled), Some(ExecType::Trade)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_illegal_transitions_are_rejected() {
+        let mut orders = OrderManager::new();
+        orders.apply(ReportSource::OrderEntry, &report("E1", "C1", 'F', '2', 2.0)).unwrap();
+        assert_eq!(
+            orders.apply(ReportSource::OrderEntry, &report("E2", "C1", '0', '0', 2.0)),
+            Err(OrderError::IllegalTransition { cl_ord_id: "C1".to_string(), from: OrdStatus::Filled, to: OrdStatus::New })
+        );
+
+        let mut orders = OrderManager::new();
+        orders.apply(ReportSource::OrderEntry, &report("E1", "C1", 'F', '1', 1.0)).unwrap();
+        assert!(matches!(
+            orders.apply(ReportSource::OrderEntry, &report("E2", "C1", '8', '8', 1.0)),
+            Err(OrderError::IllegalTransition { .. })
+        ));
+        assert!(matches!(
+            orders.apply(ReportSource::OrderEntry, &report("E3", "C1", 'F', '1', 0.5)),
+            Err(OrderError::CumQtyDecreased { .. })
+        ));
+        assert_eq!(orders.get("C1").unwrap().ord_status, OrdStatus::PartiallyFilled);
+    }
+
+    #[test]
+    fn test_drop_copy_duplicate_is_applied_once() {
+        let mut orders = OrderManager::new();
+        assert!(orders.apply(ReportSource::OrderEntry, &report("E1", "C1", '0', '0', 0.0)).unwrap().is_some());
+        assert!(orders.apply(ReportSource::DropCopy, &report("E1", "C1", '0', '0', 0.0)).unwrap().is_none());
+        assert!(!orders.get("O1").unwrap().external);
+
+        let mut orders = OrderManager::new();
+        assert!(orders.apply(ReportSource::DropCopy, &report("E9", "OTHER-1", '0', '0', 0.0)).unwrap().unwrap().external);
+    }
+
+    #[test]
+    fn test_duplicate_cl_ord_id_is_not_tracked() {
+        let mut orders = OrderManager::new();
+        let mut new_order = RawMessage::new("D");
+        new_order.set(11, "C1").set(55, "SOL-USD").set(54, 2).set(38, 2).set(44, 388);
+        orders.track_new(&new_order).unwrap();
+        orders.apply(ReportSource::OrderEntry, &report("E1", "C1", '0', '0', 0.0)).unwrap();
+        assert_eq!(orders.track_new(&new_order), Err(OrderError::DuplicateClOrdId("C1".to_string())));
+        assert_eq!(orders.get("C1").unwrap().ord_status, OrdStatus::New);
+
+        let mut replace = RawMessage::new("G");
+        replace.set(11, "C1").set(41, "C1");
+        assert_eq!(orders.track_replace(&replace), Err(OrderError::DuplicateClOrdId("C1".to_string())));
+        assert_eq!(orders.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_report_names_tag() {
+        let mut orders = OrderManager::new();
+        let mut msg = report("E1", "C1", '0', '0', 0.0);
+        msg.set(39, "Z");
+        assert_eq!(orders.apply(ReportSource::OrderEntry, &msg), Err(OrderError::InvalidReport { tag: 39, value: Some("Z".to_string()) }));
+    }
+
+    #[test]
+    fn test_replace_then_rejected_replace() {
+        let mut orders = OrderManager::new();
+        orders.apply(ReportSource::OrderEntry, &report("E1", "C1", '0', '0', 0.0)).unwrap();
+        let mut replace = RawMessage::new("G");
+        replace.set(11, "C2").set(41, "C1");
+        orders.track_replace(&replace).unwrap();
+
+        let mut replaced = report("E2", "C2", '5', '0', 0.0);
+        replaced.set(41, "C1").set(38, 3).set(44, 390);
+        let order = orders.apply(ReportSource::OrderEntry, &replaced).unwrap().unwrap();
+        assert_eq!((order.cl_ord_id.as_str(), order.orig_cl_ord_id.as_deref()), ("C2", Some("C1")));
+        assert_eq!((order.order_qty, order.price), (Decimal::from(3), Some(Decimal::from(390))));
+        assert_eq!(orders.get("C1"), orders.get("C2"));
+
+        let mut reject = RawMessage::new("9");
+        reject.set(11, "C3").set(41, "C2").set(37, "O1").set(39, '0').set(434, '2').set(58, "Too late to replace");
+        let order = orders.apply(ReportSource::OrderEntry, &reject).unwrap().unwrap();
+        assert_eq!(order.ord_status, OrdStatus::New);
+        assert_eq!(order.text.as_deref(), Some("Too late to replace"));
+    }
+
+    #[test]
+    fn test_mass_status_reconcile_finds_missing_orders() {
+        let mut orders = OrderManager::new();
+        orders.apply(ReportSource::OrderEntry, &report("E1", "C1", '0', '0', 0.0)).unwrap();
+        let mut other = report("E2", "C2", '0', '0', 0.0);
+        other.set(37, "O2");
+        orders.apply(ReportSource::OrderEntry, &other).unwrap();
+
+        let mut snapshot = MassStatusSnapshot::new("M1", None);
+        let mut status = report("E3", "C1", 'I', '1', 1.0);
+        status.set(584, "M1").set(911, 1).set(912, 'Y');
+        assert!(!snapshot.accept(&report("E4", "C1", 'F', '1', 1.0)));
+        assert!(snapshot.accept(&status));
+        assert!(snapshot.is_complete());
+        orders.apply(ReportSource::OrderEntry, &status).unwrap();
+
+        let missing: Vec<&str> = orders.reconcile(&snapshot).map(|order| order.cl_ord_id.as_str()).collect();
+        assert_eq!(missing, vec!["C2"]);
+        assert_eq!(orders.get("C1").unwrap().ord_status, OrdStatus::PartiallyFilled);
+
+        // 'no orders' report completes snapshot, names no order and is not applied
+        let mut snapshot = MassStatusSnapshot::new("M2", Some("SOL-USD"));
+        let mut none = RawMessage::new("8");
+        none.set(37, "NONE").set(150, 'I').set(39, '8').set(584, "M2").set(911, 0).set(912, 'Y');
+        assert!(snapshot.accept(&none));
+        assert!(snapshot.is_complete() && snapshot.is_empty());
+        assert!(orders.apply(ReportSource::OrderEntry, &none).unwrap().is_none());
+        assert_eq!(orders.reconcile(&snapshot).count(), 2);
+    }
+}
@@ -0,0 +1,115 @@
+// src/orders/multileg.rs
+//
+// Legs of a NewOrderMultileg (35=AB) package
+//  - NoLegs [555] group, each entry LegSymbol [600], LegSide [624] and LegRatioQty [623]
+//  - package ExecutionReports carry MultiLegReportingType [442] = '3', fills of each leg are reported
+//    separately with MultiLegReportingType = '2', LegSymbol / LegSide and LastQty [32] / LastPx [31]
+//
+
+use super::OrderError;
+use crate::fix::{Decimal, RawMessage};
+
+/// Symbol [55] of a multi-leg package, the instrument is defined by its legs
+pub const MULTILEG_SYMBOL: &str = "[N/A]";
+
+/// `OrderLeg`
+///
+/// one leg of a multi-leg order with the quantity filled on it so far
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderLeg {
+    /// LegSymbol [600]
+    pub symbol: String,
+    /// LegSide [624] e.g. '1' = Buy, as bought by a Buy package
+    pub side: String,
+    /// LegRatioQty [623] - leg quantity per unit of package quantity
+    pub ratio_qty: Decimal,
+    pub cum_qty: Decimal,
+    pub avg_px: Decimal,
+}
+
+impl OrderLeg {
+    /// leg with nothing filled yet
+    pub fn new(symbol: &str, side: &str, ratio_qty: Decimal) -> Self {
+        OrderLeg { symbol: symbol.to_string(), side: side.to_string(), ratio_qty, cum_qty: Decimal::ZERO, avg_px: Decimal::ZERO }
+    }
+
+    /// `from_raw`
+    ///
+    /// legs from the NoLegs [555] group of msg, empty when the group is missing
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - an entry has no LegSymbol [600], or LegSide [624] / LegRatioQty [623] is missing or not valid
+    pub fn from_raw(msg: &RawMessage) -> Result<Vec<OrderLeg>, OrderError> {
+        let mut legs: Vec<OrderLeg> = Vec::new();
+        let group = msg.fields().iter().skip_while(|(tag, _)| *tag != 555).skip(1);
+        for (tag, value) in group {
+            match (tag, legs.last_mut()) {
+                (600, _) => legs.push(OrderLeg::new(value, "", Decimal::ZERO)),
+                (624, Some(leg)) if value == "1" || value == "2" => leg.side = value.clone(),
+                (623, Some(leg)) => {
+                    leg.ratio_qty = value
+                        .parse()
+                        .ok()
+                        .filter(|ratio: &Decimal| *ratio > Decimal::ZERO)
+                        .ok_or(OrderError::InvalidReport { tag: 623, value: Some(value.clone()) })?;
+                }
+                (624 | 623, _) => return Err(OrderError::InvalidReport { tag: *tag, value: Some(value.clone()) }),
+                _ => {}
+            }
+        }
+        match legs.iter().find(|leg| leg.side.is_empty() || leg.ratio_qty <= Decimal::ZERO) {
+            Some(leg) if leg.side.is_empty() => Err(OrderError::InvalidReport { tag: 624, value: None }),
+            Some(_) => Err(OrderError::InvalidReport { tag: 623, value: None }),
+            None => Ok(legs),
+        }
+    }
+
+    /// `apply_fill`
+    ///
+    /// adds LastQty [32] at LastPx [31] to the leg, the leg is left as it was on error
+    ///
+    /// # Errors
+    ///
+    /// function will return error if the quantity or average price of the leg is too large for a `Decimal`
+    pub fn apply_fill(&mut self, last_qty: Decimal, last_px: Decimal) -> Result<(), OrderError> {
+        let overflow = || OrderError::InvalidReport { tag: 32, value: Some(last_qty.to_string()) };
+        let cum_qty: Decimal = self.cum_qty.checked_add(last_qty).ok_or_else(overflow)?;
+        if !cum_qty.is_zero() {
+            let held: Decimal = self.avg_px.checked_mul(self.cum_qty).ok_or_else(overflow)?;
+            let filled: Decimal = last_px.checked_mul(last_qty).ok_or_else(overflow)?;
+            self.avg_px = held.checked_add(filled).and_then(|notional| notional.checked_div(cum_qty)).ok_or_else(overflow)?;
+        }
+        self.cum_qty = cum_qty;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod multileg_tests {
+
+    use super::OrderLeg;
+    use crate::fix::{Decimal, RawMessage};
+
+    #[test]
+    fn test_legs_from_group_and_fills() {
+        let mut msg = RawMessage::new("AB");
+        msg.set(11, "C1").set(555, 2);
+        msg.push(600, "SOL-USD").push(624, 1).push(623, 1).push(600, "BTC-USD").push(624, 2).push(623, 0.01);
+        let mut legs = OrderLeg::from_raw(&msg).unwrap();
+        assert_eq!(legs.len(), 2);
+        assert_eq!((legs[1].symbol.as_str(), legs[1].side.as_str(), legs[1].ratio_qty), ("BTC-USD", "2", Decimal::new(1, 2)));
+
+        legs[0].apply_fill(Decimal::new(1, 1), Decimal::new(3881, 1)).unwrap();
+        legs[0].apply_fill(Decimal::new(2, 1), Decimal::new(3884, 1)).unwrap();
+        assert_eq!((legs[0].cum_qty.to_string(), legs[0].avg_px.to_string()), ("0.3".to_string(), "388.3".to_string()));
+        let huge: Decimal = "9999999999999999999".parse().unwrap();
+        assert!(legs[0].apply_fill(huge, huge).is_err());
+        assert_eq!((legs[0].cum_qty.to_string(), legs[0].avg_px.to_string()), ("0.3".to_string(), "388.3".to_string()));
+
+        let mut msg = RawMessage::new("AB");
+        msg.set(555, 1).push(600, "SOL-USD").push(623, 1);
+        assert!(OrderLeg::from_raw(&msg).is_err());
+    }
+}
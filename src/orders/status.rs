@@ -0,0 +1,92 @@
+// src/orders/status.rs
+//
+// Snapshot of ExecutionReports (35=8) answering an OrderMassStatusRequest (35=AF)
+//  - reports carry ExecType [150] = 'I', MassStatusReqID [584] and TotNumReports [911]
+//  - snapshot is complete when TotNumReports reports arrived or LastRptRequested [912] = 'Y'
+//
+
+use super::Order;
+use crate::fix::RawMessage;
+
+/// `MassStatusSnapshot`
+///
+/// collects the reports for one MassStatusReqID [584], `symbol` is the scope of the request (None for all orders)
+#[derive(Debug, Clone)]
+pub struct MassStatusSnapshot {
+    mass_status_req_id: String,
+    symbol: Option<String>,
+    total: Option<usize>,
+    reports: Vec<RawMessage>,
+    complete: bool,
+}
+
+impl MassStatusSnapshot {
+    pub fn new(mass_status_req_id: &str, symbol: Option<&str>) -> Self {
+        MassStatusSnapshot {
+            mass_status_req_id: mass_status_req_id.to_string(),
+            symbol: symbol.map(str::to_string),
+            total: None,
+            reports: Vec::new(),
+            complete: false,
+        }
+    }
+
+    pub fn mass_status_req_id(&self) -> &str {
+        &self.mass_status_req_id
+    }
+
+    pub fn symbol(&self) -> Option<&str> {
+        self.symbol.as_deref()
+    }
+
+    /// `accept`
+    ///
+    /// adds report to snapshot, returns false when msg is not an ExecutionReport for this MassStatusReqID [584]
+    ///
+    /// n.b. the single report sent when there are no orders (TotNumReports [911] = 0) completes the snapshot but is not kept
+    pub fn accept(&mut self, msg: &RawMessage) -> bool {
+        if msg.msg_type() != "8" || msg.get(584) != Some(self.mass_status_req_id.as_str()) {
+            return false;
+        }
+        if let Some(total) = msg.get(911).and_then(|v| v.parse::<usize>().ok()) {
+            self.total = Some(total);
+        }
+        if self.total != Some(0) {
+            self.reports.push(msg.clone());
+        }
+        self.complete = msg.get(912) == Some("Y") || self.total.map_or(false, |total| self.reports.len() >= total);
+        true
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    pub fn reports(&self) -> &[RawMessage] {
+        &self.reports
+    }
+
+    pub fn len(&self) -> usize {
+        self.reports.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reports.is_empty()
+    }
+
+    /// `contains`
+    ///
+    /// true when a report in the snapshot is for order, matched on OrderID [37] or ClOrdID [11]
+    pub fn contains(&self, order: &Order) -> bool {
+        self.reports.iter().any(|report| {
+            order.order_id.as_deref().map_or(false, |id| report.get(37) == Some(id)) || report.get(11) == Some(order.cl_ord_id.as_str())
+        })
+    }
+
+    /// `covers`
+    ///
+    /// true when order is in the scope of the request
+    pub fn covers(&self, order: &Order) -> bool {
+        self.symbol.as_deref().map_or(true, |symbol| order.symbol == symbol)
+    }
+}
@@ -0,0 +1,252 @@
+// src/positions/mod.rs
+//
+// Positions rebuilt from fill ExecutionReports (35=8, ExecType [150] = 'F')
+//  - one `Position` per Account [1] and Symbol [55], legs of multi-leg fills (MultiLegReportingType [442] = '2')
+//    count towards LegSymbol [600] / LegSide [624], package fills ('3') are skipped
+//  - net quantity, average entry price from LastQty [32] / LastPx [31], realized PnL and fees from Commission [12]
+//    n.b. fills keep LastQty / LastPx as reported in `Decimal`s, positions and PnL are f64
+//  - fills are applied once by ExecID [17], so order-entry and drop-copy sessions can feed the same keeper
+//  - unrealized PnL is valued at mark prices set with `set_mark_price`
+//
+
+use crate::fix::{Decimal, RawMessage};
+use log::info;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+/// position keeper shared between the order-entry and drop-copy sessions feeding it fills
+pub type SharedPositionKeeper = Arc<Mutex<PositionKeeper>>;
+
+// quantities below this are treated as zero
+const QTY_EPSILON: f64 = 1e-9;
+
+/// `Fill`
+///
+/// one fill read from an ExecutionReport
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub exec_id: Option<String>,
+    /// Account [1], empty when not reported
+    pub account: String,
+    pub symbol: String,
+    /// LastQty [32], < 0 for sells
+    pub signed_qty: Decimal,
+    pub last_px: Decimal,
+    /// Commission [12], 0 when not reported
+    pub commission: f64,
+}
+
+impl Fill {
+    /// `from_execution_report`
+    ///
+    /// fill of a single leg order or of one leg of a multi-leg order, None for any other report
+    pub fn from_execution_report(msg: &RawMessage) -> Option<Fill> {
+        if msg.msg_type() != "8" || msg.get(150) != Some("F") || msg.get(442) == Some("3") {
+            return None;
+        }
+        let (symbol, side) = if msg.get(442) == Some("2") { (msg.get(600), msg.get(624)) } else { (msg.get(55), msg.get(54)) };
+        let decimal = |tag: u32| msg.get(tag).and_then(|value| value.parse::<Decimal>().ok());
+        let (Some(symbol), Some(last_qty), Some(last_px)) = (symbol, decimal(32), decimal(31)) else { return None };
+        Some(Fill {
+            exec_id: msg.get(17).map(str::to_string),
+            account: msg.get(1).unwrap_or_default().to_string(),
+            symbol: symbol.to_string(),
+            signed_qty: if side == Some("2") { -last_qty } else { last_qty },
+            last_px,
+            commission: msg.get(12).and_then(|value| value.parse::<f64>().ok()).unwrap_or(0.0),
+        })
+    }
+}
+
+/// `Position`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Position {
+    pub account: String,
+    pub symbol: String,
+    /// signed quantity, > 0 long
+    pub quantity: f64,
+    /// average entry price of the open quantity, 0 when flat
+    pub avg_px: f64,
+    /// PnL of closed quantity against the average entry price, fees not included
+    pub realized_pnl: f64,
+    /// sum of Commission [12]
+    pub fees: f64,
+}
+
+impl Position {
+    pub fn new(account: &str, symbol: &str) -> Self {
+        Position { account: account.to_string(), symbol: symbol.to_string(), ..Position::default() }
+    }
+
+    pub fn is_flat(&self) -> bool {
+        self.quantity.abs() <= QTY_EPSILON
+    }
+
+    /// `apply`
+    ///
+    /// adds fill to the position, returns PnL realized by it (fees not included)
+    /// n.b. a fill reducing the position keeps the average entry price, one flipping it opens the remainder at LastPx [31]
+    pub fn apply(&mut self, signed_qty: f64, last_px: f64, commission: f64) -> f64 {
+        let mut realized: f64 = 0.0;
+        if self.quantity * signed_qty < 0.0 {
+            let closed: f64 = signed_qty.abs().min(self.quantity.abs());
+            realized = closed * (last_px - self.avg_px) * self.quantity.signum();
+        }
+        let quantity: f64 = self.quantity + signed_qty;
+        if quantity.abs() <= QTY_EPSILON {
+            self.quantity = 0.0;
+            self.avg_px = 0.0;
+        } else if self.quantity * quantity <= 0.0 {
+            self.quantity = quantity;
+            self.avg_px = last_px;
+        } else if quantity.abs() > self.quantity.abs() {
+            self.avg_px = (self.avg_px * self.quantity + last_px * signed_qty) / quantity;
+            self.quantity = quantity;
+        } else {
+            self.quantity = quantity;
+        }
+        self.realized_pnl += realized;
+        self.fees += commission;
+        realized
+    }
+
+    /// `unrealized_pnl`
+    ///
+    /// PnL of the open quantity valued at `mark_px`
+    pub fn unrealized_pnl(&self, mark_px: f64) -> f64 {
+        (mark_px - self.avg_px) * self.quantity
+    }
+}
+
+/// `PositionKeeper`
+///
+/// positions per account and symbol, fed with `on_execution_report`
+#[derive(Debug, Default)]
+pub struct PositionKeeper {
+    positions: HashMap<(String, String), Position>,
+    exec_ids: HashSet<String>,
+    mark_px: HashMap<String, f64>,
+}
+
+impl PositionKeeper {
+    pub fn new() -> Self {
+        PositionKeeper::default()
+    }
+
+    pub fn shared(self) -> SharedPositionKeeper {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// `on_execution_report`
+    ///
+    /// applies fill reports to the position of their account and symbol, returns the position changed
+    /// n.b. a report with an ExecID [17] already applied is ignored
+    pub fn on_execution_report(&mut self, msg: &RawMessage) -> Option<&Position> {
+        let fill: Fill = Fill::from_execution_report(msg)?;
+        if let Some(exec_id) = &fill.exec_id {
+            if !self.exec_ids.insert(exec_id.clone()) {
+                return None;
+            }
+        }
+        let position: &mut Position =
+            self.positions.entry((fill.account.clone(), fill.symbol.clone())).or_insert_with(|| Position::new(&fill.account, &fill.symbol));
+        position.apply(fill.signed_qty.to_f64(), fill.last_px.to_f64(), fill.commission);
+        info!(
+            "Positions - {} {} fill {} @ {}, position {} @ {} realized PnL {} fees {}",
+            fill.account,
+            fill.symbol,
+            fill.signed_qty,
+            fill.last_px,
+            position.quantity,
+            position.avg_px,
+            position.realized_pnl,
+            position.fees
+        );
+        Some(position)
+    }
+
+    /// `realized_pnl`
+    ///
+    /// realized PnL less fees of every position
+    pub fn realized_pnl(&self) -> f64 {
+        self.positions.values().map(|position| position.realized_pnl - position.fees).sum()
+    }
+
+    pub fn position(&self, account: &str, symbol: &str) -> Option<&Position> {
+        self.positions.get(&(account.to_string(), symbol.to_string()))
+    }
+
+    /// `positions`
+    ///
+    /// every position sorted by account and symbol, flat ones included
+    pub fn positions(&self) -> Vec<&Position> {
+        let mut positions: Vec<&Position> = self.positions.values().collect();
+        positions.sort_by(|a, b| (&a.account, &a.symbol).cmp(&(&b.account, &b.symbol)));
+        positions
+    }
+
+    /// `set_mark_price`
+    ///
+    /// price open positions in `symbol` are valued at, for every account
+    pub fn set_mark_price(&mut self, symbol: &str, mark_px: f64) {
+        self.mark_px.insert(symbol.to_string(), mark_px);
+    }
+
+    pub fn mark_price(&self, symbol: &str) -> Option<f64> {
+        self.mark_px.get(symbol).copied()
+    }
+
+    /// `unrealized_pnl`
+    ///
+    /// PnL of the open quantity at the mark price, None when no mark price was set for the symbol
+    pub fn unrealized_pnl(&self, position: &Position) -> Option<f64> {
+        self.mark_price(&position.symbol).map(|mark_px| position.unrealized_pnl(mark_px))
+    }
+}
+
+#[cfg(test)]
+mod positions_tests {
+
+    use super::PositionKeeper;
+    use crate::fix::RawMessage;
+
+    fn fill(exec_id: &str, account: &str, side: u8, quantity: f64, price: f64, commission: f64) -> RawMessage {
+        let mut msg = RawMessage::new("8");
+        msg.set(1, account)
+            .set(17, exec_id)
+            .set(150, 'F')
+            .set(55, "SOL-USD")
+            .set(54, side)
+            .set(32, quantity)
+            .set(31, price)
+            .set(12, commission);
+        msg
+    }
+
+    #[test]
+    fn test_net_position_average_entry_and_pnl() {
+        let mut keeper = PositionKeeper::new();
+        keeper.on_execution_report(&fill("E1", "A1", 1, 2.0, 100.0, 0.1));
+        keeper.on_execution_report(&fill("E2", "A1", 1, 2.0, 110.0, 0.1));
+        // same ExecID from the drop-copy session
+        assert!(keeper.on_execution_report(&fill("E2", "A1", 1, 2.0, 110.0, 0.1)).is_none());
+        let position = keeper.position("A1", "SOL-USD").unwrap();
+        assert_eq!((position.quantity, position.avg_px, position.realized_pnl), (4.0, 105.0, 0.0));
+
+        // sell 5 closes 4 at +15 and opens 1 short at 120
+        keeper.on_execution_report(&fill("E3", "A1", 2, 5.0, 120.0, 0.2));
+        let position = keeper.position("A1", "SOL-USD").unwrap().clone();
+        assert_eq!((position.quantity, position.avg_px, position.realized_pnl), (-1.0, 120.0, 60.0));
+        assert!((position.fees - 0.4).abs() < 1e-9);
+        assert!((keeper.realized_pnl() - 59.6).abs() < 1e-9);
+        assert_eq!(keeper.unrealized_pnl(&position), None);
+        keeper.set_mark_price("SOL-USD", 115.0);
+        assert_eq!(keeper.unrealized_pnl(&position), Some(5.0));
+
+        keeper.on_execution_report(&fill("E4", "A2", 2, 1.0, 100.0, 0.0));
+        assert_eq!(keeper.position("A2", "SOL-USD").map(|p| p.quantity), Some(-1.0));
+        assert_eq!(keeper.positions().len(), 2);
+    }
+}
@@ -0,0 +1,449 @@
+// src/risk/mod.rs
+//
+// Pre-trade risk checks applied locally to every outbound order, before any bytes are written
+//  - NewOrderSingle (35=D), NewOrderMultileg (35=AB) and OrderCancelReplaceRequest (35=G) are checked,
+//    cancels and other messages always pass
+//  - per symbol maximum OrderQty [38] and notional (OrderQty x Price [44])
+//  - price band around the reference price, i.e. the latest LastPx [31] or a price set with `set_reference_price`
+//  - maximum open orders and maximum orders per second
+//  - daily loss limit on realized PnL less Commission [12] read from the shared `PositionKeeper`,
+//    fills (ExecType [150] = 'F') fed to `on_execution_report` are applied to it
+//  - rejections are typed `RiskRejection`s, a rejected new order is marked Rejected in order state
+//  - a fill taking the daily loss to the limit trips the `KillSwitch` (if set)
+//
+
+use crate::fix::RawMessage;
+use crate::orders::SharedOrderManager;
+use crate::positions::{Fill, SharedPositionKeeper};
+use crate::session::{KillSwitch, KillTrigger};
+use chrono::{NaiveDate, Utc};
+use log::{error, info};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// risk gate shared between the sessions feeding it fills and the outbound handle checking orders
+pub type SharedRiskGate = Arc<Mutex<RiskGate>>;
+
+// quantities below this are treated as zero
+const QTY_EPSILON: f64 = 1e-9;
+
+/// `SymbolLimits`
+///
+/// order size limits for one symbol, None = no limit
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SymbolLimits {
+    /// maximum OrderQty [38]
+    pub max_order_qty: Option<f64>,
+    /// maximum OrderQty x Price [44], Market orders use the reference price
+    pub max_notional: Option<f64>,
+}
+
+/// `RiskLimits`
+///
+/// limits applied by `RiskGate`, None = no limit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RiskLimits {
+    /// limits for symbols without their own entry in `symbols`
+    pub default_symbol: SymbolLimits,
+    pub symbols: HashMap<String, SymbolLimits>,
+    /// maximum distance of Price [44] / StopPx [99] from the reference price, in percent
+    pub price_band_pct: Option<f64>,
+    pub max_open_orders: Option<usize>,
+    pub max_orders_per_second: Option<usize>,
+    /// new orders are rejected once realized PnL less fees for the UTC day falls to -limit
+    pub daily_loss_limit: Option<f64>,
+}
+
+impl RiskLimits {
+    pub fn new() -> Self {
+        RiskLimits::default()
+    }
+
+    pub fn symbol(mut self, symbol: &str, limits: SymbolLimits) -> Self {
+        self.symbols.insert(symbol.to_string(), limits);
+        self
+    }
+
+    pub fn price_band_pct(mut self, price_band_pct: f64) -> Self {
+        self.price_band_pct = Some(price_band_pct);
+        self
+    }
+
+    pub fn max_open_orders(mut self, max_open_orders: usize) -> Self {
+        self.max_open_orders = Some(max_open_orders);
+        self
+    }
+
+    pub fn max_orders_per_second(mut self, max_orders_per_second: usize) -> Self {
+        self.max_orders_per_second = Some(max_orders_per_second);
+        self
+    }
+
+    pub fn daily_loss_limit(mut self, daily_loss_limit: f64) -> Self {
+        self.daily_loss_limit = Some(daily_loss_limit);
+        self
+    }
+
+    /// `for_symbol`
+    ///
+    /// limits of symbol, `default_symbol` when it has no entry of its own
+    pub fn for_symbol(&self, symbol: &str) -> SymbolLimits {
+        self.symbols.get(symbol).copied().unwrap_or(self.default_symbol)
+    }
+}
+
+/// `RiskRejection`
+///
+/// reason an order was stopped by `RiskGate`
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskRejection {
+    /// required field is missing or not valid, e.g. OrderQty [38]
+    InvalidOrder {
+        tag: u32,
+    },
+    OrderQty {
+        symbol: String,
+        quantity: f64,
+        limit: f64,
+    },
+    Notional {
+        symbol: String,
+        notional: f64,
+        limit: f64,
+    },
+    PriceBand {
+        symbol: String,
+        price: f64,
+        reference: f64,
+        band_pct: f64,
+    },
+    OpenOrders {
+        open: usize,
+        limit: usize,
+    },
+    OrderRate {
+        limit: usize,
+    },
+    DailyLoss {
+        loss: f64,
+        limit: f64,
+    },
+}
+
+impl fmt::Display for RiskRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskRejection::InvalidOrder { tag } => write!(f, "order has no valid value for tag {tag}"),
+            RiskRejection::OrderQty { symbol, quantity, limit } => write!(f, "{symbol} quantity {quantity} exceeds limit {limit}"),
+            RiskRejection::Notional { symbol, notional, limit } => write!(f, "{symbol} notional {notional} exceeds limit {limit}"),
+            RiskRejection::PriceBand { symbol, price, reference, band_pct } => {
+                write!(f, "{symbol} price {price} is more than {band_pct}% from reference {reference}")
+            }
+            RiskRejection::OpenOrders { open, limit } => write!(f, "{open} open orders, limit is {limit}"),
+            RiskRejection::OrderRate { limit } => write!(f, "more than {limit} orders per second"),
+            RiskRejection::DailyLoss { loss, limit } => write!(f, "daily loss {loss} reached limit {limit}"),
+        }
+    }
+}
+
+impl std::error::Error for RiskRejection {}
+
+/// `RiskGate`
+///
+/// checks outbound orders against `RiskLimits`, see `OutboundHandle::set_risk_gate`
+#[derive(Debug)]
+pub struct RiskGate {
+    limits: RiskLimits,
+    orders: SharedOrderManager,
+    reference_px: HashMap<String, f64>,
+    sent: VecDeque<Instant>,
+    positions: SharedPositionKeeper,
+    day: NaiveDate,
+    // realized PnL less fees of `positions` when the UTC day started, the daily loss is counted from it
+    day_start_pnl: f64,
+    kill_switch: Option<KillSwitch>,
+}
+
+impl RiskGate {
+    /// `new`
+    ///
+    /// open orders are counted in `orders`, rejected new orders are marked Rejected there,
+    /// realized PnL and fees are read from `positions`
+    pub fn new(limits: RiskLimits, orders: SharedOrderManager, positions: SharedPositionKeeper) -> Self {
+        let day_start_pnl: f64 = positions.lock().unwrap().realized_pnl();
+        RiskGate {
+            limits,
+            orders,
+            reference_px: HashMap::new(),
+            sent: VecDeque::new(),
+            positions,
+            day: Utc::now().date_naive(),
+            day_start_pnl,
+            kill_switch: None,
+        }
+    }
+
+    pub fn shared(self) -> SharedRiskGate {
+        Arc::new(Mutex::new(self))
+    }
+
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// `set_kill_switch`
+    ///
+    /// `kill_switch` is tripped once realized PnL less fees reaches the daily loss limit
+    pub fn set_kill_switch(&mut self, kill_switch: KillSwitch) {
+        self.kill_switch = Some(kill_switch);
+    }
+
+    /// `set_reference_price`
+    ///
+    /// price band reference for symbol until the next fill on it
+    pub fn set_reference_price(&mut self, symbol: &str, price: f64) {
+        self.reference_px.insert(symbol.to_string(), price);
+    }
+
+    pub fn reference_price(&self, symbol: &str) -> Option<f64> {
+        self.reference_px.get(symbol).copied()
+    }
+
+    /// `realized_pnl`
+    ///
+    /// realized PnL less fees for the current UTC day
+    pub fn realized_pnl(&self) -> f64 {
+        self.positions.lock().unwrap().realized_pnl() - self.day_start_pnl
+    }
+
+    /// `check`
+    ///
+    /// checks an outbound message, orders passing are counted towards the order rate
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - order breaks one of the limits, a rejected NewOrderSingle / NewOrderMultileg is marked Rejected in order state
+    pub fn check(&mut self, msg: &RawMessage) -> Result<(), RiskRejection> {
+        self.check_at(msg, Instant::now())
+    }
+
+    fn check_at(&mut self, msg: &RawMessage, now: Instant) -> Result<(), RiskRejection> {
+        if !matches!(msg.msg_type(), "D" | "G" | "AB") {
+            return Ok(());
+        }
+        match self.check_order(msg, now) {
+            Ok(()) => {
+                self.sent.push_back(now);
+                Ok(())
+            }
+            Err(rejection) => {
+                let cl_ord_id: &str = msg.get(11).unwrap_or_default();
+                error!("Risk - order {cl_ord_id:?} rejected: {rejection}");
+                if msg.msg_type() != "G" {
+                    self.orders.lock().unwrap().reject_local(cl_ord_id, &format!("Risk check: {rejection}"));
+                }
+                Err(rejection)
+            }
+        }
+    }
+
+    fn check_order(&mut self, msg: &RawMessage, now: Instant) -> Result<(), RiskRejection> {
+        let number = |tag: u32| -> Result<Option<f64>, RiskRejection> {
+            msg.get(tag).map(|value| value.parse::<f64>().map_err(|_| RiskRejection::InvalidOrder { tag })).transpose()
+        };
+        let quantity: f64 = number(38)?.ok_or(RiskRejection::InvalidOrder { tag: 38 })?;
+
+        // size, notional and price band, legs of a multi-leg order by their own symbol and ratio
+        if msg.msg_type() == "AB" {
+            let legs = msg.fields().iter().skip_while(|(tag, _)| *tag != 555).skip(1);
+            let mut symbol: Option<&str> = None;
+            for (tag, value) in legs {
+                match tag {
+                    600 => symbol = Some(value),
+                    623 => {
+                        let ratio: f64 = value.parse().map_err(|_| RiskRejection::InvalidOrder { tag: 623 })?;
+                        self.check_size(symbol.unwrap_or_default(), quantity * ratio, None)?;
+                    }
+                    _ => {}
+                }
+            }
+        } else {
+            let symbol: &str = msg.get(55).ok_or(RiskRejection::InvalidOrder { tag: 55 })?;
+            let price: Option<f64> = number(44)?;
+            self.check_size(symbol, quantity, price.or_else(|| self.reference_price(symbol)))?;
+            for price in [price, number(99)?].into_iter().flatten() {
+                self.check_band(symbol, price)?;
+            }
+        }
+
+        // replacing an order does not add an open order, nor does it add to the loss
+        if msg.msg_type() != "G" {
+            if let Some(limit) = self.limits.max_open_orders {
+                let cl_ord_id: Option<&str> = msg.get(11);
+                let open: usize =
+                    self.orders.lock().unwrap().open_orders().filter(|order| Some(order.cl_ord_id.as_str()) != cl_ord_id).count();
+                if open >= limit {
+                    return Err(RiskRejection::OpenOrders { open, limit });
+                }
+            }
+            if let Some(limit) = self.limits.daily_loss_limit {
+                self.roll_day();
+                let realized_pnl: f64 = self.realized_pnl();
+                if realized_pnl <= -limit {
+                    return Err(RiskRejection::DailyLoss { loss: -realized_pnl, limit });
+                }
+            }
+        }
+        if let Some(limit) = self.limits.max_orders_per_second {
+            while self.sent.front().map_or(false, |sent| now.duration_since(*sent) >= Duration::from_secs(1)) {
+                self.sent.pop_front();
+            }
+            if self.sent.len() >= limit {
+                return Err(RiskRejection::OrderRate { limit });
+            }
+        }
+        Ok(())
+    }
+
+    fn check_size(&self, symbol: &str, quantity: f64, price: Option<f64>) -> Result<(), RiskRejection> {
+        let limits: SymbolLimits = self.limits.for_symbol(symbol);
+        if let Some(limit) = limits.max_order_qty {
+            if quantity > limit + QTY_EPSILON {
+                return Err(RiskRejection::OrderQty { symbol: symbol.to_string(), quantity, limit });
+            }
+        }
+        if let (Some(limit), Some(price)) = (limits.max_notional, price) {
+            let notional: f64 = quantity * price.abs();
+            if notional > limit {
+                return Err(RiskRejection::Notional { symbol: symbol.to_string(), notional, limit });
+            }
+        }
+        Ok(())
+    }
+
+    fn check_band(&self, symbol: &str, price: f64) -> Result<(), RiskRejection> {
+        match (self.limits.price_band_pct, self.reference_price(symbol)) {
+            (Some(band_pct), Some(reference)) if (price - reference).abs() > reference.abs() * band_pct / 100.0 => {
+                Err(RiskRejection::PriceBand { symbol: symbol.to_string(), price, reference, band_pct })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `on_execution_report`
+    ///
+    /// applies a fill to the shared `PositionKeeper` and updates the reference price from it, then checks the daily loss
+    /// n.b. the keeper applies a report once by ExecID [17], so fills can be fed from order-entry and drop-copy sessions
+    /// and by a session also feeding the keeper, leg fills (MultiLegReportingType [442] = '2') are used, the package report is not
+    pub fn on_execution_report(&mut self, msg: &RawMessage) {
+        let Some(fill) = Fill::from_execution_report(msg) else { return };
+        self.roll_day();
+        if self.positions.lock().unwrap().on_execution_report(msg).is_some() {
+            self.reference_px.insert(fill.symbol.clone(), fill.last_px.to_f64());
+        }
+        let realized_pnl: f64 = self.realized_pnl();
+        info!("Risk - {} fill {} @ {}, realized PnL today {realized_pnl}", fill.symbol, fill.signed_qty, fill.last_px);
+        if let (Some(limit), Some(kill_switch)) = (self.limits.daily_loss_limit, &self.kill_switch) {
+            if realized_pnl <= -limit {
+                kill_switch.trip(KillTrigger::RiskBreach(RiskRejection::DailyLoss { loss: -realized_pnl, limit }));
+            }
+        }
+    }
+
+    fn roll_day(&mut self) {
+        let today: NaiveDate = Utc::now().date_naive();
+        if today != self.day {
+            info!("Risk - new day {today}, realized PnL {} reset", self.realized_pnl());
+            self.day = today;
+            self.day_start_pnl = self.positions.lock().unwrap().realized_pnl();
+        }
+    }
+}
+
+#[cfg(test)]
+mod risk_tests {
+
+    use super::{RiskGate, RiskLimits, RiskRejection, SymbolLimits};
+    use crate::fix::RawMessage;
+    use crate::orders::{OrdStatus, OrderManager};
+    use crate::positions::PositionKeeper;
+    use crate::session::{KillSwitch, KillTrigger};
+    use std::time::{Duration, Instant};
+
+    fn order(cl_ord_id: &str, side: u8, quantity: f64, price: f64) -> RawMessage {
+        let mut msg = RawMessage::new("D");
+        msg.set(11, cl_ord_id).set(55, "SOL-USD").set(54, side).set(38, quantity).set(40, 2).set(44, price);
+        msg
+    }
+
+    fn fill(exec_id: &str, side: u8, quantity: f64, price: f64, commission: f64) -> RawMessage {
+        let mut msg = RawMessage::new("8");
+        msg.set(17, exec_id).set(150, 'F').set(55, "SOL-USD").set(54, side).set(32, quantity).set(31, price).set(12, commission);
+        msg
+    }
+
+    #[test]
+    fn test_size_notional_and_price_band() {
+        let orders = OrderManager::shared();
+        let limits =
+            RiskLimits::new().symbol("SOL-USD", SymbolLimits { max_order_qty: Some(10.0), max_notional: Some(2000.0) }).price_band_pct(5.0);
+        let mut gate = RiskGate::new(limits, orders.clone(), PositionKeeper::new().shared());
+
+        let fat_finger = order("C1", 2, 100.0, 388.0);
+        orders.lock().unwrap().track_new(&fat_finger).unwrap();
+        assert!(matches!(gate.check(&fat_finger), Err(RiskRejection::OrderQty { limit, .. }) if limit == 10.0));
+        assert_eq!(orders.lock().unwrap().get("C1").unwrap().ord_status, OrdStatus::Rejected);
+        assert!(matches!(gate.check(&order("C2", 2, 6.0, 388.0)), Err(RiskRejection::Notional { .. })));
+
+        // no reference price yet, band applies after the first fill
+        assert!(gate.check(&order("C3", 2, 2.0, 500.0)).is_ok());
+        gate.on_execution_report(&fill("E1", 1, 1.0, 388.0, 0.0));
+        assert!(matches!(gate.check(&order("C4", 2, 2.0, 500.0)), Err(RiskRejection::PriceBand { reference, .. }) if reference == 388.0));
+        assert!(gate.check(&order("C5", 2, 2.0, 390.0)).is_ok());
+    }
+
+    #[test]
+    fn test_open_orders_and_order_rate() {
+        let orders = OrderManager::shared();
+        let limits = RiskLimits::new().max_open_orders(2).max_orders_per_second(3);
+        let mut gate = RiskGate::new(limits, orders.clone(), PositionKeeper::new().shared());
+        let start = Instant::now();
+
+        for cl_ord_id in ["C1", "C2"] {
+            orders.lock().unwrap().track_new(&order(cl_ord_id, 1, 1.0, 100.0)).unwrap();
+            assert!(gate.check_at(&order(cl_ord_id, 1, 1.0, 100.0), start).is_ok());
+        }
+        assert!(matches!(gate.check_at(&order("C3", 1, 1.0, 100.0), start), Err(RiskRejection::OpenOrders { open: 2, limit: 2 })));
+
+        // replacing does not add an open order but counts towards the rate
+        let mut replace = order("C4", 1, 2.0, 101.0);
+        replace.set(35, "G").set(41, "C2");
+        assert!(gate.check_at(&replace, start).is_ok());
+        assert!(matches!(gate.check_at(&replace, start), Err(RiskRejection::OrderRate { limit: 3 })));
+        assert!(gate.check_at(&replace, start + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_daily_loss_limit_from_fills() {
+        let positions = PositionKeeper::new().shared();
+        let mut gate = RiskGate::new(RiskLimits::new().daily_loss_limit(10.0), OrderManager::shared(), positions.clone());
+        let kill_switch = KillSwitch::new();
+        gate.set_kill_switch(kill_switch.clone());
+
+        // long 2 @ 100, sold @ 95 less fees, loss 10 + 1 reaches the limit, ExecIDs seen twice count once
+        gate.on_execution_report(&fill("E1", 1, 2.0, 100.0, 0.5));
+        assert!(!kill_switch.is_tripped());
+        gate.on_execution_report(&fill("E2", 2, 2.0, 95.0, 0.5));
+        assert!(matches!(kill_switch.trigger(), Some(KillTrigger::RiskBreach(RiskRejection::DailyLoss { .. }))));
+        positions.lock().unwrap().on_execution_report(&fill("E2", 2, 2.0, 95.0, 0.5));
+        gate.on_execution_report(&fill("E2", 2, 2.0, 95.0, 0.5));
+        assert_eq!((gate.realized_pnl(), positions.lock().unwrap().realized_pnl()), (-11.0, -11.0));
+        assert!(positions.lock().unwrap().position("", "SOL-USD").unwrap().is_flat());
+        assert!(matches!(gate.check(&order("C1", 1, 1.0, 100.0)), Err(RiskRejection::DailyLoss { limit, .. }) if limit == 10.0));
+    }
+}
@@ -0,0 +1,99 @@
+use client_rust_fix::fix::RawMessage;
+use client_rust_fix::orders::{ExecutionReport, MassCancelReport, MassCancelScope, SharedOrderManager};
+use client_rust_fix::session::{run, FixApplication, InboundEvent, OutboundHandle};
+use log::{error,info};
+use quickfix::Message;
+use std::{sync::mpsc::{Receiver, RecvTimeoutError}, time::Duration};
+use crate::factory::FixMessageFactory;
+
+/// `cancel_all`
+///
+/// sends OrderMassCancelRequest (35=q) for scope and waits for the OrderMassCancelReport (35=r),
+/// ExecutionReports for the cancelled orders are applied to `orders` by the session reader
+///
+/// # Errors
+///
+/// function will return error if
+/// - request is rejected (35=3 / 35=j, or MassCancelResponse [531] = '0')
+/// - OrderMassCancelReport cannot be parsed
+/// - no response is received in time or session is closed
+pub fn cancel_all(
+    apikey: &str, outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, orders: &SharedOrderManager, scope: &MassCancelScope,
+    seqnum: u32,
+) -> Result<MassCancelReport, String> {
+    let request: Message = FixMessageFactory::order_mass_cancel_request(apikey, scope, seqnum)
+        .map_err(|error| format!("Error creating Order Mass Cancel Request msg {error:?}"))?;
+    let request_text: String =
+        request.to_fix_string().map_err(|error| format!("Error converting Order Mass Cancel Request msg {error:?}"))?;
+    let open_orders: usize = orders.lock().unwrap().open_orders().count();
+
+    info!("Cancelling open orders ({scope}), {open_orders} open in local order state");
+    println!("Cancelling open orders ({scope}), {open_orders} open in local order state");
+    outbound.send(&request_text).map_err(|error| error.to_string())?;
+
+    const LIMIT: u32 = 10;
+    let mut response: MassCancelResponse = MassCancelResponse { scope, outcome: None };
+    for count in 1..=LIMIT {
+        match run(&mut response, inbound, Duration::from_millis(5000), |response| response.outcome.is_some()) {
+            Ok(()) => break,
+            Err(RecvTimeoutError::Timeout) => println!(" -> No mass cancel report within 5000 ms [{count:?}/{LIMIT:?}]"),
+            Err(RecvTimeoutError::Disconnected) => {
+                return response.outcome.unwrap_or_else(|| Err("Session closed while awaiting mass cancel report".to_string()));
+            }
+        }
+    }
+    response.outcome.unwrap_or_else(|| Err(format!("No mass cancel report for ({scope})")))
+}
+
+// waits for the OrderMassCancelReport (35=r), or the rejection of OrderMassCancelRequest (372=q)
+struct MassCancelResponse<'a> {
+    scope: &'a MassCancelScope,
+    outcome: Option<Result<MassCancelReport, String>>,
+}
+
+impl FixApplication for MassCancelResponse<'_> {
+    fn on_mass_cancel_report(&mut self, msg: &RawMessage) {
+        let report: MassCancelReport = match MassCancelReport::from_raw(msg) {
+            Ok(report) => report,
+            Err(error) => {
+                self.outcome = Some(Err(error.to_string()));
+                return;
+            }
+        };
+        if report.is_rejected() {
+            error!("Mass cancel rejected [{}]", msg.to_display());
+            self.outcome = Some(Err(format!(
+                "Mass cancel rejected (reason {}): {}",
+                report.reject_reason.unwrap_or_default(),
+                report.text.unwrap_or_default()
+            )));
+            return;
+        }
+        info!("Mass cancel ({}) cancelled {} order(s)", self.scope, report.total_affected);
+        println!("Mass cancel ({}) cancelled {} order(s)", self.scope, report.total_affected);
+        for affected in &report.affected {
+            println!(" -> cancelled Client Order {:?} Order {:?}", affected.orig_cl_ord_id, affected.order_id);
+        }
+        self.outcome = Some(Ok(report));
+    }
+
+    fn on_execution_report(&mut self, _report: &ExecutionReport, msg: &RawMessage) {
+        println!("Mass cancel ExecutionReport [{}]", msg.to_display());
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        if msg.get(372) == Some("q") {
+            self.outcome = Some(Err(format!("Mass cancel rejected: {}", msg.get(58).unwrap_or_default())));
+        } else {
+            println!("Other Msg [{}]", msg.to_display());
+        }
+    }
+
+    fn on_message(&mut self, msg: &RawMessage) {
+        println!("Other Msg [{}]", msg.to_display());
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        self.outcome = Some(Err(format!("Session disconnected while awaiting mass cancel report: {reason}")));
+    }
+}
@@ -0,0 +1,112 @@
+use client_rust_fix::fix::RawMessage;
+use client_rust_fix::orders::{ExecutionReport, OrdStatus, Order, SharedOrderManager};
+use client_rust_fix::session::{run, FixApplication, InboundEvent, OutboundHandle};
+use log::{error,info};
+use quickfix::Message;
+use std::{sync::mpsc::{Receiver, RecvTimeoutError}, time::Duration};
+use crate::single_leg_order::cancel_single_order;
+
+/// `send_multi_leg_order`
+///
+/// sends NewOrderMultileg (35=AB), waits until it is confirmed 'New' or done, then cancels it by ClOrdID [11]
+/// when `cancel` is true and the order is still working. Leg fills (MultiLegReportingType [442] = '2') are printed
+/// as they arrive, returns the final order state with the filled quantity of every leg
+///
+/// # Errors
+///
+/// function will return error if
+/// - order cannot be tracked or sent
+/// - no response is received in time or session is closed
+pub fn send_multi_leg_order(
+    apikey: &str, outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, orders: &SharedOrderManager, order: Message, seqnum: u32,
+    cancel: bool,
+) -> Result<Order, String> {
+    let order_text: String = order.to_fix_string().map_err(|error| format!("Error converting Multi Leg Order msg {error:?}"))?;
+    let order: RawMessage = RawMessage::parse(&order_text).map_err(|error| error.to_string())?;
+    let cl_ord_id: String = order.get(11).unwrap_or_default().to_string();
+    orders.lock().unwrap().track_new(&order).map_err(|error| error.to_string())?;
+
+    info!("Sending Multi Leg Order {cl_ord_id:?} {}", order.to_display());
+    println!("Sending Multi Leg Order {cl_ord_id:?} {}", order.to_display());
+    outbound.send_raw(order).map_err(|error| {
+        orders.lock().unwrap().reject_local(&cl_ord_id, &error.to_string());
+        error.to_string()
+    })?;
+
+    const LIMIT: u32 = 10;
+    let mut response: MultiLegResponse = MultiLegResponse { cl_ord_id: &cl_ord_id, orders, confirmed: false, done: false };
+    for count in 1..=LIMIT {
+        match run(&mut response, inbound, Duration::from_millis(5000), |response| response.done) {
+            Ok(()) => break,
+            Err(RecvTimeoutError::Timeout) => println!(" -> No Multi Leg Order response within 5000 ms [{count:?}/{LIMIT:?}]"),
+            Err(RecvTimeoutError::Disconnected) => return Err(format!("Session closed while waiting for Multi Leg Order {cl_ord_id:?}")),
+        }
+    }
+    let confirmed: bool = response.confirmed;
+
+    let working: bool = orders.lock().unwrap().get(&cl_ord_id).map_or(false, Order::is_working);
+    if cancel && confirmed && working {
+        cancel_single_order(apikey, outbound, inbound, orders, &cl_ord_id, seqnum);
+    }
+    let order: Order = orders.lock().unwrap().get(&cl_ord_id).cloned().ok_or(format!("no order known for Client Order {cl_ord_id}"))?;
+    for leg in &order.legs {
+        println!(" -> leg {} side {} ratio {} filled {} @ {}", leg.symbol, leg.side, leg.ratio_qty, leg.cum_qty, leg.avg_px);
+    }
+    if !confirmed && !order.ord_status.is_terminal() {
+        error!("No response for Multi Leg Order {cl_ord_id:?}");
+        return Err(format!("No response for Multi Leg Order {cl_ord_id:?}"));
+    }
+    Ok(order)
+}
+
+// waits for a multileg order to be confirmed 'New' or done, printing leg fills (442=2) as they arrive
+struct MultiLegResponse<'a> {
+    cl_ord_id: &'a str,
+    orders: &'a SharedOrderManager,
+    confirmed: bool,
+    done: bool,
+}
+
+impl FixApplication for MultiLegResponse<'_> {
+    fn on_execution_report(&mut self, report: &ExecutionReport, msg: &RawMessage) {
+        if report.cl_ord_id.as_deref() != Some(self.cl_ord_id) {
+            println!("MultiLegOrder: other msg received {}", msg.to_display());
+            return;
+        }
+        if msg.get(442) == Some("2") {
+            info!("Multi Leg Order {:?} leg {:?} filled {:?} @ {:?}", self.cl_ord_id, msg.get(600), msg.get(32), msg.get(31));
+            println!("Multi Leg Order {:?} leg {:?} filled {:?} @ {:?}", self.cl_ord_id, msg.get(600), msg.get(32), msg.get(31));
+            return;
+        }
+        let ord_status: Option<OrdStatus> = self.orders.lock().unwrap().get(self.cl_ord_id).map(|o| o.ord_status);
+        match ord_status {
+            Some(ord_status @ (OrdStatus::New | OrdStatus::PartiallyFilled)) => {
+                info!("Multi Leg Order {:?} confirmed with status {ord_status}", self.cl_ord_id);
+                self.confirmed = true;
+                self.done = true;
+            },
+            Some(ord_status) if ord_status.is_terminal() => {
+                info!("Multi Leg Order {:?} is done with status {ord_status}", self.cl_ord_id);
+                println!("Multi Leg Order {:?} is done with status {ord_status}", self.cl_ord_id);
+                self.done = true;
+            },
+            _ => {},
+        }
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        println!("MultiLegOrder: other msg received {}", msg.to_display());
+    }
+
+    fn on_cancel_reject(&mut self, msg: &RawMessage) {
+        println!("MultiLegOrder: other msg received {}", msg.to_display());
+    }
+
+    fn on_message(&mut self, msg: &RawMessage) {
+        println!("MultiLegOrder: other msg received {}", msg.to_display());
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        error!("Session disconnected while waiting for Multi Leg Order {:?}: {reason}", self.cl_ord_id);
+    }
+}
@@ -0,0 +1,183 @@
+use client_rust_fix::fix::RawMessage;
+use client_rust_fix::orders::{ExecType, ExecutionReport, MassStatusSnapshot, Order, SharedOrderManager};
+use client_rust_fix::session::{run, FixApplication, InboundEvent, OutboundHandle};
+use log::{error,info};
+use quickfix::Message;
+use quickfix_msg44::field_types::Side;
+use std::{sync::mpsc::{Receiver, RecvTimeoutError}, time::{Duration, SystemTime, UNIX_EPOCH}};
+use crate::factory::FixMessageFactory;
+
+/// `request_order_status`
+///
+/// sends OrderStatusRequest (35=H) for an order in `orders`, returns order state once the status report
+/// (ExecType [150] = 'I') was applied by the session reader
+///
+/// # Errors
+///
+/// function will return error if
+/// - order is not known
+/// - request is rejected (35=3 / 35=j)
+/// - no response is received in time or session is closed
+pub fn request_order_status(
+    apikey: &str, outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, orders: &SharedOrderManager, cl_ord_id: &str, seqnum: u32,
+) -> Result<Order, String> {
+    let order: Order = orders.lock().unwrap().get(cl_ord_id).cloned().ok_or(format!("no order known for Client Order {cl_ord_id}"))?;
+    let side: Side = match order.side.as_str() {
+        "1" => Side::Buy,
+        "2" => Side::Sell,
+        _ => return Err(format!("invalid Side value {:?}", order.side)),
+    };
+    let exch_order_id: String = order.order_id.clone().unwrap_or_default();
+    let status_msg: Message =
+        FixMessageFactory::order_status_request(apikey, &order.cl_ord_id, &exch_order_id, side, &order.symbol, seqnum)
+            .map_err(|error| format!("Error creating Order Status Request msg {error:?}"))?;
+    let status_text: String = status_msg.to_fix_string().map_err(|error| format!("Error converting Order Status Request msg {error:?}"))?;
+
+    info!("Requesting status for Client Order {:?}", order.cl_ord_id);
+    println!("Requesting status for Client Order {:?}", order.cl_ord_id);
+    outbound.send(&status_text).map_err(|error| error.to_string())?;
+
+    const LIMIT: u32 = 10;
+    let mut response: StatusResponse = StatusResponse { cl_ord_id: &order.cl_ord_id, orders, outcome: None };
+    for count in 1..=LIMIT {
+        match run(&mut response, inbound, Duration::from_millis(5000), |response| response.outcome.is_some()) {
+            Ok(()) => break,
+            Err(RecvTimeoutError::Timeout) => println!(" -> No order status within 5000 ms [{count:?}/{LIMIT:?}]"),
+            Err(RecvTimeoutError::Disconnected) => {
+                return response.outcome.unwrap_or_else(|| Err("Session closed while awaiting order status".to_string()));
+            }
+        }
+    }
+    response.outcome.unwrap_or_else(|| Err(format!("No order status for [{cl_ord_id:?}]")))
+}
+
+/// `reconcile_orders`
+///
+/// sends OrderMassStatusRequest (35=AF) for all open orders (or those for `symbol`) and collects the
+/// status reports, then reports local working orders the venue no longer knows about
+///
+/// e.g. after login, so orders placed or filled while the client was away are in `orders`
+///
+/// # Errors
+///
+/// function will return error if
+/// - request is rejected (35=3 / 35=j)
+/// - snapshot is not complete in time or session is closed
+pub fn reconcile_orders(
+    apikey: &str, outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, orders: &SharedOrderManager, symbol: Option<&str>,
+    seqnum: u32,
+) -> Result<MassStatusSnapshot, String> {
+    let mass_status_req_id: String =
+        format!("MS-{}", SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards!!").as_millis());
+    let request: Message = FixMessageFactory::order_mass_status_request(apikey, &mass_status_req_id, symbol, seqnum)
+        .map_err(|error| format!("Error creating Order Mass Status Request msg {error:?}"))?;
+    let request_text: String =
+        request.to_fix_string().map_err(|error| format!("Error converting Order Mass Status Request msg {error:?}"))?;
+
+    info!("Requesting mass status [{mass_status_req_id}] for {}", symbol.unwrap_or("all orders"));
+    println!("Requesting mass status [{mass_status_req_id}] for {}", symbol.unwrap_or("all orders"));
+    outbound.send(&request_text).map_err(|error| error.to_string())?;
+
+    const LIMIT: u32 = 10;
+    let mut response = MassStatusResponse { snapshot: MassStatusSnapshot::new(&mass_status_req_id, symbol), error: None };
+    for count in 1..=LIMIT {
+        match run(&mut response, inbound, Duration::from_millis(5000), |response| {
+            response.error.is_some() || response.snapshot.is_complete()
+        }) {
+            Ok(()) => break,
+            Err(RecvTimeoutError::Timeout) => println!(" -> No mass status report within 5000 ms [{count:?}/{LIMIT:?}]"),
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(response.error.unwrap_or_else(|| "Session closed while awaiting mass status".to_string()));
+            }
+        }
+    }
+    if let Some(error) = response.error {
+        return Err(error);
+    }
+    let snapshot: MassStatusSnapshot = response.snapshot;
+    if !snapshot.is_complete() {
+        return Err(format!("Mass status [{mass_status_req_id}] incomplete, {} report(s) received", snapshot.len()));
+    }
+
+    info!("Mass status [{mass_status_req_id}] complete with {} open order(s)", snapshot.len());
+    println!("Mass status [{mass_status_req_id}] complete with {} open order(s)", snapshot.len());
+    for order in orders.lock().unwrap().reconcile(&snapshot) {
+        error!("Order [{:?}] is {} locally but was not reported by venue", order.cl_ord_id, order.ord_status);
+        println!("Order [{:?}] is {} locally but was not reported by venue", order.cl_ord_id, order.ord_status);
+    }
+    Ok(snapshot)
+}
+
+// waits for the status report (150=I) of an order, or the rejection of OrderStatusRequest (372=H)
+struct StatusResponse<'a> {
+    cl_ord_id: &'a str,
+    orders: &'a SharedOrderManager,
+    outcome: Option<Result<Order, String>>,
+}
+
+impl FixApplication for StatusResponse<'_> {
+    fn on_execution_report(&mut self, report: &ExecutionReport, msg: &RawMessage) {
+        if report.exec_type != ExecType::OrderStatus || report.cl_ord_id.as_deref() != Some(self.cl_ord_id) {
+            println!("Other Msg [{}]", msg.to_display());
+            return;
+        }
+        self.outcome =
+            Some(self.orders.lock().unwrap().get(self.cl_ord_id).cloned().ok_or(format!("order {} no longer known", self.cl_ord_id)));
+        if let Some(Ok(current)) = &self.outcome {
+            info!(
+                "Order [{:?}] status {} cum qty {} leaves qty {}",
+                current.cl_ord_id, current.ord_status, current.cum_qty, current.leaves_qty
+            );
+            println!(
+                "Order [{:?}] status {} cum qty {} leaves qty {}",
+                current.cl_ord_id, current.ord_status, current.cum_qty, current.leaves_qty
+            );
+        }
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        if msg.get(372) == Some("H") {
+            self.outcome = Some(Err(format!("Order Status Request rejected: {}", msg.get(58).unwrap_or_default())));
+        } else {
+            println!("Other Msg [{}]", msg.to_display());
+        }
+    }
+
+    fn on_message(&mut self, msg: &RawMessage) {
+        println!("Other Msg [{}]", msg.to_display());
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        self.outcome = Some(Err(format!("Session disconnected while awaiting order status: {reason}")));
+    }
+}
+
+// collects the status reports of OrderMassStatusRequest into the snapshot, error is set once it is rejected (372=AF)
+struct MassStatusResponse {
+    snapshot: MassStatusSnapshot,
+    error: Option<String>,
+}
+
+impl FixApplication for MassStatusResponse {
+    fn on_execution_report(&mut self, _report: &ExecutionReport, msg: &RawMessage) {
+        if !self.snapshot.accept(msg) {
+            println!("Other ExecutionReport [{}]", msg.to_display());
+        }
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        if msg.get(372) == Some("AF") {
+            self.error = Some(format!("Order Mass Status Request rejected: {}", msg.get(58).unwrap_or_default()));
+        } else {
+            println!("Other Msg [{}]", msg.to_display());
+        }
+    }
+
+    fn on_message(&mut self, msg: &RawMessage) {
+        println!("Other Msg [{}]", msg.to_display());
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        self.error = Some(format!("Session disconnected while awaiting mass status: {reason}"));
+    }
+}
@@ -1,22 +1,25 @@
 use crate::utils::execute_ws_request;
-use log::info;
-use native_tls::TlsStream;
+use client_rust_fix::fix::RawMessage;
+use client_rust_fix::orders::ExecutionReport;
+use client_rust_fix::session::{dispatch, FixApplication, InboundEvent, OutboundHandle};
+use log::{error, info};
 use quickfix::Message;
-use std::{env::var, io::{ErrorKind, Read, Write}, net::TcpStream, thread::sleep, time::Duration};
+use std::{env::var, sync::mpsc::{Receiver, RecvTimeoutError}, time::Duration};
 
-pub fn rfq_publish_fix(mut tls_stream: TlsStream<TcpStream>, rfq: Message) {
+pub fn rfq_publish_fix(outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, rfq: Message) {
 
     info!("Executing RFQ publish scenario");
     println!("Executing RFQ publish scenario");
 
-    match tls_stream.write(rfq.to_fix_string() .expect("Error while sending RFQ listen message").as_bytes()) { 
-        Ok(byte_count) => println!("Sent {rfq:?} with {byte_count:?} bytes ... "),
+    match outbound.send(&rfq.to_fix_string() .expect("Error while sending RFQ listen message")) { 
+        Ok(()) => println!("Sent {rfq:?} ... "),
         Err(error) => println!("Error while sending order msg {error:?} ")
     };
 
     let mut count: u32 = 0;
     let limit_str = var("PT_PUBLISH_EPOCH").expect("Error - PT_PUBLISH_EPOCH must be set in .env file");
     let limit: u32 = limit_str.parse::<u32>().unwrap();
+    let mut app: RfqEvents = RfqEvents { label: "RFQ-Publish" };
     loop {
         println!("RFQ-Publish - checking for new response");
         count += 1;
@@ -24,33 +27,51 @@ pub fn rfq_publish_fix(mut tls_stream: TlsStream<TcpStream>, rfq: Message) {
             break;
         }
         println!("RFQ-Publish - listen epoch {count} of {limit}");
-        let mut buffer2 = [0; 1024];
-        //let byte_count: usize = tls_stream.read(&mut buffer2).expect("Error reading bytes from RFQ responses"); 
-        match tls_stream.read(&mut buffer2) {
-            Ok(byte_count) => {
-                if byte_count > 0 {
-                    // Process the read bytes
-                    let response = String::from_utf8_lossy(&buffer2[..byte_count]).replace("\x01","|");
-                    println!("RFQ-Publish  {byte_count} bytes: {response:?}");
+        match inbound.recv_timeout(Duration::from_secs(1)) {
+            Ok(event) => {
+                if !dispatch(&mut app, &event) {
+                    break;
                 }
             }
-            Err(ref error) if error.kind() == ErrorKind::WouldBlock => {
-                // WouldBlock indicates that the read operation would block (non-blocking mode)
-                println!("RFQ-Publish ... would block, continuing...");
-                continue;
+            Err(RecvTimeoutError::Timeout) => {
+                println!("RFQ-Publish - no response yet, continuing...");
             }
-            Err(ref error) if error.kind() == ErrorKind::TimedOut => {
-                // TimedOut indicates that the read operation timed out
-                println!("RFQ-Publish - Read timed out, continuing...");
-                continue;
-            }
-            Err(error) => {
-                // Handle other errors
-                eprintln!("RFQ-Publish - error reading from stream: {error:?}");
-                continue;
+            Err(RecvTimeoutError::Disconnected) => {
+                eprintln!("RFQ-Publish - session closed");
+                break;
             }
         }
-        sleep(Duration::from_secs(1));
+    }
+}
+
+/// `RfqEvents`
+///
+/// prints messages received while RFQ scenarios listen, prefixed with the scenario label
+pub(crate) struct RfqEvents {
+    pub(crate) label: &'static str,
+}
+
+impl FixApplication for RfqEvents {
+    fn on_quote_request(&mut self, msg: &RawMessage) {
+        info!("{} - QuoteRequest received: {}", self.label, msg.to_display());
+        println!("{} - QuoteRequest received: {}", self.label, msg.to_display());
+    }
+
+    fn on_execution_report(&mut self, report: &ExecutionReport, msg: &RawMessage) {
+        println!("{} - ExecutionReport with status '{}' received: {}", self.label, report.ord_status, msg.to_display());
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        eprintln!("{} - Reject received: {}", self.label, msg.to_display());
+    }
+
+    fn on_message(&mut self, msg: &RawMessage) {
+        println!("{}  received: {}", self.label, msg.to_display());
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        error!("{} - session disconnected: {reason}", self.label);
+        eprintln!("{} - session disconnected: {reason}", self.label);
     }
 }
 
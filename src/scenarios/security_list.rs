@@ -0,0 +1,110 @@
+use client_rust_fix::fix::RawMessage;
+use client_rust_fix::instruments::{self, InstrumentRegistry};
+use client_rust_fix::session::{run, FixApplication, InboundEvent, OutboundHandle};
+use log::{error, info};
+use quickfix::Message;
+use std::{env::var, path::Path, sync::mpsc::{Receiver, RecvTimeoutError}, time::{Duration, SystemTime, UNIX_EPOCH}};
+use crate::factory::FixMessageFactory;
+
+/// `request_security_list`
+///
+/// sends SecurityListRequest (35=x) for all instruments (or those of `security_type`, e.g. 'OPT') and applies
+/// every SecurityList (35=y) received to the instrument registry, until LastFragment [893] = 'Y'
+///
+/// the registry is written to PT_INSTRUMENTS_FILE when set to a JSON file, returns number of instruments received
+///
+/// # Errors
+///
+/// function will return error if
+/// - request is rejected (35=3 / 35=j) or SecurityRequestResult [560] is not '0'
+/// - list is not complete in time or session is closed
+pub fn request_security_list(
+    apikey: &str, outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, security_type: Option<&str>, seqnum: u32,
+) -> Result<usize, String> {
+    let security_req_id: String =
+        format!("SL-{}", SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards!!").as_millis());
+    let request: Message = FixMessageFactory::security_list_request(apikey, &security_req_id, security_type, seqnum)
+        .map_err(|error| format!("Error creating Security List Request msg {error:?}"))?;
+    let request_text: String = request.to_fix_string().map_err(|error| format!("Error converting Security List Request msg {error:?}"))?;
+
+    info!("Requesting security list [{security_req_id}] for {}", security_type.unwrap_or("all instruments"));
+    println!("Requesting security list [{security_req_id}] for {}", security_type.unwrap_or("all instruments"));
+    outbound.send(&request_text).map_err(|error| error.to_string())?;
+
+    const LIMIT: u32 = 10;
+    let mut response: SecurityListResponse = SecurityListResponse { security_req_id: &security_req_id, received: 0, outcome: None };
+    for count in 1..=LIMIT {
+        match run(&mut response, inbound, Duration::from_millis(5000), |response| response.outcome.is_some()) {
+            Ok(()) => break,
+            Err(RecvTimeoutError::Timeout) => println!(" -> No security list within 5000 ms [{count:?}/{LIMIT:?}]"),
+            Err(RecvTimeoutError::Disconnected) => {
+                return response.outcome.unwrap_or_else(|| Err("Session closed while awaiting security list".to_string()));
+            }
+        }
+    }
+    let received: usize = response.received;
+    let outcome: Result<usize, String> =
+        response.outcome.unwrap_or_else(|| Err(format!("Security list [{security_req_id}] incomplete, {received} instrument(s) received")));
+    if outcome.is_ok() {
+        save_cache();
+    }
+    outcome
+}
+
+// applies SecurityList (35=y) fragments for the request to the registry until LastFragment [893] = 'Y',
+// or the request is rejected (372=x)
+struct SecurityListResponse<'a> {
+    security_req_id: &'a str,
+    received: usize,
+    outcome: Option<Result<usize, String>>,
+}
+
+impl FixApplication for SecurityListResponse<'_> {
+    fn on_message(&mut self, msg: &RawMessage) {
+        if msg.msg_type() != "y" || msg.get(320) != Some(self.security_req_id) {
+            println!("Other Msg [{}]", msg.to_display());
+            return;
+        }
+        match msg.get(560).unwrap_or("0") {
+            "0" => self.received += instruments::update_registry(|registry| registry.apply_security_list(msg)),
+            result => {
+                self.outcome = Some(Err(format!(
+                    "Security List [{}] failed with SecurityRequestResult {result}: {}",
+                    self.security_req_id,
+                    msg.get(58).unwrap_or_default()
+                )));
+                return;
+            }
+        }
+        if msg.get(893).unwrap_or("Y") == "Y" {
+            info!("Security list [{}] complete with {} instrument(s)", self.security_req_id, self.received);
+            println!("Security list [{}] complete with {} instrument(s)", self.security_req_id, self.received);
+            self.outcome = Some(Ok(self.received));
+        }
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        if msg.get(372) == Some("x") {
+            self.outcome = Some(Err(format!("Security List Request rejected: {}", msg.get(58).unwrap_or_default())));
+        } else {
+            println!("Other Msg [{}]", msg.to_display());
+        }
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        self.outcome = Some(Err(format!("Session disconnected while awaiting security list: {reason}")));
+    }
+}
+
+// n.b. CSV instrument files are maintained by hand and never overwritten
+fn save_cache() {
+    let Ok(file) = var("PT_INSTRUMENTS_FILE") else { return };
+    let path: &Path = Path::new(&file);
+    if path.extension().map_or(false, |extension| extension.eq_ignore_ascii_case("csv")) {
+        return;
+    }
+    if let Err(error) = instruments::update_registry(|registry: &mut InstrumentRegistry| registry.save(path)) {
+        error!("Instrument cache not written: {error}");
+        println!("Instrument cache not written: {error}");
+    }
+}
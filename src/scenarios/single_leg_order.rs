@@ -1,195 +1,422 @@
+use client_rust_fix::fix::{Decimal, RawMessage};
+use client_rust_fix::orders::{ExecType, ExecutionReport, OrdStatus, Order, OrderError, SharedOrderManager};
+use client_rust_fix::session::{run, BatchOutcome, BatchResult, FixApplication, InboundEvent, OrderBatch, OutboundHandle};
 use log::{error,info};
-use native_tls::TlsStream;
 use quickfix::{FieldMap, Message};
-use quickfix_msg44::field_types::Side;
-use std::{io::{ErrorKind, Read, Write}, net::TcpStream, option::Option::Some, thread::sleep, time::Duration};
+use quickfix_msg44::field_types::{OrdType, Side};
+use std::{option::Option::Some, sync::mpsc::{Receiver, RecvTimeoutError}, time::Duration};
 use crate::factory::FixMessageFactory;
-use crate::utils::get_attr;
-
-pub fn send_single_order(apikey: &str,  tls_stream: &mut TlsStream<TcpStream>, order: Message, seqnum: u32, is_cancel_order: Option<bool> ) {
+use crate::utils::OrderTimeInForce;
 
+pub fn send_single_order(
+    apikey: &str, outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, orders: &SharedOrderManager, order: Message, seqnum: u32,
+    is_cancel_order: Option<bool>,
+) {
     // assign parameter for cancel orders as a bool with default == 'true'
-    let is_cancel_order = is_cancel_order.unwrap_or(true); 
+    let is_cancel_order = is_cancel_order.unwrap_or(true);
 
     println!("Executing add/cancel single order scenario");
 
+    // track order as PendingNew before sending, ExecutionReports are applied to it by the session reader
+    // n.b. an order that cannot be tracked, e.g. a duplicate ClOrdID, is not sent
+    let order_text: String = order.to_fix_string().unwrap();
+    match RawMessage::parse(&order_text).map(|msg| orders.lock().unwrap().track_new(&msg).map(|_| ())) {
+        Ok(Ok(())) => {},
+        Ok(Err(error)) => {
+            error!("Single Order not sent, not tracked in order state: {error}");
+            return;
+        },
+        Err(error) => {
+            error!("Single Order not sent, not tracked in order state: {error}");
+            return;
+        },
+    }
+
     // send the new order
-    match tls_stream.write(order.to_fix_string().unwrap().as_bytes()) { 
-        Ok(byte_count) => {
-            println!("Sent Single Order {order:?} with {byte_count:?} bytes ... ");
+    match outbound.send(&order_text) {
+        Ok(()) => {
+            println!("Sent Single Order {order:?}");
         },
         Err(error) => {
             println!("Error while sending Single Order {error:?} ");
-            error!("Error while sending Single Order {error:?} ")
+            error!("Error while sending Single Order {error:?} ");
+            // n.b. the order never left, so it must not stay open in order state
+            if let Some(cl_ord_id) = order.get_field(11) {
+                orders.lock().unwrap().reject_local(&cl_ord_id, &error.to_string());
+            }
+            return;
         }
     };
 
-    // 
-    // set count for iterating on new order messages
-    // 
-    let mut count: u32 = 1; 
-    let mut is_order_confirmed_as_new = false;
-    let limit: u32 = 10; // TODO - take this value from .env config file
-    loop {
-        println!("Count/Limit [{count}/{limit} Is Order confirmed as 'New' {is_order_confirmed_as_new}");
-        if count >= limit || is_order_confirmed_as_new {
-            break;
-        }
-        count += 1;
-
-        let orig_cl_order_id: String = order.get_field(11).unwrap();
-        let mut exch_order_id = String::new();
-        println!("SingleOrder: waiting for response for Order {orig_cl_order_id:?}");
-        let mut buffer = [0; 1024];
-        match tls_stream.read(&mut buffer) {
-            Ok(bytes) => {
-                let resp: String = String::from_utf8(buffer[..bytes].to_vec()).expect("Error loading new Order responses message");
-
-                // Split response string into individual FIX messages if received
-                let messages = split_fix_messages(&resp);
-
-                // Process 1 ... many received FIX messages
-                for message in messages.iter() {
-                    let client_order_id = get_attr(message, "11"); 
-                    info!("New Order[Client Order Id: {:?}] status [{:?}] response {:?}", client_order_id, get_attr(message, "39"), message.replace("\x01","|"));
-                    println!("New Order[Client Order Id: {:?}] status [{:?}] response {:?}", get_attr(message, "11"), get_attr(message, "39"), message.replace("\x01","|"));
-                    //
-                    // check if we received a 'New' status for order placed above
-                    //
-                    if orig_cl_order_id == client_order_id && get_attr(message, "39") == "0" {
-                        exch_order_id = get_attr(message, "37"); 
-                        info!("Order[Client order Id: {:?}] Exchange order Id [{:?}] confirmed with 'New' status", client_order_id, exch_order_id);
-                        //
-                        // Order was confirmed('New') => can now send cancel msg iif is_cancel_order == 'true'. 
-                        // n.b. order will be cancelled if session API key has "cancel-on-session-close" flag selected/
-                        // 
-                        is_order_confirmed_as_new = true; 
-                    }
+    //
+    // wait for the order to be confirmed as 'New', or to be done
+    //
+    const LIMIT: u32 = 10; // TODO - take this value from .env config file
+    let orig_cl_order_id: String = order.get_field(11).unwrap();
+    let mut response: NewOrderResponse = NewOrderResponse { cl_ord_id: &orig_cl_order_id, orders, exch_order_id: None, done: false };
+    for count in 1..=LIMIT {
+        println!("SingleOrder: waiting for response for Order {orig_cl_order_id:?} [{count}/{LIMIT}]");
+        match run(&mut response, inbound, Duration::from_millis(5000), |response| response.done) {
+            Ok(()) => break,
+            Err(RecvTimeoutError::Timeout) => println!("No response within 5000 ms, waiting again ..."),
+            Err(RecvTimeoutError::Disconnected) => {
+                if !response.done {
+                    error!("Session closed while waiting for Order {orig_cl_order_id:?}");
                 }
+                return;
             },
-            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
-                println!("WouldBlock error: Retrying in 5000 ms ...");
-                sleep(Duration::from_millis(5000));
+        }
+    }
+    let is_order_confirmed_as_new: bool = response.exch_order_id.is_some();
+    if response.done && !is_order_confirmed_as_new {
+        return;
+    }
+    let exch_order_id: String = response.exch_order_id.unwrap_or_default();
+
+    //
+    // now cancel the new order using the client(our) generated order Id
+    // - if flag was passed in with 'true' and order status is 'New'
+    //
+    println!("Cancel: {is_cancel_order:?} Client Order: {orig_cl_order_id} Exchange Order {exch_order_id}");
+    if is_cancel_order &  is_order_confirmed_as_new {
+        cancel_single_order(apikey, outbound, inbound, orders, &orig_cl_order_id, seqnum);
+    }
+}
+
+/// `cancel_single_order`
+///
+/// cancels working order known to order state by any of its ClOrdIDs, waits for the 'Cancelled' ExecutionReport
+/// n.b. OrigClOrdID [41] is the latest ClOrdID of the order, which changes when it was amended
+pub fn cancel_single_order(
+    apikey: &str, outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, orders: &SharedOrderManager, cl_ord_id: &str, seqnum: u32,
+) {
+    let Some(order) = orders.lock().unwrap().get(cl_ord_id).cloned() else {
+        error!("Cancel: no order known for Client Order {cl_ord_id:?}");
+        return;
+    };
+    let orig_cl_order_id: String = order.cl_ord_id.clone();
+    let exch_order_id: String = order.order_id.clone().unwrap_or_default();
+    let symbol: String = order.symbol.clone();
+    let side: Side = match order.side.as_str() {
+        "1" => Side::Buy,
+        "2" => Side::Sell,
+        _ => {
+            panic!("Error - invalid Side value: {:?}", order.side);
+        }
+    };
+
+    info!("using Seqnum {} for FixMsg::cancel_order", seqnum);
+    let cancel_msg: Message = FixMessageFactory::cancel_order(
+        apikey,
+        &orig_cl_order_id,
+        &exch_order_id,
+        side,
+        &symbol,
+        seqnum,
+        format!("Cancel order {orig_cl_order_id}"),
+    )
+    .unwrap();
+    info!(
+        "Cancel Id: {:?}] \n{:?}",
+        &orig_cl_order_id,
+        cancel_msg.to_fix_string().expect("Error converting cancel msg").replace("\x01", "|")
+    );
+
+    // send the cancel order
+    match outbound.send(&cancel_msg.to_fix_string().unwrap()) {
+        Ok(()) => println!("Sent Cancel msg for {orig_cl_order_id:?} ... "),
+        Err(error) => println!("Error while sending order msg {error:?} ")
+    };
+
+    let mut count: u32 = 1;
+    const LIMIT: u32 = 10;
+    let mut response: CancelResponse = CancelResponse { orig_cl_ord_id: &orig_cl_order_id, orders, done: false };
+    while count < LIMIT {
+        match run(&mut response, inbound, Duration::from_millis(5000), |response| response.done) {
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {
+                info!(" -> No Cancel Trade response within 5000 ms [{count:?}/{LIMIT:?}]");
+                println!(" -> No Cancel Trade response within 5000 ms [{count:?}/{LIMIT:?}]");
             },
-            Err(e) => panic!("Error reading bytes from cancel Order responses: {:?}", e),
         };
+        count+=1;
+    }
+    info!("Exiting from Send Single Leg order & Cancel loop [{count:?}/{LIMIT:?}]");
+    println!("Exiting Single Leg Add/Cancel Order loop after {count:?} of {LIMIT:?} msg checks");
+}
 
-        //
-        // now cancel the new order using the client(our) generated order Id
-        // - if flag was passed in with 'true' and order status is 'New'
-        //
-        println!("Cancel: {is_cancel_order:?} Client Order: {orig_cl_order_id} Exchange Order {exch_order_id}");
-        if is_cancel_order &  is_order_confirmed_as_new {
-            let symbol = order.get_field(55).unwrap();
-            let side_char = Some(get_attr(order.to_fix_string().as_ref().expect("Extracting 'side' from Fix message failed"), "34"));
-            let side: Side = match side_char.as_deref() {
-                Some("1") => Side::Buy,
-                Some("2") => Side::Sell,
-                None =>  {
-                    panic!("Error - Side value is equal to 'None'");
-                },
-                _ => {
-                    panic!("Error - invalid Side value: {:?}", side_char);
-                }
-            };
-
-            info!("using Seqnum {} for FixMsg::cancel_order", seqnum);
-            let cancel_msg: Message = FixMessageFactory::cancel_order(apikey, &orig_cl_order_id,  &exch_order_id, side, &symbol, seqnum ,format!("Cancel order {orig_cl_order_id}")).unwrap();
-            info!("Cancel Id: {:?}] \n{:?}", &orig_cl_order_id, cancel_msg.to_fix_string().expect("Error converting cancel msg").replace("\x01","|"));
-
-            // senf the cancel order
-            match tls_stream.write(cancel_msg.to_fix_string().unwrap().as_bytes()) { 
-                Ok(byte_count) => println!("Sent Cancel msg with {byte_count:?} bytes ... "),
-                Err(error) => println!("Error while sending order msg {error:?} ")
-            };
-        
-            let mut count: u32 = 1;
-            const LIMIT: u32 = 10;
-            'main_loop: while count < LIMIT {
-                let mut buffer = [0; 1024];
-                match tls_stream.read(&mut buffer) {
-                    Ok(bytes_read) => {
-                        let resp: String = String::from_utf8(buffer[..bytes_read].to_vec()).expect("Error loading new Order responses message");
-
-                        // Split the string into individual FIX messages
-                        let messages = split_fix_messages(&resp);
-
-                        // Process each FIX message
-                        for message in messages.iter() {
-                            //
-                            // TODO - replace if ... with match 
-                            //
-                            if get_attr(message, "35") == "F" {
-                                info!("Received Cancel [{:?}] response {:?}", orig_cl_order_id, message.replace("\x01", "|"));
-                                println!("\nReceived Cancel [{:?}] response {:?}", orig_cl_order_id, message.replace("\x01", "|"));
-                                break;
-                            } 
-                            else if get_attr(message, "35") == "8" {
-
-                                if get_attr(message, "41") == orig_cl_order_id  {
-                                    info!("\nExecution Report with status '{:?}\n [{:?}] ", get_attr(message, "39"), message.replace("\x01", "|"));
-                                    println!("\nExecution Report with status '{:?}\n [{:?}] ", get_attr(message, "39"), message.replace("\x01", "|"));
-
-                                    // Cancelled status == "4"
-                                    // attempt to cancel trade was a success
-                                    if get_attr(message, "39") == "4" {
-                                        println!("Order [{orig_cl_order_id:?}] cancelled - status == '4'");
-                                        break 'main_loop;
-                                    } 
-                                } else {
-                                    println!("Execution report for Client Order [{:?}] received \n {:?}", get_attr(message, "41"), message.replace("\x01", "|"));
-                                }
-
-                            }
-                            else if get_attr(message, "35") == "9" {
-                                info!("Cancel Reject Msg [{:?}] with Status {:?} ", message.replace("\x01", "|"), get_attr(message, "39"));
-                                println!("\nCancel Reject Msg [{:?}] with Status {:?} ", message.replace("\x01", "|"), get_attr(message, "39"));
-                            } else {
-                                // Some other msg - continue the loop until cancel done or loop finished
-                                println!("\nOther Msg [{:?}] with Status {:?} ", message.replace("\x01", "|"), get_attr(message, "39"));
-                            }
-                        }
-                    },
-                    Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
-                        println!("Netwprk WouldBlock error: retrying in 5000 ms ...");
-                        sleep(Duration::from_millis(5000));
-                    },
-                    Err(e) => panic!("Error reading bytes from cancel Order responses: {:?}", e),
-                };
-                // sleep for 2000ms
-                info!(" -> Sleeping for 5000 ms while awaiting Cancel Trade response [{count:?}/{LIMIT:?}]");
-                println!(" -> Sleeping for 5000 ms while awaiting Cancel Trade response [{count:?}/{LIMIT:?}]");
-                sleep(Duration::from_millis(5000));
-                count+=1;
+/// `amend_order`
+///
+/// amends price and quantity of a working Limit order with OrderCancelReplaceRequest (35=G),
+/// returns order state once the 'Replaced' ExecutionReport (150=5) was received
+///
+/// # Errors
+///
+/// function will return error if
+/// - order is not known, not working or not a Limit order
+/// - OrderCancelReject (35=9) is received, error holds its Text [58]
+/// - no response is received in time or session is closed
+pub fn amend_order(
+    apikey: &str, outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, orders: &SharedOrderManager, cl_ord_id: &str,
+    new_price: Decimal, new_qty: Decimal, seqnum: u32,
+) -> Result<Order, String> {
+    let order: Order = orders.lock().unwrap().get(cl_ord_id).cloned().ok_or(format!("no order known for Client Order {cl_ord_id}"))?;
+    if !order.is_working() || order.ord_type != "2" {
+        return Err(format!(
+            "Client Order {cl_ord_id} is not a working Limit order (OrdStatus {} OrdType {})",
+            order.ord_status, order.ord_type
+        ));
+    }
+    let side: Side = match order.side.as_str() {
+        "1" => Side::Buy,
+        "2" => Side::Sell,
+        _ => return Err(format!("invalid Side value {:?}", order.side)),
+    };
+    let exch_order_id: String = order.order_id.clone().unwrap_or_default();
+
+    // keep TimeInForce [59] and GTD expiry of the order being replaced
+    let time_in_force: OrderTimeInForce =
+        OrderTimeInForce::from_fix(&order.time_in_force, order.expire_time.as_deref(), order.expire_date.as_deref())
+            .ok_or(format!("TimeInForce {:?} of Client Order {cl_ord_id} is not supported", order.time_in_force))?;
+    let replace_msg: Message = FixMessageFactory::cancel_replace_order(
+        apikey,
+        &order.cl_ord_id,
+        &exch_order_id,
+        side,
+        &order.symbol,
+        OrdType::Limit,
+        new_price,
+        new_qty,
+        time_in_force,
+        seqnum,
+    )
+    .map_err(|error| format!("Error creating Cancel/Replace msg {error:?}"))?;
+    let replace_text: String = replace_msg.to_fix_string().map_err(|error| format!("Error converting Cancel/Replace msg {error:?}"))?;
+    let replace: RawMessage = RawMessage::parse(&replace_text).map_err(|error| error.to_string())?;
+    let new_cl_ord_id: String = replace.get(11).unwrap_or_default().to_string();
+    orders.lock().unwrap().track_replace(&replace).map_err(|error| error.to_string())?;
+
+    info!("Amending Client Order {:?} -> {new_cl_ord_id:?} price {new_price} quantity {new_qty}", order.cl_ord_id);
+    println!("Amending Client Order {:?} -> {new_cl_ord_id:?} price {new_price} quantity {new_qty}", order.cl_ord_id);
+    outbound.send_raw(replace).map_err(|error| error.to_string())?;
+
+    const LIMIT: u32 = 10;
+    let mut response: ReplaceResponse = ReplaceResponse { cl_ord_id: &new_cl_ord_id, orders, outcome: None };
+    for count in 1..=LIMIT {
+        match run(&mut response, inbound, Duration::from_millis(5000), |response| response.outcome.is_some()) {
+            Ok(()) => break,
+            Err(RecvTimeoutError::Timeout) => println!(" -> No Cancel/Replace response within 5000 ms [{count:?}/{LIMIT:?}]"),
+            Err(RecvTimeoutError::Disconnected) => {
+                return response.outcome.unwrap_or_else(|| Err("Session closed while awaiting Cancel/Replace response".to_string()));
             }
-            info!("Exiting from Send Single Leg order & Cancel loop [{count:?}/{LIMIT:?}]");
-            println!("Exiting Single Leg Add/Cancel Order loop after {count:?} of {LIMIT:?} msg checks");
-            break;
         }
     }
+    response.outcome.unwrap_or_else(|| Err(format!("No Cancel/Replace response for [{new_cl_ord_id:?}]")))
 }
 
-pub fn send_multiple_orders(apikey: &str, mut tls_stream: TlsStream<TcpStream>, orders: Vec<Message>, seqnum: u32 , cancel: bool) {
-    info!("Add-multiple-orders -> TLS: {:?}", tls_stream);
+/// `send_multiple_orders`
+///
+/// sends orders as one batch written back-to-back, collects their acks for up to `timeout` and prints the outcome
+/// of each, orders still working are cancelled afterwards when `cancel` is set
+pub fn send_multiple_orders(
+    apikey: &str, outbound: &OutboundHandle, inbound: &Receiver<InboundEvent>, order_state: &SharedOrderManager, orders: Vec<Message>,
+    seqnum: u32, cancel: bool, timeout: Duration,
+) {
+    let mut batch: Vec<RawMessage> = Vec::new();
     for order in orders {
-        info!("Sending multi/set order to be executed: {:?}", order);
-        send_single_order(apikey, &mut tls_stream, order, seqnum, Some(cancel));
+        match order
+            .to_fix_string()
+            .map_err(|error| format!("{error:?}"))
+            .and_then(|text| RawMessage::parse(&text).map_err(|error| error.to_string()))
+        {
+            Ok(msg) => batch.push(msg),
+            Err(error) => error!("Batch order not sent, message is not valid: {error}"),
+        }
     }
+    info!("Sending batch of {} order(s)", batch.len());
+    println!("Sending batch of {} order(s)", batch.len());
+    let results: Vec<BatchResult> = OrderBatch::submit(outbound, order_state, batch).wait(timeout);
+    for result in &results {
+        info!("Batch Order [{:?}] {} after {:?}", result.cl_ord_id, result.outcome, result.elapsed);
+        println!("Batch Order [{:?}] {} after {:?}", result.cl_ord_id, result.outcome, result.elapsed);
+    }
+
+    if cancel {
+        for result in results.iter().filter(|result| matches!(result.outcome, BatchOutcome::Accepted { .. })) {
+            if order_state.lock().unwrap().get(&result.cl_ord_id).map_or(false, Order::is_working) {
+                cancel_single_order(apikey, outbound, inbound, order_state, &result.cl_ord_id, seqnum);
+            }
+        }
+    }
+}
+
+// waits for the response to a new order, done once it is confirmed 'New' (exch_order_id is set) or no longer working
+struct NewOrderResponse<'a> {
+    cl_ord_id: &'a str,
+    orders: &'a SharedOrderManager,
+    exch_order_id: Option<String>,
+    done: bool,
 }
 
-fn split_fix_messages(fix_messages: &str) -> Vec<String> {
-    // Split the messages based on the "8=" tag which signifies the beginning of each message
-    let mut messages: Vec<String> = fix_messages
-        .split("8=FIX.4.4\x01")
-        .filter(|s| !s.is_empty())
-        .map(|s| format!("8=FIX.4.4\x01{}", s))
-        .collect();
-
-    // Removing any potential trailing separators
-    for message in &mut messages {
-        if message.ends_with('\x01') {
-            message.pop();
+impl FixApplication for NewOrderResponse<'_> {
+    fn on_execution_report(&mut self, report: &ExecutionReport, msg: &RawMessage) {
+        let client_order_id: &str = report.cl_ord_id.as_deref().unwrap_or_default();
+        info!("New Order[Client Order Id: {:?}] status [{}] response {:?}", client_order_id, report.ord_status, msg.to_display());
+        println!("New Order[Client Order Id: {:?}] status [{}] response {:?}", client_order_id, report.ord_status, msg.to_display());
+        //
+        // check order state for order placed, report was applied before it was published
+        //
+        if self.cl_ord_id != client_order_id {
+            return;
+        }
+        let state = self
+            .orders
+            .lock()
+            .unwrap()
+            .get(self.cl_ord_id)
+            .map(|o| (o.ord_status, o.order_id.clone().unwrap_or_default(), o.reject_reason));
+        match state {
+            Some((OrdStatus::New | OrdStatus::PartiallyFilled, order_id, _)) => {
+                info!("Order[Client order Id: {:?}] Exchange order Id [{:?}] confirmed with 'New' status", client_order_id, order_id);
+                //
+                // Order was confirmed('New') => can now send cancel msg iif is_cancel_order == 'true'.
+                // n.b. order will be cancelled if session API key has "cancel-on-session-close" flag selected/
+                //      or by the client-side kill switch (SIGINT, PT_KILL_SWITCH_FILE, ...) before Logout
+                //
+                self.exch_order_id = Some(order_id);
+                self.done = true;
+            },
+            Some((OrdStatus::Rejected, _, Some(reject_reason))) => {
+                info!("Order[Client order Id: {:?}] was rejected with reason {reject_reason}", client_order_id);
+                println!("Order[Client order Id: {:?}] was rejected with reason {reject_reason}", client_order_id);
+                self.done = true;
+            },
+            Some((ord_status, _, _)) if ord_status.is_terminal() => {
+                info!("Order[Client order Id: {:?}] is done with status {ord_status}", client_order_id);
+                println!("Order[Client order Id: {:?}] is done with status {ord_status}", client_order_id);
+                self.done = true;
+            },
+            _ => {},
         }
     }
-    messages
-}
\ No newline at end of file
+
+    fn on_decode_error(&mut self, _msg: &RawMessage, error: &OrderError) {
+        error!("New Order: ExecutionReport not decoded: {error}");
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        println!("SingleOrder: other msg received {}", msg.to_display());
+    }
+
+    fn on_cancel_reject(&mut self, msg: &RawMessage) {
+        println!("SingleOrder: other msg received {}", msg.to_display());
+    }
+
+    fn on_message(&mut self, msg: &RawMessage) {
+        println!("SingleOrder: other msg received {}", msg.to_display());
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        error!("Session disconnected while waiting for Order {:?}: {reason}", self.cl_ord_id);
+        println!("Session disconnected while waiting for Order {:?}: {reason}", self.cl_ord_id);
+        self.done = true;
+    }
+}
+
+// waits for the 'Cancelled' ExecutionReport of a cancel by OrigClOrdID [41], done once cancelled or disconnected
+struct CancelResponse<'a> {
+    orig_cl_ord_id: &'a str,
+    orders: &'a SharedOrderManager,
+    done: bool,
+}
+
+impl FixApplication for CancelResponse<'_> {
+    fn on_execution_report(&mut self, report: &ExecutionReport, msg: &RawMessage) {
+        if report.orig_cl_ord_id.as_deref() != Some(self.orig_cl_ord_id) {
+            println!("Execution report for Client Order [{:?}] received \n {:?}", msg.get(41), msg.to_display());
+            return;
+        }
+        info!("\nExecution Report with status '{}'\n [{:?}] ", report.ord_status, msg.to_display());
+        println!("\nExecution Report with status '{}'\n [{:?}] ", report.ord_status, msg.to_display());
+
+        // Cancelled status == "4"
+        // attempt to cancel trade was a success
+        let ord_status = self.orders.lock().unwrap().get(self.orig_cl_ord_id).map(|o| o.ord_status);
+        if ord_status == Some(OrdStatus::Canceled) {
+            println!("Order [{:?}] cancelled - status == '4'", self.orig_cl_ord_id);
+            self.done = true;
+        }
+    }
+
+    fn on_decode_error(&mut self, _msg: &RawMessage, error: &OrderError) {
+        error!("Cancel: ExecutionReport not decoded: {error}");
+    }
+
+    fn on_cancel_reject(&mut self, msg: &RawMessage) {
+        info!("Cancel Reject Msg [{:?}] with Status {:?} ", msg.to_display(), msg.get(39));
+        println!("\nCancel Reject Msg [{:?}] with Status {:?} ", msg.to_display(), msg.get(39));
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        println!("\nOther Msg [{}]", msg.to_display());
+    }
+
+    fn on_message(&mut self, msg: &RawMessage) {
+        // Some other msg - continue until cancel done or loop finished
+        println!("\nOther Msg [{}]", msg.to_display());
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        error!("Session disconnected while awaiting Cancel response: {reason}");
+        self.done = true;
+    }
+}
+
+// waits for the 'Replaced' ExecutionReport (150=5) or OrderCancelReject (35=9) for the new ClOrdID [11]
+struct ReplaceResponse<'a> {
+    cl_ord_id: &'a str,
+    orders: &'a SharedOrderManager,
+    outcome: Option<Result<Order, String>>,
+}
+
+impl FixApplication for ReplaceResponse<'_> {
+    fn on_execution_report(&mut self, report: &ExecutionReport, msg: &RawMessage) {
+        if report.cl_ord_id.as_deref() != Some(self.cl_ord_id) {
+            println!("Other Msg [{}]", msg.to_display());
+            return;
+        }
+        let amended: Option<Order> = self.orders.lock().unwrap().get(self.cl_ord_id).cloned();
+        match amended {
+            Some(amended) if amended.last_exec_type == Some(ExecType::Replaced) => {
+                info!("Order [{:?}] replaced - price {:?} quantity {}", self.cl_ord_id, amended.price, amended.order_qty);
+                println!("Order [{:?}] replaced - price {:?} quantity {}", self.cl_ord_id, amended.price, amended.order_qty);
+                self.outcome = Some(Ok(amended));
+            },
+            Some(amended) if !amended.is_working() => {
+                self.outcome = Some(Err(format!("Order [{:?}] is done with status {}", self.cl_ord_id, amended.ord_status)));
+            },
+            _ => println!("Cancel/Replace pending for [{:?}] {}", self.cl_ord_id, msg.to_display()),
+        }
+    }
+
+    fn on_cancel_reject(&mut self, msg: &RawMessage) {
+        if msg.get(11) != Some(self.cl_ord_id) {
+            println!("Other Msg [{}]", msg.to_display());
+            return;
+        }
+        error!("Cancel/Replace rejected [{}]", msg.to_display());
+        self.outcome = Some(Err(format!("Cancel/Replace rejected: {}", msg.get(58).unwrap_or_default())));
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        println!("Other Msg [{}]", msg.to_display());
+    }
+
+    fn on_message(&mut self, msg: &RawMessage) {
+        println!("Other Msg [{}]", msg.to_display());
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        self.outcome = Some(Err(format!("Session disconnected while awaiting Cancel/Replace response: {reason}")));
+    }
+}
@@ -0,0 +1,183 @@
+// src/session/application.rs
+//
+// Application callbacks for inbound messages, in the style of quickfix's Application
+//  - `FixApplication` has one hook per business MsgType [35], every hook does nothing by default
+//  - `dispatch` routes an `InboundEvent` to its hook, ExecutionReports (35=8) are decoded into an `ExecutionReport` first
+//  - `run` dispatches events from a session's inbound channel until the application is done
+//    n.b. hooks run on the thread calling `dispatch`, order state, risk and positions are already updated by the reader thread
+//
+
+use super::InboundEvent;
+use crate::fix::RawMessage;
+use crate::orders::{ExecutionReport, OrderError};
+use log::error;
+use std::{
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+/// `FixApplication`
+pub trait FixApplication {
+    /// Logon (35=A) accepted by the counterparty
+    fn on_logon(&mut self, _msg: &RawMessage) {}
+
+    /// Logout (35=5) received, the session is ending
+    fn on_logout(&mut self, _msg: &RawMessage) {}
+
+    /// Reject (35=3) or BusinessMessageReject (35=j), RefMsgType [372] names the rejected MsgType
+    fn on_reject(&mut self, _msg: &RawMessage) {}
+
+    /// ExecutionReport (35=8) with its typed fields
+    fn on_execution_report(&mut self, _report: &ExecutionReport, _msg: &RawMessage) {}
+
+    /// OrderCancelReject (35=9)
+    fn on_cancel_reject(&mut self, _msg: &RawMessage) {}
+
+    /// OrderMassCancelReport (35=r)
+    fn on_mass_cancel_report(&mut self, _msg: &RawMessage) {}
+
+    /// QuoteRequest (35=R), RFQs published to the session
+    fn on_quote_request(&mut self, _msg: &RawMessage) {}
+
+    /// any other message, e.g. Heartbeat (35=0) or SecurityList (35=y)
+    fn on_message(&mut self, _msg: &RawMessage) {}
+
+    /// ExecutionReport that could not be decoded, `error` names the offending tag
+    fn on_decode_error(&mut self, msg: &RawMessage, error: &OrderError) {
+        error!("ExecutionReport not decoded: {error} [{}]", msg.to_display());
+    }
+
+    /// stream closed or failed, no further events will follow
+    fn on_disconnect(&mut self, _reason: &str) {}
+}
+
+/// `dispatch`
+///
+/// calls the hook for the MsgType [35] of `event`, returns false once the session is disconnected
+pub fn dispatch<A: FixApplication + ?Sized>(app: &mut A, event: &InboundEvent) -> bool {
+    let msg: &RawMessage = match event {
+        InboundEvent::Disconnected(reason) => {
+            app.on_disconnect(reason);
+            return false;
+        }
+        other => match other.message() {
+            Some(msg) => msg,
+            None => return true,
+        },
+    };
+    match msg.msg_type() {
+        "A" => app.on_logon(msg),
+        "5" => app.on_logout(msg),
+        "3" | "j" => app.on_reject(msg),
+        "8" => match ExecutionReport::decode(msg) {
+            Ok(report) => app.on_execution_report(&report, msg),
+            Err(error) => app.on_decode_error(msg, &error),
+        },
+        "9" => app.on_cancel_reject(msg),
+        "r" => app.on_mass_cancel_report(msg),
+        "R" => app.on_quote_request(msg),
+        _ => app.on_message(msg),
+    }
+    true
+}
+
+/// `run`
+///
+/// dispatches events received on `inbound` until `done` returns true for the application
+///
+/// # Errors
+///
+/// function will return error if
+/// - no event is received within `timeout`, `RecvTimeoutError::Timeout`
+/// - the session disconnected or its channel closed, `RecvTimeoutError::Disconnected`
+pub fn run<A: FixApplication>(
+    app: &mut A, inbound: &Receiver<InboundEvent>, timeout: Duration, done: impl Fn(&A) -> bool,
+) -> Result<(), RecvTimeoutError> {
+    while !done(app) {
+        let event: InboundEvent = inbound.recv_timeout(timeout)?;
+        if !dispatch(app, &event) {
+            return Err(RecvTimeoutError::Disconnected);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod application_tests {
+
+    use super::{dispatch, run, FixApplication};
+    use crate::fix::RawMessage;
+    use crate::orders::{ExecType, ExecutionReport, OrderError};
+    use crate::session::InboundEvent;
+    use std::{
+        sync::mpsc::{channel, RecvTimeoutError},
+        time::Duration,
+    };
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<String>,
+    }
+
+    impl FixApplication for Recorder {
+        fn on_logon(&mut self, _msg: &RawMessage) {
+            self.calls.push("logon".to_string());
+        }
+
+        fn on_reject(&mut self, msg: &RawMessage) {
+            self.calls.push(format!("reject {}", msg.msg_type()));
+        }
+
+        fn on_execution_report(&mut self, report: &ExecutionReport, _msg: &RawMessage) {
+            self.calls.push(format!("report {:?}", report.exec_type));
+        }
+
+        fn on_quote_request(&mut self, _msg: &RawMessage) {
+            self.calls.push("quote request".to_string());
+        }
+
+        fn on_decode_error(&mut self, _msg: &RawMessage, error: &OrderError) {
+            self.calls.push(format!("decode error {error:?}"));
+        }
+
+        fn on_disconnect(&mut self, reason: &str) {
+            self.calls.push(format!("disconnect {reason}"));
+        }
+    }
+
+    fn event(msg_type: &str, fields: &[(u32, &str)]) -> InboundEvent {
+        let mut msg = RawMessage::new(msg_type);
+        for (tag, value) in fields {
+            msg.set(*tag, value);
+        }
+        InboundEvent::from_raw(msg)
+    }
+
+    #[test]
+    fn test_events_are_routed_by_msg_type() {
+        let mut app = Recorder::default();
+        assert!(dispatch(&mut app, &event("A", &[])));
+        assert!(dispatch(&mut app, &event("8", &[(150, "F"), (39, "2")])));
+        assert!(dispatch(&mut app, &event("8", &[(150, "F"), (39, "Z")])));
+        assert!(dispatch(&mut app, &event("j", &[(372, "D")])));
+        assert!(dispatch(&mut app, &event("R", &[])));
+        // no hook overridden, nothing recorded
+        assert!(dispatch(&mut app, &event("0", &[])));
+        assert!(!dispatch(&mut app, &InboundEvent::Disconnected("closed".to_string())));
+        assert_eq!(app.calls, [
+            "logon".to_string(),
+            format!("report {:?}", ExecType::Trade),
+            format!("decode error {:?}", OrderError::InvalidReport { tag: 39, value: Some("Z".to_string()) }),
+            "reject j".to_string(),
+            "quote request".to_string(),
+            "disconnect closed".to_string(),
+        ]);
+
+        let (sender, receiver) = channel();
+        sender.send(event("3", &[])).unwrap();
+        sender.send(event("A", &[])).unwrap();
+        let mut app = Recorder::default();
+        assert_eq!(run(&mut app, &receiver, Duration::from_millis(10), |app| app.calls.contains(&"logon".to_string())), Ok(()));
+        assert_eq!(run(&mut app, &receiver, Duration::from_millis(10), |_| false), Err(RecvTimeoutError::Timeout));
+    }
+}
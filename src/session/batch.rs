@@ -0,0 +1,159 @@
+// src/session/batch.rs
+//
+// Pipelined batch order submission
+//  - every order of the batch is tracked as PendingNew and checked by the kill switch and risk gate, those passing
+//    are queued together and written back-to-back by the writer thread with consecutive MsgSeqNums [34]
+//  - nothing waits for an ack before the next order is written, acks are collected afterwards by ClOrdID [11]
+//    from the `OrderUpdate`s published by order state, so reports arriving in any order are matched
+//  - `wait` returns one `BatchResult` per order, in the order given, orders without a report in time are `TimedOut`
+//    n.b. an order that cannot be tracked (e.g. no ClOrdID) is not sent, its outcome could never be reported
+//
+
+use super::OutboundHandle;
+use crate::fix::RawMessage;
+use crate::orders::{OrdStatus, OrderUpdate, SharedOrderManager};
+use log::{error, info};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+/// `BatchOutcome`
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchOutcome {
+    /// first report was not a reject, e.g. New, or Filled for an IOC order
+    Accepted { order_id: Option<String>, ord_status: OrdStatus },
+    /// rejected by the venue, with OrdRejReason [103] or Text [58]
+    Rejected(String),
+    /// not written, e.g. stopped by the risk gate or the kill switch
+    NotSent(String),
+    /// no report within the timeout, the order is still PendingNew in order state
+    TimedOut,
+}
+
+impl fmt::Display for BatchOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchOutcome::Accepted { order_id, ord_status } => {
+                write!(f, "accepted as {} with status {ord_status}", order_id.as_deref().unwrap_or("?"))
+            }
+            BatchOutcome::Rejected(reason) => write!(f, "rejected: {reason}"),
+            BatchOutcome::NotSent(reason) => write!(f, "not sent: {reason}"),
+            BatchOutcome::TimedOut => write!(f, "no ack in time"),
+        }
+    }
+}
+
+/// `BatchResult`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResult {
+    pub cl_ord_id: String,
+    pub outcome: BatchOutcome,
+    /// time from the batch being queued to the first report of the order
+    pub elapsed: Option<Duration>,
+}
+
+/// `OrderBatch`
+///
+/// orders submitted together, see `submit` and `wait`
+pub struct OrderBatch {
+    results: Vec<BatchResult>,
+    // ClOrdID -> index in results, orders sent and not acked yet
+    pending: HashMap<String, usize>,
+    updates: Receiver<OrderUpdate>,
+    queued_at: Instant,
+}
+
+impl OrderBatch {
+    /// `submit`
+    ///
+    /// tracks and checks every NewOrderSingle (35=D) / NewOrderMultileg (35=AB) of `msgs` and queues those passing
+    /// as one batch, returns without waiting for any ack
+    pub fn submit(outbound: &OutboundHandle, orders: &SharedOrderManager, msgs: Vec<RawMessage>) -> OrderBatch {
+        // subscribed before anything is sent, so no ack can be missed
+        let updates: Receiver<OrderUpdate> = orders.lock().unwrap().subscribe();
+        let mut results: Vec<BatchResult> = Vec::new();
+        let mut admitted: Vec<RawMessage> = Vec::new();
+        for msg in msgs {
+            let cl_ord_id: String = msg.get(11).unwrap_or_default().to_string();
+            // n.b. an order not tracked here, e.g. a duplicate ClOrdID, must leave the order it collides with untouched
+            let tracked: Result<(), String> = orders.lock().unwrap().track_new(&msg).map(|_| ()).map_err(|error| error.to_string());
+            let outcome: BatchOutcome = match tracked {
+                Err(reason) => {
+                    error!("Batch - order {cl_ord_id:?} not tracked: {reason}");
+                    BatchOutcome::NotSent(reason)
+                }
+                Ok(()) => match outbound.admit(&msg) {
+                    Ok(()) => {
+                        admitted.push(msg);
+                        BatchOutcome::TimedOut
+                    }
+                    Err(error) => {
+                        error!("Batch - order {cl_ord_id:?} not sent: {error}");
+                        orders.lock().unwrap().reject_local(&cl_ord_id, &error.to_string());
+                        BatchOutcome::NotSent(error.to_string())
+                    }
+                },
+            };
+            results.push(BatchResult { cl_ord_id, outcome, elapsed: None });
+        }
+
+        let count: usize = admitted.len();
+        let mut pending: HashMap<String, usize> = HashMap::new();
+        if let Err(error) = outbound.queue_batch(admitted) {
+            error!("Batch - {count} order(s) not sent: {error}");
+            for result in results.iter_mut().filter(|result| result.outcome == BatchOutcome::TimedOut) {
+                orders.lock().unwrap().reject_local(&result.cl_ord_id, &error.to_string());
+                result.outcome = BatchOutcome::NotSent(error.to_string());
+            }
+        } else {
+            pending = results
+                .iter()
+                .enumerate()
+                .filter(|(_, result)| result.outcome == BatchOutcome::TimedOut)
+                .map(|(i, result)| (result.cl_ord_id.clone(), i))
+                .collect();
+            info!("Batch - {count} of {} order(s) queued", results.len());
+        }
+        OrderBatch { results, pending, updates, queued_at: Instant::now() }
+    }
+
+    /// orders sent and not acked yet
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// `wait`
+    ///
+    /// collects acks until every order sent has one or `timeout` elapsed, returns the outcome of every order
+    pub fn wait(mut self, timeout: Duration) -> Vec<BatchResult> {
+        let deadline: Instant = Instant::now() + timeout;
+        while !self.pending.is_empty() {
+            let update: OrderUpdate = match self.updates.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(update) => update,
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            };
+            let order = &update.order;
+            if order.ord_status == OrdStatus::PendingNew {
+                continue;
+            }
+            let Some(index) = self.pending.remove(&order.cl_ord_id) else { continue };
+            let result: &mut BatchResult = &mut self.results[index];
+            result.elapsed = Some(self.queued_at.elapsed());
+            result.outcome = match order.ord_status {
+                OrdStatus::Rejected => BatchOutcome::Rejected(match (order.reject_reason, &order.text) {
+                    (Some(reason), Some(text)) => format!("{reason} - {text}"),
+                    (Some(reason), None) => reason.to_string(),
+                    (None, text) => text.clone().unwrap_or_default(),
+                }),
+                ord_status => BatchOutcome::Accepted { order_id: order.order_id.clone(), ord_status },
+            };
+        }
+        if !self.pending.is_empty() {
+            error!("Batch - {} order(s) not acked within {timeout:?}", self.pending.len());
+        }
+        self.results
+    }
+}
@@ -0,0 +1,98 @@
+// src/session/drop_copy.rs
+//
+// Drop-copy session running beside the order-entry session
+//  - ExecutionReports are merged into the shared `OrderManager` by the session reader thread
+//  - listener thread drains remaining events so the inbound channel never backs up, reports are logged
+//    by a `FixApplication`
+//
+
+use super::{dispatch, FixApplication, Session};
+use crate::fix::RawMessage;
+use crate::orders::ExecutionReport;
+use log::{error, info};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::RecvTimeoutError,
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// `DropCopy`
+///
+/// drop-copy session owned by a listener thread, stopped with `stop`
+pub struct DropCopy {
+    running: Arc<AtomicBool>,
+    listener: JoinHandle<()>,
+}
+
+impl DropCopy {
+    /// `start`
+    ///
+    /// takes over a logged on drop-copy session, see `Session::spawn_tracked`
+    pub fn start(session: Session) -> DropCopy {
+        let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+        let listener = {
+            let running = Arc::clone(&running);
+            thread::Builder::new()
+                .name(format!("fix-drop-copy-{}", session.config().target_comp_id))
+                .spawn(move || listen(session, &running))
+                .expect("Failed to spawn drop-copy listener thread")
+        };
+        DropCopy { running, listener }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// `stop`
+    ///
+    /// logs out drop-copy session and waits for listener thread to finish
+    pub fn stop(self) {
+        self.running.store(false, Ordering::SeqCst);
+        if self.listener.join().is_err() {
+            error!("FIX drop-copy listener thread panicked");
+        }
+    }
+}
+
+// logs reports and stops the listener on Logout
+#[derive(Default)]
+struct DropCopyApplication {
+    logged_out: bool,
+}
+
+impl FixApplication for DropCopyApplication {
+    fn on_execution_report(&mut self, _report: &ExecutionReport, msg: &RawMessage) {
+        info!("FIX drop-copy - ExecutionReport {}", msg.to_display());
+    }
+
+    fn on_logout(&mut self, msg: &RawMessage) {
+        error!("FIX drop-copy - Logout received {}", msg.to_display());
+        self.logged_out = true;
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        error!("FIX drop-copy - disconnected: {reason}");
+    }
+}
+
+fn listen(session: Session, running: &AtomicBool) {
+    let mut app: DropCopyApplication = DropCopyApplication::default();
+    while running.load(Ordering::SeqCst) && !app.logged_out {
+        match session.inbound.recv_timeout(Duration::from_millis(100)) {
+            Ok(event) => {
+                if !dispatch(&mut app, &event) {
+                    break;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    running.store(false, Ordering::SeqCst);
+    session.logout();
+}
@@ -0,0 +1,231 @@
+// src/session/kill_switch.rs
+//
+// Client-side kill switch, in addition to the API key's server-side "cancel-on-session-close" flag
+//  - tripped by SIGINT / SIGTERM (`register_signals`), a flag file appearing (`flag_file`),
+//    a control command or a risk breach (`trip`)
+//  - once tripped `OutboundHandle::send_raw` refuses new orders and amends, cancels still pass
+//    and the writer drops those still queued behind the throttle, rejecting them in order state
+//  - `engage` mass-cancels every open order (35=q, MassCancelRequestType [530] = '7'), waits until order
+//    state has no open order left and sends Logout (35=5), `watch` runs it on a thread when tripped
+//    n.b. a second SIGINT ends the process, a switch tripped otherwise still lets the first one through
+//
+
+use super::{OutboundHandle, SessionConfig};
+use crate::fix::{utc_timestamp, RawMessage};
+use crate::orders::SharedOrderManager;
+use crate::risk::RiskRejection;
+use log::{error, info};
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// `KillTrigger`
+///
+/// what tripped the kill switch
+#[derive(Debug, Clone, PartialEq)]
+pub enum KillTrigger {
+    /// SIGINT or SIGTERM
+    Signal,
+    /// flag file was found
+    FileFlag(PathBuf),
+    /// control command, e.g. 'kill' typed on stdin
+    Command(String),
+    /// risk limit breached, e.g. daily loss limit reached by a fill
+    RiskBreach(RiskRejection),
+}
+
+impl fmt::Display for KillTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KillTrigger::Signal => write!(f, "signal"),
+            KillTrigger::FileFlag(path) => write!(f, "flag file {}", path.display()),
+            KillTrigger::Command(command) => write!(f, "command '{command}'"),
+            KillTrigger::RiskBreach(rejection) => write!(f, "risk breach: {rejection}"),
+        }
+    }
+}
+
+/// `KillSwitch`
+///
+/// cloneable, every clone trips the same switch
+#[derive(Debug, Clone, Default)]
+pub struct KillSwitch {
+    tripped: Arc<AtomicBool>,
+    // set by SIGINT / SIGTERM only, recorded as `KillTrigger::Signal` by `is_tripped`
+    signalled: Arc<AtomicBool>,
+    trigger: Arc<Mutex<Option<KillTrigger>>>,
+    disarmed: Arc<AtomicBool>,
+    flag_file: Option<PathBuf>,
+}
+
+impl KillSwitch {
+    pub fn new() -> Self {
+        KillSwitch::default()
+    }
+
+    /// `flag_file`
+    ///
+    /// switch trips once a file exists at `path`, e.g. created with `touch`
+    pub fn flag_file(mut self, path: &Path) -> Self {
+        self.flag_file = Some(path.to_path_buf());
+        self
+    }
+
+    /// `register_signals`
+    ///
+    /// SIGINT and SIGTERM trip the switch instead of ending the process, a SIGINT after an earlier signal ends it
+    ///
+    /// # Errors
+    ///
+    /// function will return error if the signal handlers cannot be registered
+    pub fn register_signals(&self) -> io::Result<()> {
+        use signal_hook::{consts::{SIGINT, SIGTERM}, flag};
+        // registered first, so only a SIGINT following an earlier signal finds the flag set,
+        // a switch tripped by flag file, command or risk breach does not arm it
+        flag::register_conditional_shutdown(SIGINT, 1, Arc::clone(&self.signalled))?;
+        flag::register(SIGINT, Arc::clone(&self.signalled))?;
+        flag::register(SIGTERM, Arc::clone(&self.signalled))?;
+        Ok(())
+    }
+
+    /// `trip`
+    ///
+    /// returns true when this call tripped the switch, false when it was tripped before
+    pub fn trip(&self, trigger: KillTrigger) -> bool {
+        let mut current = self.trigger.lock().unwrap();
+        if self.tripped.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        error!("Kill switch tripped by {trigger}");
+        *current = Some(trigger);
+        true
+    }
+
+    /// `is_tripped`
+    ///
+    /// n.b. checks the flag file too, a signal caught since the last call is recorded as its trigger
+    pub fn is_tripped(&self) -> bool {
+        if self.signalled.load(Ordering::SeqCst) {
+            self.trip(KillTrigger::Signal);
+        }
+        if self.tripped.load(Ordering::SeqCst) {
+            return true;
+        }
+        match &self.flag_file {
+            Some(path) if path.exists() => {
+                self.trip(KillTrigger::FileFlag(path.clone()));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn trigger(&self) -> Option<KillTrigger> {
+        self.is_tripped();
+        self.trigger.lock().unwrap().clone()
+    }
+
+    /// `disarm`
+    ///
+    /// stops the `watch` thread, the switch can still be tripped and engaged directly
+    pub fn disarm(&self) {
+        self.disarmed.store(true, Ordering::SeqCst);
+    }
+
+    /// `engage`
+    ///
+    /// mass-cancels every open order, waits up to `timeout` for order state to confirm them cancelled
+    /// and sends Logout (35=5), returns the number of orders still open
+    /// n.b. trips the switch first, if it was not tripped yet, so no new order gets out meanwhile,
+    /// orders still queued behind the throttle are dropped and rejected in order state
+    pub fn engage(&self, outbound: &OutboundHandle, config: &SessionConfig, orders: &SharedOrderManager, timeout: Duration) -> usize {
+        if !self.is_tripped() {
+            self.trip(KillTrigger::Command("engage".to_string()));
+        }
+        let open: usize = orders.lock().unwrap().open_orders().count();
+        info!("Kill switch - cancelling all orders, {open} open in local order state");
+        if let Err(error) = outbound.send_raw(mass_cancel_all(config)) {
+            error!("Kill switch - mass cancel not sent: {error}");
+        }
+
+        let deadline: Instant = Instant::now() + timeout;
+        let mut open: usize = orders.lock().unwrap().open_orders().count();
+        while open > 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
+            open = orders.lock().unwrap().open_orders().count();
+        }
+        if open > 0 {
+            error!("Kill switch - {open} order(s) not confirmed cancelled within {timeout:?}");
+        } else {
+            info!("Kill switch - every order confirmed cancelled");
+        }
+        if let Err(error) = outbound.send_raw(config.admin_message("5")) {
+            error!("Kill switch - Logout not sent: {error}");
+        }
+        open
+    }
+
+    /// `watch`
+    ///
+    /// polls the switch every `poll` on a thread and engages it once tripped, until `disarm` is called
+    pub fn watch(
+        &self, outbound: OutboundHandle, config: SessionConfig, orders: SharedOrderManager, timeout: Duration, poll: Duration,
+    ) -> JoinHandle<()> {
+        let kill_switch: KillSwitch = self.clone();
+        thread::Builder::new()
+            .name("kill-switch".to_string())
+            .spawn(move || {
+                while !kill_switch.disarmed.load(Ordering::SeqCst) {
+                    if kill_switch.is_tripped() {
+                        kill_switch.engage(&outbound, &config, &orders, timeout);
+                        return;
+                    }
+                    thread::sleep(poll);
+                }
+            })
+            .expect("Failed to spawn kill switch thread")
+    }
+}
+
+/// `mass_cancel_all`
+///
+/// OrderMassCancelRequest (35=q) for every order of the session, MsgSeqNum [34] is assigned by the writer thread
+pub(super) fn mass_cancel_all(config: &SessionConfig) -> RawMessage {
+    let millis: u128 = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards!!").as_millis();
+    let mut msg: RawMessage = config.admin_message("q");
+    msg.set(11, format!("KILL-{millis}")).set(530, '7').set(60, utc_timestamp());
+    msg
+}
+
+#[cfg(test)]
+mod kill_switch_tests {
+
+    use super::{KillSwitch, KillTrigger};
+    use crate::risk::RiskRejection;
+
+    #[test]
+    fn test_first_trigger_is_kept() {
+        let kill_switch = KillSwitch::new();
+        let clone = kill_switch.clone();
+        assert!(!kill_switch.is_tripped());
+        assert!(clone.trip(KillTrigger::RiskBreach(RiskRejection::DailyLoss { loss: 12.0, limit: 10.0 })));
+        assert!(!kill_switch.trip(KillTrigger::Command("kill".to_string())));
+        assert!(kill_switch.is_tripped());
+        assert!(matches!(kill_switch.trigger(), Some(KillTrigger::RiskBreach(_))));
+
+        let path = std::env::temp_dir().join(format!("pt-kill-{}", std::process::id()));
+        let flagged = KillSwitch::new().flag_file(&path);
+        assert!(!flagged.is_tripped());
+        std::fs::write(&path, "").unwrap();
+        assert!(flagged.is_tripped());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(flagged.trigger(), Some(KillTrigger::FileFlag(path)));
+    }
+}
@@ -0,0 +1,223 @@
+// src/session/latency.rs
+//
+// Order round-trip latency measured by the session threads
+//  - writer thread stamps orders (35=D / 35=AB), cancels (35=F / 35=q) and amends (35=G) by ClOrdID [11]
+//    once they are written to the stream
+//  - reader thread stamps the first response carrying the same ClOrdID [11], ExecutionReport (35=8),
+//    OrderCancelReject (35=9) or OrderMassCancelReport (35=r), when its bytes were read
+//  - one `RoundTrip` is kept per ClOrdID, p50 / p99 / max are computed per request MsgType [35]
+//    n.b. both stamps are `Instant`s, wall clock time is kept for the CSV export only
+//
+
+use super::MessageClass;
+use crate::fix::{utc_timestamp, RawMessage};
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// latency tracker shared by the writer and reader threads of a session
+pub type SharedLatencyTracker = Arc<Mutex<LatencyTracker>>;
+
+/// `RoundTrip`
+///
+/// request and its first response with the same ClOrdID [11]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundTrip {
+    pub cl_ord_id: String,
+    /// MsgType [35] of the request, e.g. "D"
+    pub msg_type: String,
+    /// UTC time the request was written
+    pub sent_at: String,
+    /// MsgType [35] of the response, "8", "9" or "r"
+    pub response_type: String,
+    pub latency: Duration,
+}
+
+/// `LatencyStats`
+///
+/// latency distribution of one request MsgType [35], percentiles are nearest-rank
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub p50: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    fn from_samples(mut samples: Vec<Duration>) -> LatencyStats {
+        samples.sort_unstable();
+        let percentile = |p: f64| {
+            let rank: usize = (p * samples.len() as f64).ceil() as usize;
+            samples.get(rank.saturating_sub(1)).copied().unwrap_or_default()
+        };
+        LatencyStats { count: samples.len(), p50: percentile(0.5), p99: percentile(0.99), max: samples.last().copied().unwrap_or_default() }
+    }
+}
+
+impl fmt::Display for LatencyStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "count {} p50 {:?} p99 {:?} max {:?}", self.count, self.p50, self.p99, self.max)
+    }
+}
+
+struct Pending {
+    msg_type: String,
+    sent_at: String,
+    sent: Instant,
+}
+
+/// `LatencyTracker`
+///
+/// round trips of the requests written by one session, see `OutboundHandle::latency_tracker`
+#[derive(Default)]
+pub struct LatencyTracker {
+    pending: HashMap<String, Pending>,
+    round_trips: Vec<RoundTrip>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        LatencyTracker::default()
+    }
+
+    pub fn shared(self) -> SharedLatencyTracker {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// `on_sent`
+    ///
+    /// stamps orders, cancels and amends written at `sent`, any other message is ignored
+    /// n.b. a ClOrdID [11] sent again restarts its measurement
+    pub fn on_sent(&mut self, msg: &RawMessage, sent: Instant) {
+        if MessageClass::of(msg) == MessageClass::Other {
+            return;
+        }
+        if let Some(cl_ord_id) = msg.get(11) {
+            self.pending.insert(cl_ord_id.to_string(), Pending { msg_type: msg.msg_type().to_string(), sent_at: utc_timestamp(), sent });
+        }
+    }
+
+    /// `on_received`
+    ///
+    /// completes the round trip of the request with the response's ClOrdID [11], returns it
+    /// None for later responses and responses to requests this session did not send
+    pub fn on_received(&mut self, msg: &RawMessage, received: Instant) -> Option<&RoundTrip> {
+        if !matches!(msg.msg_type(), "8" | "9" | "r") {
+            return None;
+        }
+        let cl_ord_id: &str = msg.get(11)?;
+        let pending: Pending = self.pending.remove(cl_ord_id)?;
+        self.round_trips.push(RoundTrip {
+            cl_ord_id: cl_ord_id.to_string(),
+            msg_type: pending.msg_type,
+            sent_at: pending.sent_at,
+            response_type: msg.msg_type().to_string(),
+            latency: received.saturating_duration_since(pending.sent),
+        });
+        self.round_trips.last()
+    }
+
+    pub fn round_trips(&self) -> &[RoundTrip] {
+        &self.round_trips
+    }
+
+    /// requests still waiting for a response
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// `stats`
+    ///
+    /// latency distribution of requests of `msg_type`, None when none completed
+    pub fn stats(&self, msg_type: &str) -> Option<LatencyStats> {
+        let samples: Vec<Duration> =
+            self.round_trips.iter().filter(|round_trip| round_trip.msg_type == msg_type).map(|round_trip| round_trip.latency).collect();
+        if samples.is_empty() {
+            None
+        } else {
+            Some(LatencyStats::from_samples(samples))
+        }
+    }
+
+    /// `summary`
+    ///
+    /// latency distribution of every request MsgType [35] completed, sorted by MsgType
+    pub fn summary(&self) -> Vec<(String, LatencyStats)> {
+        let mut msg_types: Vec<&str> = self.round_trips.iter().map(|round_trip| round_trip.msg_type.as_str()).collect();
+        msg_types.sort_unstable();
+        msg_types.dedup();
+        msg_types.into_iter().filter_map(|msg_type| self.stats(msg_type).map(|stats| (msg_type.to_string(), stats))).collect()
+    }
+
+    /// `to_csv`
+    ///
+    /// one line per round trip, latency in microseconds
+    pub fn to_csv(&self) -> String {
+        let mut csv: String = "cl_ord_id,msg_type,sent_at,response_type,latency_us\n".to_string();
+        for round_trip in &self.round_trips {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                round_trip.cl_ord_id,
+                round_trip.msg_type,
+                round_trip.sent_at,
+                round_trip.response_type,
+                round_trip.latency.as_micros()
+            ));
+        }
+        csv
+    }
+
+    /// `write_csv`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if the file cannot be written
+    pub fn write_csv(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+}
+
+#[cfg(test)]
+mod latency_tests {
+
+    use super::LatencyTracker;
+    use crate::fix::RawMessage;
+    use std::time::{Duration, Instant};
+
+    fn msg(msg_type: &str, cl_ord_id: &str) -> RawMessage {
+        let mut msg = RawMessage::new(msg_type);
+        msg.set(11, cl_ord_id);
+        msg
+    }
+
+    #[test]
+    fn test_round_trips_by_cl_ord_id_and_percentiles() {
+        let mut tracker = LatencyTracker::new();
+        let start = Instant::now();
+        for i in 1..=100_u64 {
+            let cl_ord_id: String = format!("ORDER-{i}");
+            tracker.on_sent(&msg("D", &cl_ord_id), start);
+            tracker.on_received(&msg("8", &cl_ord_id), start + Duration::from_millis(i));
+        }
+        // only the first response of an order is its ack
+        assert!(tracker.on_received(&msg("8", "ORDER-1"), start + Duration::from_secs(1)).is_none());
+        tracker.on_sent(&msg("F", "CANCEL-1"), start);
+        tracker.on_sent(&msg("0", "HB"), start);
+        let round_trip = tracker.on_received(&msg("9", "CANCEL-1"), start + Duration::from_millis(3)).unwrap();
+        assert_eq!((round_trip.msg_type.as_str(), round_trip.response_type.as_str()), ("F", "9"));
+
+        let stats = tracker.stats("D").unwrap();
+        assert_eq!(
+            (stats.count, stats.p50, stats.p99, stats.max),
+            (100, Duration::from_millis(50), Duration::from_millis(99), Duration::from_millis(100))
+        );
+        assert_eq!(tracker.summary().iter().map(|(msg_type, _)| msg_type.as_str()).collect::<Vec<_>>(), ["D", "F"]);
+        assert_eq!(tracker.pending(), 0);
+        assert_eq!(tracker.to_csv().lines().count(), 102);
+    }
+}
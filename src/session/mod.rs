@@ -0,0 +1,738 @@
+// src/session/mod.rs
+//
+// FIX session split into a reader thread and a writer thread
+//  - writer thread owns outbound sequencing (MsgSeqNum [34]) and heartbeats
+//  - reader thread frames inbound bytes and publishes typed `InboundEvent`s
+//  - both threads share the stream behind a mutex, the stream read timeout
+//    sets how long the reader may hold the lock (see `SessionConfig::poll_interval`)
+//
+
+mod application;
+mod batch;
+mod drop_copy;
+mod kill_switch;
+mod latency;
+mod throttle;
+
+pub use application::{dispatch, run, FixApplication};
+pub use batch::{BatchOutcome, BatchResult, OrderBatch};
+pub use drop_copy::DropCopy;
+pub use kill_switch::{KillSwitch, KillTrigger};
+pub use latency::{LatencyStats, LatencyTracker, RoundTrip, SharedLatencyTracker};
+pub use throttle::{MessageClass, QueueStats, RateLimit, Throttle, ThrottleConfig, ThrottleMetrics};
+
+use crate::fix::{utc_timestamp, FixError, FrameReader, RawMessage};
+use crate::orders::{ReportSource, SharedOrderManager};
+use crate::positions::SharedPositionKeeper;
+use crate::risk::{RiskRejection, SharedRiskGate};
+use log::{error, info};
+use native_tls::TlsStream;
+use std::{
+    fmt,
+    io::{self, ErrorKind, Read, Write},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// `SessionRole`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionRole {
+    /// orders are sent and acknowledged on this session
+    OrderEntry,
+    /// read-only copy of ExecutionReports for every order of the account
+    DropCopy,
+}
+
+/// `SessionConfig`
+///
+/// settings used by reader/writer threads for one FIX session
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    /// SenderCompID [49] - the power.trade API key
+    pub sender_comp_id: String,
+    /// TargetCompID [56] - e.g. "PT-OE" for order entry
+    pub target_comp_id: String,
+    pub role: SessionRole,
+    /// writer sends a Heartbeat (35=0) when nothing was sent for this long
+    pub heartbeat_interval: Duration,
+    /// read timeout expected on the stream, used as reader back-off when no bytes are available
+    pub poll_interval: Duration,
+    /// outbound rate limits the writer thread applies to orders, cancels and amends, nothing is throttled by default
+    pub throttle: ThrottleConfig,
+    /// on every Logon after the first, mass-cancel orders still open in order state, i.e. working when the previous
+    /// connection dropped
+    /// n.b. needs a session started with `spawn_reconnecting`
+    pub cancel_on_reconnect: bool,
+    /// wait between attempts to reconnect, see `Session::spawn_reconnecting`
+    pub reconnect_interval: Duration,
+}
+
+impl SessionConfig {
+    pub fn new(sender_comp_id: &str, target_comp_id: &str) -> Self {
+        SessionConfig {
+            sender_comp_id: sender_comp_id.to_string(),
+            target_comp_id: target_comp_id.to_string(),
+            role: SessionRole::OrderEntry,
+            heartbeat_interval: Duration::from_secs(30),
+            poll_interval: Duration::from_millis(50),
+            throttle: ThrottleConfig::default(),
+            cancel_on_reconnect: false,
+            reconnect_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// `drop_copy`
+    ///
+    /// config for a drop-copy session, e.g. TargetCompID [56] = "PT-DC"
+    pub fn drop_copy(sender_comp_id: &str, target_comp_id: &str) -> Self {
+        SessionConfig { role: SessionRole::DropCopy, ..SessionConfig::new(sender_comp_id, target_comp_id) }
+    }
+
+    /// `admin_message`
+    ///
+    /// session level message (Heartbeat, Logout, ...) with header fields set from config
+    /// n.b. MsgSeqNum [34] is assigned by the writer thread
+    pub fn admin_message(&self, msg_type: &str) -> RawMessage {
+        let mut msg = RawMessage::new(msg_type);
+        msg.set_header(34, 0)
+            .set_header(49, &self.sender_comp_id)
+            .set_header(52, utc_timestamp())
+            .set_header(56, &self.target_comp_id);
+        msg
+    }
+}
+
+/// `SessionStream`
+///
+/// stream a session can run on, TLS to power.trade or plain TCP to the local simulator
+pub trait SessionStream: Read + Write + Send + 'static {
+    /// `set_poll_timeout`
+    ///
+    /// sets the read timeout of the underlying socket, see `SessionConfig::poll_interval`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if the socket option cannot be set
+    fn set_poll_timeout(&self, timeout: Duration) -> io::Result<()>;
+}
+
+impl SessionStream for TcpStream {
+    fn set_poll_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.set_read_timeout(Some(timeout))
+    }
+}
+
+impl SessionStream for TlsStream<TcpStream> {
+    fn set_poll_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.get_ref().set_read_timeout(Some(timeout))
+    }
+}
+
+/// `Connector`
+///
+/// opens a new connection for a session and returns it with the Logon (35=A) to send on it first
+pub type Connector<S> = Box<dyn FnMut() -> io::Result<(S, RawMessage)> + Send>;
+
+/// `InboundEvent`
+///
+/// inbound FIX messages classified by MsgType [35], plus connection state changes
+/// n.b. `dispatch` routes an event to the matching hook of a `FixApplication`
+#[derive(Debug, Clone)]
+pub enum InboundEvent {
+    Logon(RawMessage),
+    Heartbeat(RawMessage),
+    TestRequest(RawMessage),
+    Reject(RawMessage),
+    Logout(RawMessage),
+    ExecutionReport(RawMessage),
+    OrderCancelReject(RawMessage),
+    OrderMassCancelReport(RawMessage),
+    Other(RawMessage),
+    /// stream closed or failed, no further events will follow
+    Disconnected(String),
+}
+
+impl InboundEvent {
+    pub fn from_raw(msg: RawMessage) -> Self {
+        match msg.msg_type() {
+            "A" => InboundEvent::Logon(msg),
+            "0" => InboundEvent::Heartbeat(msg),
+            "1" => InboundEvent::TestRequest(msg),
+            "3" => InboundEvent::Reject(msg),
+            "5" => InboundEvent::Logout(msg),
+            "8" => InboundEvent::ExecutionReport(msg),
+            "9" => InboundEvent::OrderCancelReject(msg),
+            "r" => InboundEvent::OrderMassCancelReport(msg),
+            _ => InboundEvent::Other(msg),
+        }
+    }
+
+    /// `message`
+    ///
+    /// underlying FIX message, None for `Disconnected`
+    pub fn message(&self) -> Option<&RawMessage> {
+        match self {
+            InboundEvent::Logon(msg)
+            | InboundEvent::Heartbeat(msg)
+            | InboundEvent::TestRequest(msg)
+            | InboundEvent::Reject(msg)
+            | InboundEvent::Logout(msg)
+            | InboundEvent::ExecutionReport(msg)
+            | InboundEvent::OrderCancelReject(msg)
+            | InboundEvent::OrderMassCancelReport(msg)
+            | InboundEvent::Other(msg) => Some(msg),
+            InboundEvent::Disconnected(_) => None,
+        }
+    }
+}
+
+/// `SessionError`
+///
+/// errors returned by `OutboundHandle`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionError {
+    /// outbound message could not be parsed
+    InvalidMessage(FixError),
+    /// order stopped by pre-trade risk checks, nothing was written
+    RiskRejected(RiskRejection),
+    /// order or amend stopped because the kill switch was tripped, nothing was written
+    KillSwitch(KillTrigger),
+    /// writer thread has stopped
+    Closed,
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::InvalidMessage(error) => write!(f, "invalid outbound message: {error}"),
+            SessionError::RiskRejected(rejection) => write!(f, "order rejected by risk checks: {rejection}"),
+            SessionError::KillSwitch(trigger) => write!(f, "order blocked, kill switch tripped by {trigger}"),
+            SessionError::Closed => write!(f, "session writer is closed"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+enum Outbound {
+    Message(RawMessage),
+    /// messages queued together, nothing sent meanwhile can get between them
+    Batch(Vec<RawMessage>),
+    Shutdown,
+}
+
+/// `OutboundHandle`
+///
+/// cloneable sender for messages written by the session writer thread
+/// n.b. the risk gate, kill switch, position keeper and latency tracker are shared by every clone, including the one held by the reader thread
+#[derive(Clone)]
+pub struct OutboundHandle {
+    tx: Sender<Outbound>,
+    risk: Arc<Mutex<Option<SharedRiskGate>>>,
+    kill_switch: Arc<Mutex<Option<KillSwitch>>>,
+    positions: Arc<Mutex<Option<SharedPositionKeeper>>>,
+    metrics: Arc<Mutex<ThrottleMetrics>>,
+    latency: SharedLatencyTracker,
+}
+
+impl OutboundHandle {
+    /// `send`
+    ///
+    /// queues FIX message text for the writer thread, MsgSeqNum [34] is (re)assigned when written
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - message text cannot be parsed
+    /// - writer thread has stopped
+    pub fn send(&self, fix_text: &str) -> Result<(), SessionError> {
+        let msg: RawMessage = RawMessage::parse(fix_text).map_err(SessionError::InvalidMessage)?;
+        self.send_raw(msg)
+    }
+
+    /// `send_raw`
+    ///
+    /// orders are checked by the kill switch and the risk gate (if set) before they are queued
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - order or amend is sent after the kill switch was tripped
+    /// - order is rejected by the risk gate
+    /// - writer thread has stopped
+    pub fn send_raw(&self, msg: RawMessage) -> Result<(), SessionError> {
+        self.admit(&msg)?;
+        self.tx.send(Outbound::Message(msg)).map_err(|_| SessionError::Closed)
+    }
+
+    /// `send_batch`
+    ///
+    /// queues messages to be written back-to-back with consecutive MsgSeqNums [34], every order is checked like
+    /// with `send_raw` and only those passing are queued, returns the outcome of each message in the order given
+    /// n.b. `OrderBatch` submits orders this way and collects their acks by ClOrdID [11]
+    ///      the throttle still spaces orders when `SessionConfig::throttle` limits them, and a cancel sent meanwhile
+    ///      can take its priority lane ahead of orders of the batch not written yet
+    pub fn send_batch(&self, msgs: Vec<RawMessage>) -> Vec<Result<(), SessionError>> {
+        let mut admitted: Vec<RawMessage> = Vec::new();
+        let mut results: Vec<Result<(), SessionError>> = Vec::new();
+        for msg in msgs {
+            let result: Result<(), SessionError> = self.admit(&msg);
+            if result.is_ok() {
+                admitted.push(msg);
+            }
+            results.push(result);
+        }
+        if self.queue_batch(admitted).is_err() {
+            results.iter_mut().filter(|result| result.is_ok()).for_each(|result| *result = Err(SessionError::Closed));
+        }
+        results
+    }
+
+    // kill switch and risk gate checks applied to every message before it is queued
+    fn admit(&self, msg: &RawMessage) -> Result<(), SessionError> {
+        if matches!(MessageClass::of(msg), MessageClass::New | MessageClass::Amend) {
+            if let Some(trigger) = self.kill_switch().and_then(|kill_switch| kill_switch.trigger()) {
+                error!("Kill switch - {} {:?} blocked", msg.msg_type(), msg.get(11).unwrap_or_default());
+                return Err(SessionError::KillSwitch(trigger));
+            }
+        }
+        if let Some(gate) = self.risk_gate() {
+            gate.lock().unwrap().check(msg).map_err(SessionError::RiskRejected)?;
+        }
+        Ok(())
+    }
+
+    // messages already admitted, written back-to-back by the writer thread
+    fn queue_batch(&self, msgs: Vec<RawMessage>) -> Result<(), SessionError> {
+        if msgs.is_empty() {
+            return Ok(());
+        }
+        self.tx.send(Outbound::Batch(msgs)).map_err(|_| SessionError::Closed)
+    }
+
+    /// `set_risk_gate`
+    ///
+    /// every order sent from now on must pass `gate`, fills received by the session are fed to it
+    /// n.b. the same gate can be set on the drop-copy session so fills seen there count too
+    pub fn set_risk_gate(&self, gate: SharedRiskGate) {
+        *self.risk.lock().unwrap() = Some(gate);
+    }
+
+    pub fn risk_gate(&self) -> Option<SharedRiskGate> {
+        self.risk.lock().unwrap().clone()
+    }
+
+    /// `set_kill_switch`
+    ///
+    /// no order or amend is sent once `kill_switch` is tripped, cancels still are
+    pub fn set_kill_switch(&self, kill_switch: KillSwitch) {
+        *self.kill_switch.lock().unwrap() = Some(kill_switch);
+    }
+
+    pub fn kill_switch(&self) -> Option<KillSwitch> {
+        self.kill_switch.lock().unwrap().clone()
+    }
+
+    /// `set_position_keeper`
+    ///
+    /// fills received by the session from now on are applied to `positions`
+    /// n.b. the same keeper can be set on the drop-copy session, fills seen on both are applied once
+    pub fn set_position_keeper(&self, positions: SharedPositionKeeper) {
+        *self.positions.lock().unwrap() = Some(positions);
+    }
+
+    pub fn position_keeper(&self) -> Option<SharedPositionKeeper> {
+        self.positions.lock().unwrap().clone()
+    }
+
+    /// `throttle_metrics`
+    ///
+    /// messages written so far and the time they spent queued by the throttle, per class
+    pub fn throttle_metrics(&self) -> ThrottleMetrics {
+        *self.metrics.lock().unwrap()
+    }
+
+    /// `latency_tracker`
+    ///
+    /// round trips of the orders, cancels and amends sent on this session, measured by ClOrdID [11] from
+    /// the request written to the first response read
+    pub fn latency_tracker(&self) -> SharedLatencyTracker {
+        Arc::clone(&self.latency)
+    }
+}
+
+/// `Session`
+///
+/// running reader/writer thread pair for one FIX session
+pub struct Session {
+    pub outbound: OutboundHandle,
+    pub inbound: Receiver<InboundEvent>,
+    config: SessionConfig,
+    running: Arc<AtomicBool>,
+    // set by the writer once a Logout (35=5) was written, e.g. by the kill switch
+    logged_out: Arc<AtomicBool>,
+    reader: JoinHandle<()>,
+    writer: JoinHandle<()>,
+}
+
+impl Session {
+    /// `spawn`
+    ///
+    /// starts reader and writer threads on stream
+    /// - `seqnum` holds the next outbound MsgSeqNum [34], shared with callers using `increment_seqnum`
+    /// - the read timeout of stream is set to `config.poll_interval`, so the reader releases it and writes are not delayed
+    ///
+    /// # Errors
+    ///
+    /// function will return error if the read timeout of stream cannot be set
+    pub fn spawn<S>(stream: S, config: SessionConfig, seqnum: Arc<Mutex<u32>>) -> io::Result<Session>
+    where
+        S: SessionStream,
+    {
+        Session::start(stream, config, seqnum, None, None)
+    }
+
+    /// `spawn_tracked`
+    ///
+    /// as `spawn`, reader thread also merges every ExecutionReport (35=8) into `orders`
+    /// before publishing it, so order state is current even when events are not consumed
+    /// n.b. an order-entry and a drop-copy session can share `orders`, a report seen on both is applied once
+    ///
+    /// # Errors
+    ///
+    /// function will return error if the read timeout of stream cannot be set
+    pub fn spawn_tracked<S>(stream: S, config: SessionConfig, seqnum: Arc<Mutex<u32>>, orders: SharedOrderManager) -> io::Result<Session>
+    where
+        S: SessionStream,
+    {
+        Session::start(stream, config, seqnum, Some(orders), None)
+    }
+
+    /// `spawn_reconnecting`
+    ///
+    /// as `spawn_tracked`, when the connection drops the reader thread calls `connect` every
+    /// `config.reconnect_interval` until a new connection is open, then writes the Logon returned with it first
+    /// - MsgSeqNum [34] restarts at 1, the Logon should set ResetSeqNumFlag [141] = 'Y'
+    /// - nothing is published for the dropped connection, the Logon response is, then events of the new connection
+    /// - messages written while disconnected are lost, e.g. an order sent then is never acknowledged
+    ///
+    /// # Errors
+    ///
+    /// function will return error if the read timeout of stream cannot be set
+    pub fn spawn_reconnecting<S, C>(
+        stream: S, config: SessionConfig, seqnum: Arc<Mutex<u32>>, orders: SharedOrderManager, mut connect: C,
+    ) -> io::Result<Session>
+    where
+        S: SessionStream,
+        C: FnMut() -> io::Result<(S, RawMessage)> + Send + 'static,
+    {
+        let poll_interval: Duration = config.poll_interval;
+        let connector: Connector<S> = Box::new(move || {
+            let (stream, logon) = connect()?;
+            stream.set_poll_timeout(poll_interval)?;
+            Ok((stream, logon))
+        });
+        Session::start(stream, config, seqnum, Some(orders), Some(connector))
+    }
+
+    fn start<S>(
+        stream: S, config: SessionConfig, seqnum: Arc<Mutex<u32>>, orders: Option<SharedOrderManager>, connector: Option<Connector<S>>,
+    ) -> io::Result<Session>
+    where
+        S: SessionStream,
+    {
+        stream.set_poll_timeout(config.poll_interval)?;
+        let reconnecting: bool = connector.is_some();
+        let writer_orders: Option<SharedOrderManager> = orders.clone();
+        let stream: Arc<Mutex<S>> = Arc::new(Mutex::new(stream));
+        let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+        let logged_out: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let (out_tx, out_rx) = channel::<Outbound>();
+        let (in_tx, in_rx) = channel::<InboundEvent>();
+        let metrics: Arc<Mutex<ThrottleMetrics>> = Arc::new(Mutex::new(ThrottleMetrics::default()));
+        let latency: SharedLatencyTracker = LatencyTracker::new().shared();
+        let outbound = OutboundHandle {
+            tx: out_tx,
+            risk: Arc::new(Mutex::new(None)),
+            kill_switch: Arc::new(Mutex::new(None)),
+            positions: Arc::new(Mutex::new(None)),
+            metrics: Arc::clone(&metrics),
+            latency: Arc::clone(&latency),
+        };
+
+        let reader = {
+            let stream = Arc::clone(&stream);
+            let running = Arc::clone(&running);
+            let outbound = outbound.clone();
+            let in_tx = in_tx.clone();
+            let config = config.clone();
+            let seqnum = Arc::clone(&seqnum);
+            thread::Builder::new()
+                .name(format!("fix-reader-{}", config.target_comp_id))
+                .spawn(move || read_loop(&stream, &running, &outbound, &in_tx, &config, orders.as_ref(), &seqnum, connector))
+                .expect("Failed to spawn FIX reader thread")
+        };
+        let writer = {
+            let running = Arc::clone(&running);
+            let logged_out = Arc::clone(&logged_out);
+            let config = config.clone();
+            let kill_switch = Arc::clone(&outbound.kill_switch);
+            thread::Builder::new()
+                .name(format!("fix-writer-{}", config.target_comp_id))
+                .spawn(move || {
+                    write_loop(
+                        &stream, &running, &logged_out, &out_rx, &in_tx, &config, &seqnum, metrics, &latency, &kill_switch, writer_orders.as_ref(),
+                        reconnecting,
+                    )
+                })
+                .expect("Failed to spawn FIX writer thread")
+        };
+
+        info!("Session started for {} -> {}", config.sender_comp_id, config.target_comp_id);
+        Ok(Session { outbound, inbound: in_rx, config, running, logged_out, reader, writer })
+    }
+
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// `logout`
+    ///
+    /// sends Logout (35=5) unless one was sent already, stops both threads and waits for them to finish
+    pub fn logout(self) {
+        if !self.logged_out.load(Ordering::SeqCst) {
+            let _ = self.outbound.send_raw(self.config.admin_message("5"));
+        }
+        let _ = self.outbound.tx.send(Outbound::Shutdown);
+        if self.writer.join().is_err() {
+            error!("FIX writer thread panicked");
+        }
+        self.running.store(false, Ordering::SeqCst);
+        if self.reader.join().is_err() {
+            error!("FIX reader thread panicked");
+        }
+        info!("Session closed for {} -> {}", self.config.sender_comp_id, self.config.target_comp_id);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn read_loop<S: Read + Write>(
+    stream: &Mutex<S>, running: &AtomicBool, outbound: &OutboundHandle, in_tx: &Sender<InboundEvent>, config: &SessionConfig,
+    orders: Option<&SharedOrderManager>, seqnum: &Mutex<u32>, mut connector: Option<Connector<S>>,
+) {
+    let source: ReportSource = match config.role {
+        SessionRole::OrderEntry => ReportSource::OrderEntry,
+        SessionRole::DropCopy => ReportSource::DropCopy,
+    };
+    let mut frames = FrameReader::new();
+    let mut buffer = [0; 4096];
+    // Logons (35=A) received, every one after the first follows a reconnect
+    let mut logons: usize = 0;
+    while running.load(Ordering::SeqCst) {
+        let result = stream.lock().unwrap().read(&mut buffer);
+        let disconnected: Option<String> = match result {
+            Ok(0) => Some("stream closed by peer".to_string()),
+            Ok(byte_count) => {
+                frames.push(&buffer[..byte_count]);
+                None
+            }
+            Err(ref error) if error.kind() == ErrorKind::WouldBlock || error.kind() == ErrorKind::TimedOut => {
+                // no data yet, release the stream so writer can use it
+                thread::sleep(config.poll_interval);
+                continue;
+            }
+            Err(error) => {
+                error!("FIX reader - error reading from stream: {error:?}");
+                Some(error.to_string())
+            }
+        };
+        if let Some(reason) = disconnected {
+            info!("FIX reader - disconnected: {reason}");
+            if connector.as_mut().map_or(false, |connector| reconnect(stream, running, config, seqnum, connector)) {
+                // bytes left from the dropped connection can never complete a message
+                frames = FrameReader::new();
+                continue;
+            }
+            let _ = in_tx.send(InboundEvent::Disconnected(reason));
+            break;
+        }
+
+        // responses are stamped when their bytes were read, not when they were parsed
+        let received: Instant = Instant::now();
+        while let Some(frame) = frames.next_frame() {
+            let msg: RawMessage = match RawMessage::parse(&frame) {
+                Ok(msg) => msg,
+                Err(error) => {
+                    error!("FIX reader - dropping unparsable message {frame:?}: {error}");
+                    continue;
+                }
+            };
+            info!("FIX reader - received {}", msg.to_display());
+            if let Some(round_trip) = outbound.latency.lock().unwrap().on_received(&msg, received) {
+                info!(
+                    "FIX reader - ClOrdID {} {} -> {} round trip {:?}",
+                    round_trip.cl_ord_id, round_trip.msg_type, round_trip.response_type, round_trip.latency
+                );
+            }
+
+            // TestRequest [35=1] must be answered by a Heartbeat carrying TestReqID [112]
+            if msg.msg_type() == "1" {
+                let mut heartbeat = config.admin_message("0");
+                heartbeat.set(112, msg.get(112).unwrap_or_default());
+                let _ = outbound.send_raw(heartbeat);
+            }
+            if msg.msg_type() == "A" {
+                logons += 1;
+            }
+            if let (Some(orders), "A", true) = (orders, msg.msg_type(), config.cancel_on_reconnect && logons > 1) {
+                let open: usize = orders.lock().unwrap().open_orders().count();
+                if open > 0 {
+                    info!("FIX reader - logged on again with {open} open order(s) from the previous connection, cancelling all");
+                    let _ = outbound.send_raw(kill_switch::mass_cancel_all(config));
+                }
+            }
+            if let (Some(orders), "8" | "9") = (orders, msg.msg_type()) {
+                match orders.lock().unwrap().apply(source, &msg) {
+                    Ok(Some(order)) => info!(
+                        "FIX reader - {source} ClOrdID {} OrdStatus {} CumQty {} LeavesQty {}",
+                        order.cl_ord_id, order.ord_status, order.cum_qty, order.leaves_qty
+                    ),
+                    Ok(None) => {}
+                    Err(error) => error!("FIX reader - {source} ExecutionReport not applied to order state: {error}"),
+                }
+            }
+            if let (Some(gate), "8") = (outbound.risk_gate(), msg.msg_type()) {
+                gate.lock().unwrap().on_execution_report(&msg);
+            }
+            if let (Some(positions), "8") = (outbound.position_keeper(), msg.msg_type()) {
+                positions.lock().unwrap().on_execution_report(&msg);
+            }
+            if in_tx.send(InboundEvent::from_raw(msg)).is_err() {
+                // nobody is listening for events anymore
+                running.store(false, Ordering::SeqCst);
+                return;
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_loop<S: Write>(
+    stream: &Mutex<S>, running: &AtomicBool, logged_out: &AtomicBool, out_rx: &Receiver<Outbound>, in_tx: &Sender<InboundEvent>,
+    config: &SessionConfig, seqnum: &Mutex<u32>, metrics: Arc<Mutex<ThrottleMetrics>>, latency: &SharedLatencyTracker,
+    kill_switch: &Mutex<Option<KillSwitch>>, orders: Option<&SharedOrderManager>, reconnecting: bool,
+) {
+    let mut throttle = Throttle::new(config.throttle, metrics, Instant::now());
+    let mut last_sent: Instant = Instant::now();
+    // after Shutdown, messages still queued by the throttle are written before the writer stops
+    let mut closing: bool = false;
+    while running.load(Ordering::SeqCst) {
+        // orders and amends queued before the kill switch tripped must not follow its mass cancel onto the wire
+        if !throttle.is_empty() && kill_switch.lock().unwrap().as_ref().map_or(false, KillSwitch::is_tripped) {
+            for msg in throttle.purge(&[MessageClass::New, MessageClass::Amend]) {
+                error!("FIX writer - {} not sent, kill switch tripped", msg.to_display());
+                if let (Some(orders), Some(cl_ord_id)) = (orders, msg.get(11)) {
+                    orders.lock().unwrap().reject_local(cl_ord_id, "kill switch tripped");
+                }
+            }
+        }
+        let now: Instant = Instant::now();
+        let mut msg: RawMessage = match throttle.pop_ready(now) {
+            Some(msg) => msg,
+            None => {
+                let idle: Duration = config.heartbeat_interval.saturating_sub(now.duration_since(last_sent));
+                let wait: Duration = throttle.next_ready_in(now).map_or(idle, |ready_in| ready_in.min(idle));
+                if closing {
+                    if throttle.is_empty() {
+                        break;
+                    }
+                    thread::sleep(wait);
+                    continue;
+                }
+                match out_rx.recv_timeout(wait) {
+                    Ok(Outbound::Message(msg)) => {
+                        throttle.push(msg, Instant::now());
+                        continue;
+                    }
+                    Ok(Outbound::Batch(msgs)) => {
+                        let now: Instant = Instant::now();
+                        msgs.into_iter().for_each(|msg| throttle.push(msg, now));
+                        continue;
+                    }
+                    Ok(Outbound::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                        closing = true;
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Timeout) if last_sent.elapsed() >= config.heartbeat_interval => config.admin_message("0"),
+                    Err(RecvTimeoutError::Timeout) => continue,
+                }
+            }
+        };
+
+        let result = {
+            // assign next MsgSeqNum [34] at write time so concurrent senders never reuse a number, with the stream locked
+            // so a reconnect cannot restart numbering between taking the number and writing it
+            let mut stream = stream.lock().unwrap();
+            {
+                let mut seqnum = seqnum.lock().unwrap();
+                msg.set_header(34, *seqnum);
+                *seqnum += 1;
+            }
+            stream.write_all(msg.to_fix_string().as_bytes()).and_then(|()| stream.flush())
+        };
+        last_sent = Instant::now();
+        match result {
+            Ok(()) => {
+                latency.lock().unwrap().on_sent(&msg, last_sent);
+                if msg.msg_type() == "5" {
+                    logged_out.store(true, Ordering::SeqCst);
+                }
+                info!("FIX writer - sent {}", msg.to_display());
+            }
+            Err(error) if reconnecting => {
+                // the reader thread replaces the stream, later messages go out on the new connection
+                error!("FIX writer - {} not sent, connection lost: {error:?}", msg.to_display());
+            }
+            Err(error) => {
+                error!("FIX writer - error while sending msg {error:?}");
+                running.store(false, Ordering::SeqCst);
+                let _ = in_tx.send(InboundEvent::Disconnected(error.to_string()));
+                break;
+            }
+        }
+    }
+}
+
+// replaces the stream with a new connection from `connector` and writes its Logon (35=A) before anything else,
+// MsgSeqNum [34] restarts at 1, returns false when the session was stopped before reconnecting
+fn reconnect<S: Write>(
+    stream: &Mutex<S>, running: &AtomicBool, config: &SessionConfig, seqnum: &Mutex<u32>, connector: &mut Connector<S>,
+) -> bool {
+    while running.load(Ordering::SeqCst) {
+        match connector() {
+            Ok((new_stream, mut logon)) => {
+                // the stream stays locked until Logon is written, so the writer cannot send ahead of it
+                let mut stream = stream.lock().unwrap();
+                *stream = new_stream;
+                logon.set_header(34, 1);
+                *seqnum.lock().unwrap() = 2;
+                match stream.write_all(logon.to_fix_string().as_bytes()).and_then(|()| stream.flush()) {
+                    Ok(()) => {
+                        info!("FIX reader - reconnected, sent {}", logon.to_display());
+                        return true;
+                    }
+                    Err(error) => error!("FIX reader - Logon not sent after reconnect: {error:?}"),
+                }
+            }
+            Err(error) => error!("FIX reader - reconnect to {} failed: {error}", config.target_comp_id),
+        }
+        thread::sleep(config.reconnect_interval);
+    }
+    false
+}
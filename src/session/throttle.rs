@@ -0,0 +1,384 @@
+// src/session/throttle.rs
+//
+// Outbound throttle applied by the session writer thread
+//  - one token bucket per message class, new orders (35=D / 35=AB), cancels (35=F / 35=q) and amends (35=G),
+//    classes without a `RateLimit` are not delayed by their bucket
+//  - session and other messages (`MessageClass::Other`, e.g. Heartbeat, Logout, 35=H) take a lane of their own
+//    written first, so they are never delayed by orders waiting for a token
+//  - messages leave in the order they were sent, except cancels which take a priority lane ahead of queued
+//    orders when `ThrottleConfig::cancel_priority` is set
+//    n.b. a cancel can then overtake the queued order it cancels, the venue rejects it as unknown
+//  - time spent queued is recorded per class in `ThrottleMetrics`
+//
+
+use crate::fix::RawMessage;
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// `MessageClass`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageClass {
+    /// NewOrderSingle (35=D), NewOrderMultileg (35=AB)
+    New,
+    /// OrderCancelRequest (35=F), OrderMassCancelRequest (35=q)
+    Cancel,
+    /// OrderCancelReplaceRequest (35=G)
+    Amend,
+    /// session and every other application message
+    Other,
+}
+
+impl MessageClass {
+    pub fn of(msg: &RawMessage) -> MessageClass {
+        match msg.msg_type() {
+            "D" | "AB" => MessageClass::New,
+            "F" | "q" => MessageClass::Cancel,
+            "G" => MessageClass::Amend,
+            _ => MessageClass::Other,
+        }
+    }
+}
+
+impl fmt::Display for MessageClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `RateLimit`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// messages per second once the burst is used
+    pub per_second: f64,
+    /// messages sent back to back after an idle period, at least 1
+    pub burst: f64,
+}
+
+impl RateLimit {
+    pub fn new(per_second: f64, burst: f64) -> Self {
+        RateLimit { per_second, burst: burst.max(1.0) }
+    }
+}
+
+impl std::str::FromStr for RateLimit {
+    type Err = String;
+
+    /// parses `<per second>` or `<per second>:<burst>`, burst defaults to the rate
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("rate limit '{s}' is not valid, expected <per second>[:<burst>]");
+        let (rate, burst) = s.trim().split_once(':').unwrap_or((s.trim(), ""));
+        let per_second: f64 = rate.parse().ok().filter(|rate: &f64| *rate > 0.0).ok_or_else(invalid)?;
+        let burst: f64 =
+            if burst.is_empty() { per_second } else { burst.parse().ok().filter(|burst: &f64| *burst >= 1.0).ok_or_else(invalid)? };
+        Ok(RateLimit::new(per_second, burst))
+    }
+}
+
+/// `ThrottleConfig`
+///
+/// None = class is not throttled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThrottleConfig {
+    pub new: Option<RateLimit>,
+    pub cancel: Option<RateLimit>,
+    pub amend: Option<RateLimit>,
+    /// cancels are sent ahead of queued orders
+    pub cancel_priority: bool,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        ThrottleConfig { new: None, cancel: None, amend: None, cancel_priority: true }
+    }
+}
+
+impl ThrottleConfig {
+    fn limit(&self, class: MessageClass) -> Option<RateLimit> {
+        match class {
+            MessageClass::New => self.new,
+            MessageClass::Cancel => self.cancel,
+            MessageClass::Amend => self.amend,
+            MessageClass::Other => None,
+        }
+    }
+}
+
+/// `QueueStats`
+///
+/// messages of one class written by the session and the time they spent queued by the throttle
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueueStats {
+    pub sent: u64,
+    /// messages which could not be written when sent, their class had no token or messages were queued ahead of them
+    pub delayed: u64,
+    pub total_queued: Duration,
+    pub max_queued: Duration,
+}
+
+impl QueueStats {
+    pub fn mean_queued(&self) -> Duration {
+        if self.sent == 0 { Duration::ZERO } else { self.total_queued / u32::try_from(self.sent).unwrap_or(u32::MAX) }
+    }
+
+    fn record(&mut self, queued: Duration, delayed: bool) {
+        self.sent += 1;
+        if delayed {
+            self.delayed += 1;
+        }
+        self.total_queued += queued;
+        self.max_queued = self.max_queued.max(queued);
+    }
+}
+
+impl fmt::Display for QueueStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sent {} delayed {} queued mean {:?} max {:?}", self.sent, self.delayed, self.mean_queued(), self.max_queued)
+    }
+}
+
+/// `ThrottleMetrics`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ThrottleMetrics {
+    pub new: QueueStats,
+    pub cancel: QueueStats,
+    pub amend: QueueStats,
+    pub other: QueueStats,
+    /// messages waiting in the throttle now
+    pub queued: usize,
+}
+
+impl ThrottleMetrics {
+    pub fn stats(&self, class: MessageClass) -> &QueueStats {
+        match class {
+            MessageClass::New => &self.new,
+            MessageClass::Cancel => &self.cancel,
+            MessageClass::Amend => &self.amend,
+            MessageClass::Other => &self.other,
+        }
+    }
+
+    fn stats_mut(&mut self, class: MessageClass) -> &mut QueueStats {
+        match class {
+            MessageClass::New => &mut self.new,
+            MessageClass::Cancel => &mut self.cancel,
+            MessageClass::Amend => &mut self.amend,
+            MessageClass::Other => &mut self.other,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        TokenBucket { limit, tokens: limit.burst, refilled: now }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed: f64 = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst);
+        self.refilled = now;
+    }
+
+    /// time until a token is available, zero when one is available now
+    fn ready_in(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.tokens >= 1.0 { Duration::ZERO } else { Duration::from_secs_f64((1.0 - self.tokens) / self.limit.per_second) }
+    }
+
+    fn take(&mut self) {
+        self.tokens -= 1.0;
+    }
+}
+
+struct Queued {
+    msg: RawMessage,
+    class: MessageClass,
+    enqueued: Instant,
+    delayed: bool,
+}
+
+/// `Throttle`
+///
+/// queues outbound messages until their class has a token, see `SessionConfig::throttle`
+pub struct Throttle {
+    config: ThrottleConfig,
+    buckets: [Option<TokenBucket>; 3],
+    // MessageClass::Other, never throttled
+    session: VecDeque<Queued>,
+    priority: VecDeque<Queued>,
+    queue: VecDeque<Queued>,
+    metrics: Arc<Mutex<ThrottleMetrics>>,
+}
+
+impl Throttle {
+    pub fn new(config: ThrottleConfig, metrics: Arc<Mutex<ThrottleMetrics>>, now: Instant) -> Self {
+        let bucket = |class: MessageClass| config.limit(class).map(|limit| TokenBucket::new(limit, now));
+        Throttle {
+            config,
+            buckets: [bucket(MessageClass::New), bucket(MessageClass::Cancel), bucket(MessageClass::Amend)],
+            session: VecDeque::new(),
+            priority: VecDeque::new(),
+            queue: VecDeque::new(),
+            metrics,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.session.is_empty() && self.priority.is_empty() && self.queue.is_empty()
+    }
+
+    /// `push`
+    ///
+    /// queues message sent at `now`
+    pub fn push(&mut self, msg: RawMessage, now: Instant) {
+        let class: MessageClass = MessageClass::of(&msg);
+        if class == MessageClass::Other {
+            self.session.push_back(Queued { msg, class, enqueued: now, delayed: false });
+            self.metrics.lock().unwrap().queued += 1;
+            return;
+        }
+        let priority: bool = class == MessageClass::Cancel && self.config.cancel_priority;
+        let lane_empty: bool = if priority { self.priority.is_empty() } else { self.queue.is_empty() };
+        let delayed: bool = !lane_empty || self.ready_in(class, now) > Duration::ZERO;
+        let queued = Queued { msg, class, enqueued: now, delayed };
+        if priority {
+            self.priority.push_back(queued);
+        } else {
+            self.queue.push_back(queued);
+        }
+        self.metrics.lock().unwrap().queued += 1;
+    }
+
+    /// `pop_ready`
+    ///
+    /// next message which may be written at `now`, session lane first, then priority lane
+    pub fn pop_ready(&mut self, now: Instant) -> Option<RawMessage> {
+        let queued: Queued = match self.session.pop_front() {
+            Some(queued) => queued,
+            None => {
+                let from_priority: bool = match (self.priority.front().map(|q| q.class), self.queue.front().map(|q| q.class)) {
+                    (Some(class), _) if self.ready_in(class, now) == Duration::ZERO => true,
+                    (_, Some(class)) if self.ready_in(class, now) == Duration::ZERO => false,
+                    _ => return None,
+                };
+                if from_priority { self.priority.pop_front()? } else { self.queue.pop_front()? }
+            }
+        };
+        if let Some(bucket) = self.bucket(queued.class) {
+            bucket.take();
+        }
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.queued = metrics.queued.saturating_sub(1);
+        metrics.stats_mut(queued.class).record(now.saturating_duration_since(queued.enqueued), queued.delayed);
+        Some(queued.msg)
+    }
+
+    /// `purge`
+    ///
+    /// removes every queued message of `classes` and returns them in the order they were sent,
+    /// e.g. orders and amends still queued when the kill switch trips
+    pub fn purge(&mut self, classes: &[MessageClass]) -> Vec<RawMessage> {
+        let mut purged: Vec<RawMessage> = Vec::new();
+        for lane in [&mut self.priority, &mut self.queue] {
+            let (removed, kept): (VecDeque<Queued>, VecDeque<Queued>) = lane.drain(..).partition(|q| classes.contains(&q.class));
+            *lane = kept;
+            purged.extend(removed.into_iter().map(|q| q.msg));
+        }
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.queued = metrics.queued.saturating_sub(purged.len());
+        purged
+    }
+
+    /// `next_ready_in`
+    ///
+    /// time until a queued message may be written, None when nothing is queued
+    pub fn next_ready_in(&mut self, now: Instant) -> Option<Duration> {
+        if !self.session.is_empty() {
+            return Some(Duration::ZERO);
+        }
+        let classes: Vec<MessageClass> = self.priority.front().into_iter().chain(self.queue.front()).map(|q| q.class).collect();
+        classes.into_iter().map(|class| self.ready_in(class, now)).min()
+    }
+
+    fn ready_in(&mut self, class: MessageClass, now: Instant) -> Duration {
+        self.bucket(class).map_or(Duration::ZERO, |bucket| bucket.ready_in(now))
+    }
+
+    fn bucket(&mut self, class: MessageClass) -> Option<&mut TokenBucket> {
+        match class {
+            MessageClass::New => self.buckets[0].as_mut(),
+            MessageClass::Cancel => self.buckets[1].as_mut(),
+            MessageClass::Amend => self.buckets[2].as_mut(),
+            MessageClass::Other => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod throttle_tests {
+
+    use super::{MessageClass, RateLimit, Throttle, ThrottleConfig, ThrottleMetrics};
+    use crate::fix::RawMessage;
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    fn msg(msg_type: &str, cl_ord_id: &str) -> RawMessage {
+        let mut msg = RawMessage::new(msg_type);
+        msg.set(11, cl_ord_id);
+        msg
+    }
+
+    #[test]
+    fn test_token_bucket_and_cancel_priority_lane() {
+        let start = Instant::now();
+        let metrics = Arc::new(Mutex::new(ThrottleMetrics::default()));
+        let config = ThrottleConfig { new: Some("2:1".parse().unwrap()), ..ThrottleConfig::default() };
+        let mut throttle = Throttle::new(config, Arc::clone(&metrics), start);
+        for cl_ord_id in ["N1", "N2", "N3"] {
+            throttle.push(msg("D", cl_ord_id), start);
+        }
+        assert_eq!(throttle.pop_ready(start).unwrap().get(11), Some("N1"));
+        assert!(throttle.pop_ready(start).is_none());
+        assert_eq!(throttle.next_ready_in(start), Some(Duration::from_millis(500)));
+
+        // heartbeats and cancels jump the queue of new orders waiting for a token, heartbeats first
+        throttle.push(msg("F", "C1"), start);
+        throttle.push(msg("0", "HB"), start);
+        assert_eq!(throttle.next_ready_in(start), Some(Duration::ZERO));
+        assert_eq!(throttle.pop_ready(start).unwrap().get(11), Some("HB"));
+        assert_eq!(throttle.pop_ready(start).unwrap().get(11), Some("C1"));
+        let later = start + Duration::from_millis(500);
+        assert_eq!(throttle.pop_ready(later).unwrap().get(11), Some("N2"));
+        assert!(throttle.pop_ready(later).is_none());
+        let end = start + Duration::from_millis(1000);
+        assert_eq!(throttle.pop_ready(end).unwrap().get(11), Some("N3"));
+        assert!(throttle.is_empty());
+
+        throttle.push(msg("D", "N4"), end);
+        throttle.push(msg("G", "A1"), end);
+        throttle.push(msg("D", "N5"), end);
+        throttle.push(msg("F", "C2"), end);
+        let purged: Vec<RawMessage> = throttle.purge(&[MessageClass::New, MessageClass::Amend]);
+        assert_eq!(purged.iter().map(|msg| msg.get(11).unwrap()).collect::<Vec<&str>>(), ["N4", "A1", "N5"]);
+        assert_eq!(throttle.pop_ready(end).unwrap().get(11), Some("C2"));
+        assert!(throttle.is_empty());
+
+        let metrics = *metrics.lock().unwrap();
+        assert_eq!((metrics.new.sent, metrics.new.delayed, metrics.new.max_queued), (3, 2, Duration::from_millis(1000)));
+        assert_eq!(metrics.stats(MessageClass::Cancel).sent, 2);
+        assert_eq!((metrics.other.sent, metrics.other.delayed, metrics.other.max_queued, metrics.queued), (1, 0, Duration::ZERO, 0));
+        assert!("0".parse::<RateLimit>().is_err() && "5:0.5".parse::<RateLimit>().is_err());
+    }
+}
@@ -1,7 +1,15 @@
+pub mod setup_cl_ord_id;
+pub mod setup_drop_copy;
 pub mod setup_env;
 pub mod setup_heartbeat;
+pub mod setup_instruments;
 pub mod setup_keys;
+pub mod setup_kill_switch;
 pub mod setup_logging;
+pub mod setup_paper;
+pub mod setup_positions;
 pub mod setup_rfq;
+pub mod setup_risk;
 pub mod setup_session;
+pub mod setup_throttle;
 pub mod setup_trading;
\ No newline at end of file
@@ -0,0 +1,29 @@
+use client_rust_fix::orders::cl_ord_id::{set_generator, ClOrdIdGenerator, CounterGenerator, PrefixGenerator, UuidGenerator, UuidVersion};
+use log::info;
+use std::{env::var, error::Error};
+
+pub(crate) fn exec() -> Result<bool, Box<dyn Error>> {
+    //
+    // ClOrdID [11] strategy, PT_CL_ORD_ID = counter / uuid4 / uuid7 (default) / prefix
+    //  - counter - PT_CL_ORD_ID_PREFIX tag + date + counter saved in PT_CL_ORD_ID_FILE
+    //  - prefix  - PT_CL_ORD_ID_PREFIX + start time + counter
+    //
+    let strategy: String = var("PT_CL_ORD_ID").unwrap_or_else(|_| "uuid7".to_string());
+    let prefix: String = var("PT_CL_ORD_ID_PREFIX").unwrap_or_else(|_| "PT".to_string());
+    let generator: Box<dyn ClOrdIdGenerator> = match strategy.to_lowercase().as_str() {
+        "counter" => {
+            let file: String = var("PT_CL_ORD_ID_FILE").unwrap_or_else(|_| ".cl_ord_id".to_string());
+            let generator = CounterGenerator::open(&file, &prefix)?;
+            info!("ClOrdID counter continues from {} ({file})", generator.last());
+            Box::new(generator)
+        }
+        "uuid4" => Box::new(UuidGenerator::new(UuidVersion::V4)),
+        "uuid7" => Box::new(UuidGenerator::new(UuidVersion::V7)),
+        "prefix" => Box::new(PrefixGenerator::new(&prefix)),
+        _ => return Err(format!("PT_CL_ORD_ID '{strategy}' is not valid, expected counter, uuid4, uuid7 or prefix").into()),
+    };
+    set_generator(generator);
+    println!("ClOrdID strategy : {strategy}");
+    info!("ClOrdID strategy : {strategy}");
+    Ok(true)
+}
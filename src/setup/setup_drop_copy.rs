@@ -0,0 +1,55 @@
+use client_rust_fix::orders::SharedOrderManager;
+use client_rust_fix::positions::SharedPositionKeeper;
+use client_rust_fix::risk::SharedRiskGate;
+use client_rust_fix::session::{DropCopy, SessionConfig};
+use client_rust_fix::sim::Simulator;
+use jwtk::ecdsa::EcdsaPrivateKey;
+use log::{error, info};
+use native_tls::TlsStream;
+use std::{env::var, error::Error, net::TcpStream, sync::Arc};
+
+use crate::setup::setup_session;
+use crate::utils::{connect_tls, setup_tls_connection};
+
+pub(crate) fn exec(
+    apikey: &str, pkey: EcdsaPrivateKey, simulator: Option<&Simulator>, orders: &SharedOrderManager, risk: &SharedRiskGate,
+    positions: &SharedPositionKeeper,
+) -> Result<(bool, Option<DropCopy>), Box<dyn Error>> {
+    //
+    // drop-copy session is optional, enabled with PT_DROP_COPY=true
+    //
+    let enabled: bool = var("PT_DROP_COPY").map(|value| value.eq_ignore_ascii_case("true")).unwrap_or(false);
+    if !enabled {
+        info!("Drop-copy session is disabled");
+        return Ok((true, None));
+    }
+    let target_comp_id: String = var("PT_DROP_COPY_TARGET").unwrap_or_else(|_| "PT-DC".to_string());
+    let config: SessionConfig = SessionConfig::drop_copy(apikey, &target_comp_id);
+    println!("Starting drop-copy session with TargetCompID {target_comp_id}");
+
+    //
+    // connect to simulator when paper trading, power.trade drop-copy port otherwise
+    // ExecutionReports are merged into the same order state as the order-entry session
+    //
+    let (status, _seqnum, session) = match simulator {
+        Some(simulator) => {
+            let address = simulator.local_addr();
+            let stream: TcpStream = TcpStream::connect(address)?;
+            setup_session::exec(config, pkey, stream, Arc::clone(orders), move || TcpStream::connect(address))?
+        }
+        None => {
+            let port: String = var("PT_DROP_COPY_PORT").unwrap_or_else(|_| "2021".to_string());
+            let tls_stream: TlsStream<TcpStream> = setup_tls_connection(&port);
+            setup_session::exec(config, pkey, tls_stream, Arc::clone(orders), move || connect_tls(&port))?
+        }
+    };
+    if !status {
+        error!("Drop-copy session logon failed");
+        session.logout();
+        return Ok((false, None));
+    }
+    // fills seen on drop-copy count towards the daily loss limit, reports seen on both sessions are counted once
+    session.outbound.set_risk_gate(Arc::clone(risk));
+    session.outbound.set_position_keeper(Arc::clone(positions));
+    Ok((true, Some(DropCopy::start(session))))
+}
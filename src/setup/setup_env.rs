@@ -1,16 +1,21 @@
 use std::error::Error;
 use clap::{value_parser, Arg, ArgAction, ArgMatches, Command, ValueEnum};
+use client_rust_fix::instruments::InstrumentQuery;
+use client_rust_fix::orders::MassCancelScope;
 use log::info;
 use std::env::var;
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-enum Environment {
+pub(crate) enum Environment {
     Development,
     Test,
-    Production
+    Production,
+    /// paper trading against the in-process simulator (see `sim::Simulator`)
+    Paper
 }
 
-pub(crate) fn exec() -> Result<(bool, String), Box<dyn Error>> {
+#[allow(clippy::type_complexity)]
+pub(crate) fn exec() -> Result<(bool, String, Environment, Option<MassCancelScope>, Option<InstrumentQuery>), Box<dyn Error>> {
     println!("Initializing env ...");
 
     // check ENV to be run && set env config file name based on ENV settings
@@ -27,6 +32,22 @@ pub(crate) fn exec() -> Result<(bool, String), Box<dyn Error>> {
             .value_name("pt_env")
             .value_parser(value_parser!(Environment))
      )
+    .arg(
+        Arg::new("cancel-all")
+            .action(ArgAction::Set)
+            .long("cancel-all")
+            .help("Cancel open orders after logon instead of running PT_SCENARIO - all, symbol=<symbol> or type=<security type>")
+            .value_name("scope")
+            .value_parser(|scope: &str| scope.parse::<MassCancelScope>())
+     )
+    .arg(
+        Arg::new("instruments")
+            .action(ArgAction::Set)
+            .long("instruments")
+            .help("Request security list after logon and print instruments matching query instead of running PT_SCENARIO, e.g. \"underlying=SOL type=option expires=week\"")
+            .value_name("query")
+            .value_parser(|query: &str| query.parse::<InstrumentQuery>())
+     )
     .arg(Arg::new("custom-help")
         .short('?')
         .action(ArgAction::Help)
@@ -55,13 +76,27 @@ pub(crate) fn exec() -> Result<(bool, String), Box<dyn Error>> {
             // Load environment variables from production version of .env file
             dotenvy::from_filename(".env.prod").expect("Failed to load env values from file '.env.prod'");
         },
+        Environment::Paper => {
+            info!("Environment is set to PAPER");
+            // Load environment variables from paper trading version of .env file
+            dotenvy::from_filename(".env.paper").expect("Failed to load env values from file '.env.paper'");
+        },
     }
 
     //
     // load Scenario setting from Env
     //
-    let scenario: String = var("PT_SCENARIO").unwrap();
+    // n.b. --cancel-all and --instruments override the scenario from Env
+    let cancel_scope: Option<MassCancelScope> = matches.get_one::<MassCancelScope>("cancel-all").cloned();
+    let instrument_query: Option<InstrumentQuery> = matches.get_one::<InstrumentQuery>("instruments").cloned();
+    let scenario: String = if cancel_scope.is_some() {
+        "CANCEL_ALL".to_string()
+    } else if instrument_query.is_some() {
+        "SECURITY_LIST".to_string()
+    } else {
+        var("PT_SCENARIO").unwrap()
+    };
     println!("Executing Scenario : {scenario}");
     info!("Executing Scenario : {scenario}");
-    Ok((true, scenario))
+    Ok((true, scenario, *pt_env, cancel_scope, instrument_query))
 }
@@ -0,0 +1,26 @@
+use client_rust_fix::instruments::{set_registry, InstrumentRegistry};
+use log::info;
+use std::{env::var, error::Error, path::Path};
+
+pub(crate) fn exec() -> Result<bool, Box<dyn Error>> {
+    //
+    // instrument reference data (tick size, lot size, minimum quantity, multiplier, status) used to validate orders
+    //  - PT_INSTRUMENTS_FILE - JSON array or CSV ('.csv') cache, orders are not checked when it is not set
+    //  - a missing file is not an error, it is written once a SecurityList (35=y) was received
+    //
+    let Ok(file) = var("PT_INSTRUMENTS_FILE") else {
+        info!("Instrument registry is not set, orders are not checked against reference data");
+        return Ok(true);
+    };
+    let path: &Path = Path::new(&file);
+    if !path.exists() {
+        info!("Instrument cache {file} not found, orders are not checked against reference data");
+        println!("Instrument cache {file} not found, orders are not checked against reference data");
+        return Ok(true);
+    }
+    let registry: InstrumentRegistry = InstrumentRegistry::load(path)?;
+    println!("Instruments : {} loaded from {file}", registry.len());
+    info!("Instruments : {} loaded from {file}", registry.len());
+    set_registry(registry);
+    Ok(true)
+}
@@ -0,0 +1,51 @@
+use client_rust_fix::session::{KillSwitch, KillTrigger};
+use log::{error, info};
+use std::{env::var, error::Error, io::{stdin, BufRead}, path::Path, thread, time::Duration};
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn exec() -> Result<(bool, KillSwitch, Duration, bool), Box<dyn Error>> {
+    //
+    // client-side kill switch, SIGINT / SIGTERM always trip it
+    //  - PT_KILL_SWITCH_FILE=<path> - trips once the file exists, e.g. `touch /tmp/pt.kill`
+    //  - PT_KILL_SWITCH_STDIN=true - trips when 'kill' is typed on stdin
+    //  - PT_KILL_SWITCH_TIMEOUT_MS - how long to wait for cancels to be confirmed before Logout, default 5000
+    //  - PT_CANCEL_ON_RECONNECT=true - cancel orders still working when the session logs on again after PT_RECONNECT
+    //
+    let mut kill_switch: KillSwitch = KillSwitch::new();
+    if let Some(file) = var("PT_KILL_SWITCH_FILE").ok().filter(|file| !file.trim().is_empty()) {
+        info!("Kill switch flag file : {file}");
+        println!("Kill switch flag file : {file}");
+        kill_switch = kill_switch.flag_file(Path::new(file.trim()));
+    }
+    kill_switch.register_signals()?;
+
+    if var("PT_KILL_SWITCH_STDIN").map_or(false, |value| value.eq_ignore_ascii_case("true")) {
+        let commands: KillSwitch = kill_switch.clone();
+        thread::Builder::new().name("kill-switch-stdin".to_string()).spawn(move || {
+            for line in stdin().lock().lines() {
+                match line {
+                    Ok(line) if line.trim().eq_ignore_ascii_case("kill") => {
+                        commands.trip(KillTrigger::Command(line.trim().to_string()));
+                        return;
+                    }
+                    Ok(line) => println!("Unknown command '{line}', type 'kill' to cancel all orders and log out"),
+                    Err(error) => {
+                        error!("Kill switch - stdin closed: {error}");
+                        return;
+                    }
+                }
+            }
+        })?;
+        println!("Kill switch : type 'kill' to cancel all orders and log out");
+    }
+
+    let timeout: Duration = match var("PT_KILL_SWITCH_TIMEOUT_MS").unwrap_or_default().trim() {
+        "" => Duration::from_millis(5000),
+        value => Duration::from_millis(
+            value.parse().map_err(|_| format!("PT_KILL_SWITCH_TIMEOUT_MS '{value}' is not a number of milliseconds"))?,
+        ),
+    };
+    let cancel_on_reconnect: bool = var("PT_CANCEL_ON_RECONNECT").map_or(false, |value| value.eq_ignore_ascii_case("true"));
+    info!("Kill switch : cancel confirm timeout {timeout:?}, cancel on reconnect {cancel_on_reconnect}");
+    Ok((true, kill_switch, timeout, cancel_on_reconnect))
+}
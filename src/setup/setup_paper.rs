@@ -0,0 +1,32 @@
+use client_rust_fix::sim::{SimCommand, SimConfig, Simulator};
+use log::{error, info};
+use std::{env::var, error::Error, net::TcpStream};
+
+pub(crate) fn exec() -> Result<(bool, Simulator, TcpStream), Box<dyn Error>> {
+    //
+    // start simulator in-process on a free local port, orders are matched in its order book
+    //
+    let simulator: Simulator = Simulator::start(SimConfig::new("127.0.0.1:0"))?;
+    info!("Paper trading simulator listening on {}", simulator.local_addr());
+    println!("Paper trading simulator listening on {}", simulator.local_addr());
+
+    //
+    // apply simulator commands from env, e.g. seed the book so orders are filled
+    // PT_SIM_COMMANDS="liquidity SOL-USD buy 10@388;liquidity SOL-USD sell 10@390"
+    //
+    let mut status: bool = true;
+    for line in var("PT_SIM_COMMANDS").unwrap_or_default().split(';').filter(|line| !line.trim().is_empty()) {
+        match line.parse::<SimCommand>() {
+            Ok(command) => simulator.command(command),
+            Err(error) => {
+                error!("Invalid PT_SIM_COMMANDS entry: {error}");
+                println!("Invalid PT_SIM_COMMANDS entry: {error}");
+                status = false;
+            }
+        }
+    }
+
+    let stream: TcpStream = TcpStream::connect(simulator.local_addr())?;
+    stream.set_nodelay(true)?;
+    Ok((status, simulator, stream))
+}
@@ -0,0 +1,19 @@
+use client_rust_fix::positions::{PositionKeeper, SharedPositionKeeper};
+use log::info;
+use std::{env::var, error::Error};
+
+pub(crate) fn exec() -> Result<(bool, SharedPositionKeeper), Box<dyn Error>> {
+    //
+    // positions rebuilt from fills on order-entry and drop-copy sessions
+    //  - PT_MARK_PRICES="SOL-USD=390;BTC-USD=60000" - prices unrealized PnL is valued at, symbols not listed have none
+    //
+    let mut keeper: PositionKeeper = PositionKeeper::new();
+    for entry in var("PT_MARK_PRICES").unwrap_or_default().split(';').filter(|entry| !entry.trim().is_empty()) {
+        let (symbol, price) =
+            entry.trim().split_once('=').ok_or(format!("PT_MARK_PRICES entry '{entry}' is not valid, expected symbol=price"))?;
+        let price: f64 = price.trim().parse().map_err(|_| format!("PT_MARK_PRICES entry '{entry}' has no valid price"))?;
+        keeper.set_mark_price(symbol.trim(), price);
+        info!("Mark price for {} : {price}", symbol.trim());
+    }
+    Ok((true, keeper.shared()))
+}
@@ -1,4 +1,6 @@
 
+use client_rust_fix::fix::Decimal;
+use client_rust_fix::instruments::Symbol;
 use log::info;
 use quickfix_msg44::field_types::{OrdType, Side};
 use std::error::Error;
@@ -13,7 +15,7 @@ pub(crate) fn exec(apikey: &str, seqnum_latest: u32) -> Result<(bool,Message), B
     // TODO - assign values fron .env file
     // 
     static SYMBOL: &str = "BTC-USD";
-    const QUANTITY: f64 = 2.00; 
+    const QUANTITY: Decimal = Decimal::new(200, 2); 
     const SIDE: Side = Side::Sell;
     const ORDERTYPE: OrdType = OrdType::Limit;
     // create RFQ subscription msg for demonstrationg RFQ quote flow 
@@ -32,7 +34,7 @@ pub(crate) fn exec(apikey: &str, seqnum_latest: u32) -> Result<(bool,Message), B
 
     // Create RFQ quote msg for creating new RFQ quote for a symbol
     //
-    let rfq_quote_msg: Message = FixMessageFactory::new_rfq_quote( apikey, SYMBOL.to_string(), SIDE, QUANTITY, ORDERTYPE, seqnum_latest ).unwrap();
+    let rfq_quote_msg: Message = FixMessageFactory::new_rfq_quote( apikey, SYMBOL.parse::<Symbol>()?, SIDE, QUANTITY, ORDERTYPE, seqnum_latest ).unwrap();
     info!("Created new RFQ Quote msg using FixMsgFactory: {rfq_quote_msg:?}");
 
     Ok((true, rfq_quote_msg))
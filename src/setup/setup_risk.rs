@@ -0,0 +1,53 @@
+use client_rust_fix::orders::SharedOrderManager;
+use client_rust_fix::positions::SharedPositionKeeper;
+use client_rust_fix::risk::{RiskGate, RiskLimits, SharedRiskGate, SymbolLimits};
+use log::info;
+use std::{env::var, error::Error, str::FromStr, sync::Arc};
+
+pub(crate) fn exec(orders: &SharedOrderManager, positions: &SharedPositionKeeper) -> Result<(bool, SharedRiskGate), Box<dyn Error>> {
+    //
+    // pre-trade risk limits, every limit not set is not checked
+    //  - PT_RISK_MAX_QTY / PT_RISK_MAX_NOTIONAL - per order, for symbols not listed in PT_RISK_SYMBOLS
+    //  - PT_RISK_SYMBOLS="SOL-USD:10:5000;BTC-USD:0.1:10000" - symbol:max quantity:max notional, empty = no limit
+    //  - PT_RISK_PRICE_BAND_PCT, PT_RISK_MAX_OPEN_ORDERS, PT_RISK_MAX_ORDERS_PER_SEC,
+    //    PT_RISK_DAILY_LOSS_LIMIT - on realized PnL less fees of the shared positions
+    //  - PT_RISK_REFERENCE_PRICES="SOL-USD=388" - price band reference until the first fill
+    //
+    let mut limits: RiskLimits = RiskLimits::new();
+    limits.default_symbol =
+        SymbolLimits { max_order_qty: env_number("PT_RISK_MAX_QTY")?, max_notional: env_number("PT_RISK_MAX_NOTIONAL")? };
+    for entry in var("PT_RISK_SYMBOLS").unwrap_or_default().split(';').filter(|entry| !entry.trim().is_empty()) {
+        let parts: Vec<&str> = entry.trim().split(':').collect();
+        let (symbol, max_order_qty, max_notional) = match parts.as_slice() {
+            [symbol, max_order_qty] => (symbol, number(max_order_qty)?, None),
+            [symbol, max_order_qty, max_notional] => (symbol, number(max_order_qty)?, number(max_notional)?),
+            _ => return Err(format!("PT_RISK_SYMBOLS entry '{entry}' is not valid, expected symbol:max quantity:max notional").into()),
+        };
+        limits = limits.symbol(symbol, SymbolLimits { max_order_qty, max_notional });
+    }
+    limits.price_band_pct = env_number("PT_RISK_PRICE_BAND_PCT")?;
+    limits.max_open_orders = env_number("PT_RISK_MAX_OPEN_ORDERS")?;
+    limits.max_orders_per_second = env_number("PT_RISK_MAX_ORDERS_PER_SEC")?;
+    limits.daily_loss_limit = env_number("PT_RISK_DAILY_LOSS_LIMIT")?;
+
+    let mut gate: RiskGate = RiskGate::new(limits, Arc::clone(orders), Arc::clone(positions));
+    for entry in var("PT_RISK_REFERENCE_PRICES").unwrap_or_default().split(';').filter(|entry| !entry.trim().is_empty()) {
+        let (symbol, price) =
+            entry.trim().split_once('=').ok_or(format!("PT_RISK_REFERENCE_PRICES entry '{entry}' is not valid, expected symbol=price"))?;
+        gate.set_reference_price(symbol, number(price)?.ok_or(format!("PT_RISK_REFERENCE_PRICES entry '{entry}' has no price"))?);
+    }
+    info!("Risk limits : {:?}", gate.limits());
+    println!("Risk limits : {:?}", gate.limits());
+    Ok((true, gate.shared()))
+}
+
+fn env_number<T: FromStr>(name: &str) -> Result<Option<T>, Box<dyn Error>> {
+    number(&var(name).unwrap_or_default()).map_err(|error| format!("{name}: {error}").into())
+}
+
+fn number<T: FromStr>(value: &str) -> Result<Option<T>, Box<dyn Error>> {
+    match value.trim() {
+        "" => Ok(None),
+        value => value.parse().map(Some).map_err(|_| format!("'{value}' is not a valid number").into()),
+    }
+}
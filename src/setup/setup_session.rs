@@ -1,20 +1,27 @@
+use client_rust_fix::fix::RawMessage;
+use client_rust_fix::orders::SharedOrderManager;
+use client_rust_fix::session::{run, FixApplication, Session, SessionConfig, SessionStream};
 use jwtk::ecdsa::EcdsaPrivateKey;
 use log::{info, error};
-use native_tls::TlsStream;
-use std::{error::Error, io::{Read, Write}, net::TcpStream, sync::{Arc, Mutex}};
+use std::{env::var, error::Error, io::{self, ErrorKind}, sync::{mpsc::RecvTimeoutError, Arc, Mutex}, time::Duration};
 use quickfix::Message;
-use crate::{factory::FixMessageFactory, utils::get_attr, increment_seqnum};
+use crate::factory::FixMessageFactory;
 
 #[allow(clippy::type_complexity)]
-pub(crate) fn exec(apikey: &str, pkey: EcdsaPrivateKey, tls_stream: &mut TlsStream<TcpStream>) ->  Result<(bool, Arc<Mutex<u32>>), Box<dyn Error>> {
-
+pub(crate) fn exec<S, C>(
+    config: SessionConfig, pkey: EcdsaPrivateKey, stream: S, orders: SharedOrderManager, mut connect: C,
+) -> Result<(bool, Arc<Mutex<u32>>, Session), Box<dyn Error>>
+where
+    S: SessionStream,
+    C: FnMut() -> io::Result<S> + Send + 'static,
+{
     let mut status: bool = false;
 
     //+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
     //
     // Create Fix LOGON Message using environment settings
     //
-    let logon_msg: Message = match FixMessageFactory::new_logon( apikey.to_string(), pkey) {
+    let logon_msg: Message = match FixMessageFactory::new_logon( config.sender_comp_id.clone(), pkey.clone()) {
         Ok(logon_msg) => {
             println!("Created new Fix Logon msg : {:?}", logon_msg);
             logon_msg
@@ -26,78 +33,98 @@ pub(crate) fn exec(apikey: &str, pkey: EcdsaPrivateKey, tls_stream: &mut TlsStre
     };
 
     //
-    // Send Fix LOGON message to server via TLS channel 
+    // Start reader/writer threads for the session
+    // - short read timeout lets the reader release the stream so writer is never blocked for long
+    // - Sequence is LOGON = 1, writer increments seqnum on every message sent
+    // - ExecutionReports are merged into shared order state by the reader thread
+    // - PT_RECONNECT=true - when the connection drops, reconnect with `connect` and logon again,
+    //   PT_CANCEL_ON_RECONNECT=true then cancels the orders left working
     //
-    match tls_stream.write( logon_msg.to_fix_string().expect("Error converting Logon Msg to Bytes").as_bytes()) {
-        Ok(byte_count) => { println!("Sent {byte_count} bytes"); }
-        Err(error) => { println!("Error while sending msg {error} "); }
+    let seqnum: Arc<Mutex<u32>> = Arc::new(Mutex::new(1));
+    let target_comp_id: String = config.target_comp_id.clone();
+    info!("Starting {:?} session with TargetCompID {target_comp_id}", config.role);
+    let reconnect: bool = var("PT_RECONNECT").map_or(false, |value| value.eq_ignore_ascii_case("true"));
+    if config.cancel_on_reconnect && !reconnect {
+        println!("PT_CANCEL_ON_RECONNECT has no effect unless PT_RECONNECT=true");
+    }
+    let session: Session = if reconnect {
+        let sender_comp_id: String = config.sender_comp_id.clone();
+        let logon_target: String = target_comp_id.clone();
+        Session::spawn_reconnecting(stream, config, Arc::clone(&seqnum), orders, move || {
+            // a new Logon for every connection, its JWT must not have expired
+            let logon_msg: Message = FixMessageFactory::new_logon(sender_comp_id.clone(), pkey.clone())
+                .map_err(|error| io::Error::new(ErrorKind::Other, format!("{error:?}")))?;
+            let mut logon: RawMessage =
+                RawMessage::parse(&logon_msg.to_fix_string().map_err(|error| io::Error::new(ErrorKind::Other, format!("{error:?}")))?)
+                    .map_err(|error| io::Error::new(ErrorKind::InvalidData, error.to_string()))?;
+            logon.set_header(56, &logon_target);
+            info!("Reconnecting {logon_target} session");
+            println!("Reconnecting {logon_target} session");
+            Ok((connect()?, logon))
+        })?
+    } else {
+        Session::spawn_tracked(stream, config, Arc::clone(&seqnum), orders)?
     };
-    println!("Checking response to LOGON msg ...");
 
     //
-    // setup buffer to hold the logon msg response
+    // Send Fix LOGON message to server via session writer, addressed to the session TargetCompID [56]
     //
-    let mut buffer = [0; 1024];
+    let mut logon: RawMessage = RawMessage::parse(&logon_msg.to_fix_string().expect("Error converting Logon Msg to Bytes"))?;
+    logon.set_header(56, &target_comp_id);
+    session.outbound.send_raw(logon)?;
+    println!("Checking response to LOGON msg ...");
 
     //
-    // Read response to LOGON request msg
+    // Read responses until LOGON is confirmed or session is rejected/closed
     //
-    match tls_stream.read(&mut buffer) {
-        Ok(bytes_read) => {
-            if bytes_read > 0 {
-                let mut islogin_complete = false;
-                while !islogin_complete {
-                    let response:String = String::from_utf8_lossy(&buffer[..bytes_read]).replace('\x01', "|");
-                    //
-                    // Check Msg type
-                    //
-                    let msg_type: String = get_attr(&response, "35");
-                    match msg_type.as_str() {
-                        "A" => {
-                            info!("Received Logon response: {response}");
-                            println!("Received Logon response: {response}");
-                            islogin_complete = true;
-                        },
-                        "D" => {
-                            info!("Received New Order(Single) response: {response}");
-                            println!("Received New Order(Single) response: {response}");
-                        },
-                        "3" => {
-                            info!("Received Reject response: {response}");
-                            println!("Received Reject response: {response}");
-                        },
-                        "8" => {
-                            info!("Received Exec Report response: {response}");
-                            println!("Received Exec Report response: {response}");
-                        },
-                        _ => {
-                            info!("Received different response type: {response}");
-                            println!("Received different response type: {response}");
-                        }
-                    };
-                    //
-                    // Keep reading messages if login not completed
-                    //
-                    if !islogin_complete {
-                        let _ = tls_stream.read(&mut buffer);
-                    } 
-                }
-                if islogin_complete { // update status if login was completed Ok
-                     status = true 
-                };
-            } else {
-                error!("No response received from server for Logon request");
-                println!("No response received from server for Logon request");
-            }
+    const LOGON_TIMEOUT: Duration = Duration::from_secs(10);
+    let mut logon: LogonResponse = LogonResponse::default();
+    match run(&mut logon, &session.inbound, LOGON_TIMEOUT, |logon| logon.accepted.is_some()) {
+        Ok(()) => status = logon.accepted == Some(true),
+        Err(RecvTimeoutError::Timeout) => {
+            error!("No response received from server for Logon request");
+            println!("No response received from server for Logon request");
         },
-        Err(e) => {
-            error!("Failed to read from stream: {e}");
-            panic!("Failed to read from stream: {e}"); // panic since nothing else can be done here
+        Err(RecvTimeoutError::Disconnected) => {
+            error!("Session closed before Logon completed");
         }
     }
 
-    let seqnum: Arc<Mutex<u32>> = Arc::new(Mutex::new(1)); // Sequence is LOGON = 1 means running only a single sceniario here
-    let seqnum_copy: Arc<Mutex<u32>> = Arc::clone(&seqnum);
-    increment_seqnum(seqnum_copy.clone());
-    Ok((status, seqnum))
-}
\ No newline at end of file
+    Ok((status, seqnum, session))
+}
+
+// waits for the response to Logon, accepted is set once Logon is confirmed (true) or refused (false)
+#[derive(Default)]
+struct LogonResponse {
+    accepted: Option<bool>,
+}
+
+impl FixApplication for LogonResponse {
+    fn on_logon(&mut self, msg: &RawMessage) {
+        info!("Received Logon response: {}", msg.to_display());
+        println!("Received Logon response: {}", msg.to_display());
+        self.accepted = Some(true);
+    }
+
+    fn on_reject(&mut self, msg: &RawMessage) {
+        info!("Received Reject response: {}", msg.to_display());
+        println!("Received Reject response: {}", msg.to_display());
+    }
+
+    fn on_logout(&mut self, msg: &RawMessage) {
+        error!("Received Logout in response to Logon: {}", msg.to_display());
+        println!("Received Logout in response to Logon: {}", msg.to_display());
+        self.accepted = Some(false);
+    }
+
+    fn on_message(&mut self, msg: &RawMessage) {
+        info!("Received different response type: {}", msg.to_display());
+        println!("Received different response type: {}", msg.to_display());
+    }
+
+    fn on_disconnect(&mut self, reason: &str) {
+        error!("Session disconnected before Logon completed: {reason}");
+        println!("Session disconnected before Logon completed: {reason}");
+        self.accepted = Some(false);
+    }
+}
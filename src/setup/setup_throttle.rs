@@ -0,0 +1,26 @@
+use client_rust_fix::session::{RateLimit, ThrottleConfig};
+use log::info;
+use std::{env::var, error::Error};
+
+pub(crate) fn exec() -> Result<(bool, ThrottleConfig), Box<dyn Error>> {
+    //
+    // outbound rate limits of the order-entry session, empty = not throttled
+    //  - PT_THROTTLE_NEW / PT_THROTTLE_CANCEL / PT_THROTTLE_AMEND="<per second>[:<burst>]", e.g. "10:20"
+    //  - PT_THROTTLE_CANCEL_PRIORITY=false - cancels wait behind queued orders, they are sent ahead by default
+    //
+    let limit = |name: &str| -> Result<Option<RateLimit>, Box<dyn Error>> {
+        match var(name).unwrap_or_default().trim() {
+            "" => Ok(None),
+            value => value.parse().map(Some).map_err(|error| format!("{name}: {error}").into()),
+        }
+    };
+    let throttle = ThrottleConfig {
+        new: limit("PT_THROTTLE_NEW")?,
+        cancel: limit("PT_THROTTLE_CANCEL")?,
+        amend: limit("PT_THROTTLE_AMEND")?,
+        cancel_priority: var("PT_THROTTLE_CANCEL_PRIORITY").map_or(true, |value| !value.eq_ignore_ascii_case("false")),
+    };
+    info!("Outbound throttle : {throttle:?}");
+    println!("Outbound throttle : {throttle:?}");
+    Ok((true, throttle))
+}
@@ -19,7 +19,10 @@ pub(crate) fn exec() ->  Result<bool , Box<dyn Error>> {
     assert!(price > 0.0);
     assert!(quantity > 0.0);
     assert!(side == Side::Buy || side == Side::Sell, "SIDE should be either Buy or Sell");
-    assert!(order_type == OrdType::Limit || order_type == OrdType::Market, "ORDERTYPE should be either Limit or Market");
+    assert!(
+        matches!(order_type, OrdType::Limit | OrdType::Market | OrdType::Stop | OrdType::StopLimit),
+        "ORDERTYPE should be either Limit, Market, Stop or StopLimit"
+    );
     assert_eq!(symbol, "SOL-USD");
     Ok(true)
 }
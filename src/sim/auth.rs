@@ -0,0 +1,96 @@
+// src/sim/auth.rs
+//
+// Validation of the JWT carried in Logon (35=A) RawData [554], mirroring checks done by power.trade
+//
+
+use crate::fix::RawMessage;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use jwtk::ecdsa::EcdsaPublicKey;
+use serde_json::{Map, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `validate_logon`
+///
+/// returns SenderCompID [49] (the API key) when Logon is accepted
+///
+/// # Errors
+///
+/// function will return error text (sent back in Logout Text [58]) if
+/// - SenderCompID [49] or RawData [554] is missing
+/// - token is not a JWT signed with ES256
+/// - claim `sub` does not match SenderCompID [49]
+/// - claim `exp` is in the past
+/// - `verify_key_pem` is set and token signature does not verify with it
+pub fn validate_logon(msg: &RawMessage, verify_key_pem: Option<&[u8]>) -> Result<String, String> {
+    let apikey: &str = msg.get(49).filter(|v| !v.is_empty()).ok_or("SenderCompID [49] is missing")?;
+    let token: &str = msg.get(554).filter(|v| !v.is_empty()).ok_or("JWT [554] is missing")?;
+
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err("JWT [554] must have 3 parts".to_string());
+    }
+    let header: Map<String, Value> = decode_part(parts[0]).map_err(|e| format!("JWT header invalid: {e}"))?;
+    let claims: Map<String, Value> = decode_part(parts[1]).map_err(|e| format!("JWT claims invalid: {e}"))?;
+
+    if header.get("alg").and_then(Value::as_str) != Some("ES256") {
+        return Err("JWT must be signed with ES256".to_string());
+    }
+    if claims.get("sub").and_then(Value::as_str) != Some(apikey) {
+        return Err("JWT 'sub' does not match SenderCompID [49]".to_string());
+    }
+    let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards!!").as_secs();
+    match claims.get("exp").and_then(Value::as_u64) {
+        Some(exp) if exp > now => {}
+        Some(_) => return Err("JWT has expired".to_string()),
+        None => return Err("JWT 'exp' is missing".to_string()),
+    }
+
+    if let Some(pem) = verify_key_pem {
+        let key: EcdsaPublicKey = EcdsaPublicKey::from_pem(pem).map_err(|e| format!("Simulator public key invalid: {e}"))?;
+        jwtk::verify::<Map<String, Value>>(token, &key).map_err(|e| format!("JWT signature invalid: {e}"))?;
+    }
+    Ok(apikey.to_string())
+}
+
+fn decode_part(part: &str) -> Result<Map<String, Value>, String> {
+    let bytes: Vec<u8> = URL_SAFE_NO_PAD.decode(part).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod sim_auth_tests {
+
+    use super::validate_logon;
+    use crate::fix::RawMessage;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+
+    fn token(sub: &str, exp: u64) -> String {
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"ES256","typ":"JWT"}"#);
+        let claims = URL_SAFE_NO_PAD.encode(format!(r#"{{"sub":"{sub}","exp":{exp}}}"#));
+        format!("{header}.{claims}.c2ln")
+    }
+
+    fn logon(apikey: &str, jwt: &str) -> RawMessage {
+        let mut msg = RawMessage::new("A");
+        msg.set(49, apikey).set(554, jwt);
+        msg
+    }
+
+    #[test]
+    fn test_logon_accepted() {
+        let msg = logon("key-1", &token("key-1", u64::MAX / 2));
+        assert_eq!(validate_logon(&msg, None), Ok("key-1".to_string()));
+    }
+
+    #[test]
+    fn test_logon_rejects_wrong_subject() {
+        let msg = logon("key-1", &token("key-2", u64::MAX / 2));
+        assert!(validate_logon(&msg, None).is_err());
+    }
+
+    #[test]
+    fn test_logon_rejects_expired_token() {
+        let msg = logon("key-1", &token("key-1", 1));
+        assert_eq!(validate_logon(&msg, None), Err("JWT has expired".to_string()));
+    }
+}
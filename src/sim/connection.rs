@@ -0,0 +1,287 @@
+// src/sim/connection.rs
+//
+// One simulated FIX session: Logon, heartbeats, order entry and injected faults
+//
+
+use super::{auth::validate_logon, exchange::SimReport, SimContext};
+use crate::fix::{utc_timestamp, FrameReader, RawMessage};
+use crate::instruments::{Instrument, SecurityType};
+use chrono::Utc;
+use log::{error, info};
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::SocketAddr,
+    sync::{
+        atomic::Ordering,
+        mpsc::{channel, Receiver},
+    },
+};
+
+struct Connection<'a, S> {
+    stream: S,
+    peer: SocketAddr,
+    ctx: &'a SimContext,
+    client_comp_id: Option<String>,
+    /// SenderCompID [49] of this side, the TargetCompID [56] the client logged on to
+    comp_id: String,
+    drop_copy: bool,
+    out_seqnum: u32,
+    /// reports routed from other sessions (e.g. fills on resting orders), set after Logon
+    routed: Option<Receiver<RawMessage>>,
+}
+
+pub(super) fn run<S: Read + Write>(stream: S, peer: SocketAddr, ctx: &SimContext) {
+    let comp_id: String = ctx.config.comp_id.clone();
+    let mut conn = Connection { stream, peer, ctx, client_comp_id: None, comp_id, drop_copy: false, out_seqnum: 1, routed: None };
+    conn.serve();
+    if conn.drop_copy {
+        ctx.drop_copies.lock().unwrap().retain(|(addr, _, _)| *addr != peer);
+    } else if let (Some(client_comp_id), Some(_)) = (&conn.client_comp_id, &conn.routed) {
+        ctx.sessions.lock().unwrap().remove(client_comp_id);
+    }
+}
+
+impl<S: Read + Write> Connection<'_, S> {
+    fn serve(&mut self) {
+        let peer: SocketAddr = self.peer;
+        let epoch: u64 = self.ctx.control.lock().unwrap().disconnect_epoch;
+        let mut frames = FrameReader::new();
+        let mut buffer = [0; 4096];
+
+        while self.ctx.running.load(Ordering::SeqCst) {
+            if self.ctx.control.lock().unwrap().disconnect_epoch != epoch {
+                info!("pt-sim dropping connection from {peer} on command");
+                return;
+            }
+            // Day / GoodTillDate orders are expired by whichever connection polls first
+            let expired: Vec<SimReport> = self.ctx.exchange.lock().unwrap().expire_orders(Utc::now());
+            if !expired.is_empty() {
+                self.ctx.route(expired);
+            }
+            let routed: Vec<RawMessage> = self.routed.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+            for msg in routed {
+                if !self.send(msg) {
+                    return;
+                }
+            }
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    info!("pt-sim connection from {peer} closed by client");
+                    return;
+                }
+                Ok(byte_count) => frames.push(&buffer[..byte_count]),
+                Err(ref error) if error.kind() == ErrorKind::WouldBlock || error.kind() == ErrorKind::TimedOut => continue,
+                Err(error) => {
+                    error!("pt-sim error reading from {peer}: {error}");
+                    return;
+                }
+            }
+            while let Some(frame) = frames.next_frame() {
+                match RawMessage::parse(&frame) {
+                    Ok(msg) => {
+                        if !self.handle(&msg) {
+                            return;
+                        }
+                    }
+                    Err(error) => error!("pt-sim dropping unparsable message from {peer}: {error}"),
+                }
+            }
+        }
+    }
+
+    /// `handle`
+    ///
+    /// processes one inbound message, returns false when the connection must be closed
+    fn handle(&mut self, msg: &RawMessage) -> bool {
+        info!("pt-sim {} received {}", self.peer, msg.to_display());
+
+        if self.client_comp_id.is_none() && msg.msg_type() != "A" {
+            return self.logout(msg.get(49), "Logon (35=A) required");
+        }
+        match msg.msg_type() {
+            "A" => self.logon(msg),
+            "0" => true,
+            "1" => {
+                let mut heartbeat = RawMessage::new("0");
+                heartbeat.set(112, msg.get(112).unwrap_or_default());
+                self.send(heartbeat)
+            }
+            "5" => {
+                let _ = self.logout(None, "Logout acknowledged");
+                false
+            }
+            "D" | "F" | "G" | "AB" => {
+                if self.drop_copy {
+                    return self.send(session_reject(msg, "Drop copy session does not accept orders"));
+                }
+                let reject_message = self.ctx.control.lock().unwrap().reject_messages.pop_front();
+                if let Some(text) = reject_message {
+                    return self.send(session_reject(msg, &text));
+                }
+                let owner: String = self.client_comp_id.clone().unwrap_or_default();
+                let reports: Vec<SimReport> = if msg.msg_type() == "D" || msg.msg_type() == "AB" {
+                    let reject_order = self.ctx.control.lock().unwrap().reject_orders.pop_front();
+                    let mut exchange = self.ctx.exchange.lock().unwrap();
+                    match reject_order {
+                        Some(text) => vec![exchange.reject_order(&owner, msg, &text)],
+                        None => exchange.new_order(&owner, msg),
+                    }
+                } else if msg.msg_type() == "F" {
+                    vec![self.ctx.exchange.lock().unwrap().cancel_order(&owner, msg)]
+                } else {
+                    self.ctx.exchange.lock().unwrap().replace_order(&owner, msg)
+                };
+                self.report(&owner, reports)
+            }
+            "q" => {
+                if self.drop_copy {
+                    return self.send(session_reject(msg, "Drop copy session does not accept orders"));
+                }
+                // cancel ExecutionReports are copied to drop copies like any other, the OrderMassCancelReport is not
+                let owner: String = self.client_comp_id.clone().unwrap_or_default();
+                let reports: Vec<SimReport> = self.ctx.exchange.lock().unwrap().mass_cancel(&owner, msg);
+                let (cancels, report): (Vec<SimReport>, Vec<SimReport>) = reports.into_iter().partition(|r| r.message.msg_type() == "8");
+                self.report(&owner, cancels) && report.into_iter().all(|report| self.send(report.message))
+            }
+            "H" | "AF" => {
+                // status reports go back to the requesting session only, they are not copied to drop copies
+                let owner: String = self.client_comp_id.clone().unwrap_or_default();
+                let reports: Vec<SimReport> = if msg.msg_type() == "H" {
+                    vec![self.ctx.exchange.lock().unwrap().order_status(&owner, msg)]
+                } else {
+                    self.ctx.exchange.lock().unwrap().mass_status(&owner, msg)
+                };
+                reports.into_iter().all(|report| self.send(report.message))
+            }
+            "x" => self.send(security_list(msg, &self.ctx.config.instruments)),
+            _ => self.send(session_reject(msg, "Unsupported MsgType")),
+        }
+    }
+
+    fn logon(&mut self, msg: &RawMessage) -> bool {
+        let verify_key_pem: Option<&[u8]> = self.ctx.config.verify_key_pem.as_deref();
+        match validate_logon(msg, verify_key_pem) {
+            Ok(apikey) => {
+                info!("pt-sim {} logon accepted for {apikey}", self.peer);
+                let (tx, rx) = channel::<RawMessage>();
+                if msg.get(56) == Some(self.ctx.config.drop_copy_comp_id.as_str()) {
+                    info!("pt-sim {} drop-copy session for {apikey}", self.peer);
+                    self.drop_copy = true;
+                    self.comp_id = self.ctx.config.drop_copy_comp_id.clone();
+                    self.ctx.drop_copies.lock().unwrap().push((self.peer, apikey.clone(), tx));
+                } else {
+                    self.ctx.sessions.lock().unwrap().insert(apikey.clone(), tx);
+                }
+                self.routed = Some(rx);
+                self.client_comp_id = Some(apikey);
+                self.out_seqnum = 1;
+                let mut response = RawMessage::new("A");
+                response
+                    .set(98, 0)
+                    .set(108, msg.get(108).unwrap_or("30"))
+                    .set(141, msg.get(141).unwrap_or("Y"));
+                self.send(response)
+            }
+            Err(reason) => {
+                error!("pt-sim {} logon rejected: {reason}", self.peer);
+                let _ = self.logout(msg.get(49), &reason);
+                false
+            }
+        }
+    }
+
+    fn logout(&mut self, client_comp_id: Option<&str>, text: &str) -> bool {
+        if self.client_comp_id.is_none() {
+            self.client_comp_id = client_comp_id.map(str::to_string);
+        }
+        let mut logout = RawMessage::new("5");
+        logout.set(58, text);
+        let _ = self.send(logout);
+        false
+    }
+
+    /// `report`
+    ///
+    /// sends reports owned by this session, routes the others (counterparty fills) to their sessions
+    fn report(&mut self, owner: &str, reports: Vec<SimReport>) -> bool {
+        let (own, others): (Vec<SimReport>, Vec<SimReport>) = reports.into_iter().partition(|r| r.owner == owner);
+        self.ctx.copy(&own);
+        self.ctx.route(others);
+        own.into_iter().all(|report| self.send(report.message))
+    }
+
+    /// `send`
+    ///
+    /// stamps header fields and writes message, returns false when write fails
+    fn send(&mut self, mut msg: RawMessage) -> bool {
+        let gap: u32 = std::mem::take(&mut self.ctx.control.lock().unwrap().gap);
+        if gap > 0 {
+            info!("pt-sim {} skipping {gap} outbound MsgSeqNum on command", self.peer);
+            self.out_seqnum += gap;
+        }
+        msg.set_header(34, self.out_seqnum)
+            .set_header(49, &self.comp_id)
+            .set_header(52, utc_timestamp())
+            .set_header(56, self.client_comp_id.as_deref().unwrap_or_default());
+        self.out_seqnum += 1;
+
+        let result = self.stream.write_all(msg.to_fix_string().as_bytes()).and_then(|()| self.stream.flush());
+        match result {
+            Ok(()) => {
+                info!("pt-sim {} sent {}", self.peer, msg.to_display());
+                true
+            }
+            Err(error) => {
+                error!("pt-sim error writing to {}: {error}", self.peer);
+                false
+            }
+        }
+    }
+}
+
+/// `session_reject`
+///
+/// Reject (35=3) referencing MsgSeqNum [45] and MsgType [372] of the rejected message
+fn session_reject(msg: &RawMessage, text: &str) -> RawMessage {
+    let mut reject = RawMessage::new("3");
+    reject
+        .set(45, msg.get(34).unwrap_or("0"))
+        .set(372, msg.msg_type())
+        .set(58, text);
+    reject
+}
+
+/// `security_list`
+///
+/// SecurityList (35=y) in a single message answering SecurityListRequest (35=x), all instruments for
+/// SecurityListRequestType [559] = '4', those of SecurityType [167] for '1'
+fn security_list(msg: &RawMessage, instruments: &[Instrument]) -> RawMessage {
+    let security_type: Option<SecurityType> = if msg.get(559) == Some("1") { msg.get(167).and_then(SecurityType::from_fix) } else { None };
+    let listed: Vec<&Instrument> = instruments.iter().filter(|i| security_type.is_none() || i.security_type == security_type).collect();
+    let mut list = RawMessage::new("y");
+    // SecurityRequestResult [560] - '0' valid request, '2' no instruments found
+    list.set(320, msg.get(320).unwrap_or_default())
+        .set(322, format!("SL-{}", msg.get(34).unwrap_or("0")))
+        .set(560, if listed.is_empty() { 2 } else { 0 })
+        .set(146, listed.len());
+    for instrument in listed {
+        list.push(55, &instrument.symbol);
+        if let Some(security_type) = instrument.security_type {
+            list.push(167, security_type.as_fix());
+        }
+        list.push(311, instrument.underlying());
+        if let Some(expiry) = instrument.expiry {
+            list.push(541, expiry.format("%Y%m%d"));
+        }
+        if let (Some(strike), Some(put_or_call)) = (instrument.strike, instrument.put_or_call) {
+            list.push(202, strike).push(201, put_or_call.as_fix());
+        }
+        list.push(969, instrument.tick_size)
+            .push(561, instrument.lot_size)
+            .push(562, instrument.min_qty)
+            .push(231, instrument.multiplier)
+            .push(326, 17);
+    }
+    list.set(893, 'Y');
+    list
+}
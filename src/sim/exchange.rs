@@ -0,0 +1,1121 @@
+// src/sim/exchange.rs
+//
+// Order handling for the simulator: acknowledges NewOrderSingle (35=D),
+// OrderCancelRequest (35=F) and OrderCancelReplaceRequest (35=G) the way power.trade
+// order entry does, then matches
+// orders in the `MatchingEngine` and reports fills with ExecType [150] = 'F'
+// OrderStatusRequest (35=H) and OrderMassStatusRequest (35=AF) are answered with ExecType [150] = 'I'
+// OrderMassCancelRequest (35=q) cancels resting orders and ends with an OrderMassCancelReport (35=r)
+// TimeInForce [59] - IOC remainder and unfilled FOK orders are cancelled, Day and GTD orders expire (150=C)
+// Stop / StopLimit orders wait outside the book until the last trade reaches StopPx [99], then are reported
+// with ExecType [150] = 'L' and matched as Market / Limit orders
+// ExecInst [18] post-only ('6') / reduce-only ('E') and SelfMatchPreventionInstruction [2964] are checked before
+// matching, rejections carry OrdRejReason [103] 100 / 101 / 102
+// NewOrderMultileg (35=AB) packages wait outside the book until every leg can be filled in full within the net
+// price, then all legs are taken from their books at once and reported per leg (MultiLegReportingType [442] = '2')
+//
+
+use super::matching::{BookOrder, BookSide, Fill, MatchingEngine};
+use crate::fix::{parse_utc_timestamp, utc_timestamp, Decimal, RawMessage};
+use crate::orders::{OrderLeg, MULTILEG_SYMBOL};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+
+/// owner of liquidity added with `SimCommand::Liquidity`, reports for it are not sent anywhere
+pub const HOUSE_OWNER: &str = "PT-SIM";
+
+/// `SimReport`
+///
+/// message produced by the exchange and the SenderCompID [49] of the session it belongs to
+#[derive(Debug, Clone)]
+pub struct SimReport {
+    pub owner: String,
+    pub message: RawMessage,
+}
+
+#[derive(Debug, Clone)]
+struct SimOrder {
+    cl_ord_id: String,
+    order_id: String,
+    symbol: String,
+    side: String,
+    ord_type: String,
+    /// SecurityType [167] sent with the order, used by mass cancel
+    security_type: Option<String>,
+    /// TimeInForce [59], ExpireTime [126] and ExpireDate [432] as sent
+    time_in_force: String,
+    expire_time: Option<String>,
+    expire_date: Option<String>,
+    /// Day and GoodTillDate orders expire at this time
+    expire_at: Option<DateTime<Utc>>,
+    /// StopPx [99] and TriggerPriceType [1107] as sent, the simulator always triggers on the last trade
+    stop_px: Option<f64>,
+    trigger_price_type: Option<String>,
+    triggered: bool,
+    /// ExecInst [18] and SelfMatchPreventionInstruction [2964] as sent
+    exec_inst: Option<String>,
+    self_trade_prevention: Option<String>,
+    /// legs of a multi-leg package (35=AB), Price [44] is then the net price of the package
+    legs: Vec<OrderLeg>,
+    quantity: f64,
+    price: Option<f64>,
+    cum_qty: f64,
+    avg_px: f64,
+    owner: String,
+    closed: bool,
+}
+
+/// `SimExchange`
+///
+/// orders known to the simulator keyed by OrderID [37], with an index on owner + ClOrdID [11]
+#[derive(Debug, Default)]
+pub struct SimExchange {
+    next_order_id: u64,
+    next_exec_id: u64,
+    engine: MatchingEngine,
+    orders: HashMap<String, SimOrder>,
+    cl_ord_ids: HashMap<(String, String), String>,
+    /// price of the last trade per symbol, triggers Stop / StopLimit orders
+    last_px: HashMap<String, f64>,
+    /// net filled quantity per owner and symbol, positive when long, checked by reduce-only orders
+    positions: HashMap<(String, String), f64>,
+}
+
+impl SimExchange {
+    pub fn new() -> Self {
+        SimExchange::default()
+    }
+
+    pub fn engine(&self) -> &MatchingEngine {
+        &self.engine
+    }
+
+    /// `new_order`
+    ///
+    /// ExecutionReports (35=8) for NewOrderSingle (35=D) and NewOrderMultileg (35=AB)
+    /// - 39=0 'New' when order is accepted, followed by fills (150=F) from matching
+    /// - NewOrderMultileg reports carry the NoLegs [555] group, a package needs at least two legs
+    /// - 39=8 'Rejected' when a required field is missing or invalid
+    /// - 39=4 'Cancelled' for the unfilled remainder of a Market order
+    /// - 39=8 with OrdRejReason [103] 100 / 101 / 102 when post-only, reduce-only or self-trade prevention stops the order,
+    ///   resting orders of owner are cancelled first when self-trade prevention cancels passive orders
+    pub fn new_order(&mut self, owner: &str, msg: &RawMessage) -> Vec<SimReport> {
+        let order = match self.parse_order(owner, msg) {
+            Ok(order) => order,
+            Err(reason) => return vec![self.reject_order(owner, msg, &reason)],
+        };
+        if self.cl_ord_ids.contains_key(&(owner.to_string(), order.cl_ord_id.clone())) {
+            return vec![self.reject_order_with_reason(owner, msg, 6, "Duplicate ClOrdID [11]")];
+        }
+        if let Err((ord_rej_reason, reason)) = self.check_exec_inst(&order) {
+            return vec![self.reject_order_with_reason(owner, msg, ord_rej_reason, &reason)];
+        }
+
+        // SelfMatchPreventionInstruction [2964] '1' cancel aggressive, '2' cancel passive, '3' cancel both
+        let mut reports: Vec<SimReport> = Vec::new();
+        let own_orders: Vec<String> = if order.self_trade_prevention.is_some() { self.own_crossing_orders(&order) } else { Vec::new() };
+        if !own_orders.is_empty() {
+            if matches!(order.self_trade_prevention.as_deref(), Some("2" | "3")) {
+                reports.extend(self.cancel_resting(&own_orders, "Cancelled by self-trade prevention"));
+            }
+            if matches!(order.self_trade_prevention.as_deref(), Some("1" | "3")) {
+                reports.push(self.reject_order_with_reason(owner, msg, 102, "Order would trade against an order of the same account"));
+                return reports;
+            }
+        }
+        reports.extend(self.submit(order));
+        reports
+    }
+
+    /// `add_liquidity`
+    ///
+    /// adds a house Limit order to the book, fills against client orders are reported to their owners
+    pub fn add_liquidity(&mut self, symbol: &str, side: BookSide, quantity: f64, price: f64) -> Vec<SimReport> {
+        self.next_order_id += 1;
+        let order = SimOrder {
+            cl_ord_id: format!("HOUSE-{}", self.next_order_id),
+            order_id: format!("SIM-{}", self.next_order_id),
+            symbol: symbol.to_string(),
+            side: if side == BookSide::Buy { "1" } else { "2" }.to_string(),
+            ord_type: "2".to_string(),
+            security_type: None,
+            time_in_force: "1".to_string(),
+            expire_time: None,
+            expire_date: None,
+            expire_at: None,
+            stop_px: None,
+            trigger_price_type: None,
+            triggered: false,
+            exec_inst: None,
+            self_trade_prevention: None,
+            legs: Vec::new(),
+            quantity,
+            price: Some(price),
+            cum_qty: 0.0,
+            avg_px: 0.0,
+            owner: HOUSE_OWNER.to_string(),
+            closed: false,
+        };
+        self.submit(order)
+    }
+
+    /// `reject_order`
+    ///
+    /// ExecutionReport (35=8) with ExecType [150] / OrdStatus [39] = '8' and Text [58] set to reason
+    pub fn reject_order(&mut self, owner: &str, msg: &RawMessage, reason: &str) -> SimReport {
+        self.reject_order_with_reason(owner, msg, 99, reason)
+    }
+
+    /// `reject_order_with_reason`
+    ///
+    /// as `reject_order` with OrdRejReason [103] set to `ord_rej_reason`
+    fn reject_order_with_reason(&mut self, owner: &str, msg: &RawMessage, ord_rej_reason: u32, reason: &str) -> SimReport {
+        let mut report = RawMessage::new("8");
+        report
+            .set(11, msg.get(11).unwrap_or_default())
+            .set(37, "NONE")
+            .set(17, self.next_exec_id())
+            .set(150, '8')
+            .set(39, '8')
+            .set(103, ord_rej_reason)
+            .set(55, msg.get(55).unwrap_or_default())
+            .set(54, msg.get(54).unwrap_or_default())
+            .set(38, msg.get(38).unwrap_or("0"))
+            .set(151, 0)
+            .set(14, 0)
+            .set(6, 0)
+            .set(58, reason)
+            .set(60, utc_timestamp());
+        SimReport { owner: owner.to_string(), message: report }
+    }
+
+    /// `cancel_order`
+    ///
+    /// ExecutionReport (35=8) with OrdStatus [39] = '4' when OrigClOrdID [41] is resting in the book,
+    /// otherwise OrderCancelReject (35=9) with CxlRejResponseTo [434] = '1'
+    pub fn cancel_order(&mut self, owner: &str, msg: &RawMessage) -> SimReport {
+        let orig_cl_ord_id: &str = msg.get(41).unwrap_or_default();
+        let order_id: Option<String> = self.cl_ord_ids.get(&(owner.to_string(), orig_cl_ord_id.to_string())).cloned();
+        let Some(order_id) = order_id else {
+            return self.cancel_reject(owner, msg, '1', "Unknown order");
+        };
+        let symbol: String = self.orders[&order_id].symbol.clone();
+        if !is_off_book(&self.orders[&order_id]) && self.engine.cancel(&symbol, &order_id).is_none() {
+            return self.cancel_reject(owner, msg, '1', "Too late to cancel");
+        }
+
+        let order = self.orders.get_mut(&order_id).expect("order exists");
+        order.closed = true;
+        let order = order.clone();
+        let mut report = self.execution_report(&order, '4', '4');
+        report.message.set(11, msg.get(11).unwrap_or_default()).set(41, orig_cl_ord_id);
+        report
+    }
+
+    /// `replace_order`
+    ///
+    /// OrderCancelReplaceRequest (35=G) for a resting Limit order
+    /// - ExecutionReport (35=8) with ExecType [150] = '5' 'Replaced', ClOrdID [11] of the request and
+    ///   OrigClOrdID [41], followed by fills if the new price crosses the book
+    /// - OrderCancelReject (35=9) with CxlRejResponseTo [434] = '2' when order is unknown, done or
+    ///   the new OrderQty [38] is not above CumQty [14]
+    ///
+    /// n.b. replaced order loses its time priority
+    pub fn replace_order(&mut self, owner: &str, msg: &RawMessage) -> Vec<SimReport> {
+        let orig_cl_ord_id: &str = msg.get(41).unwrap_or_default();
+        let order_id: Option<String> = self.cl_ord_ids.get(&(owner.to_string(), orig_cl_ord_id.to_string())).cloned();
+        let Some(order_id) = order_id else {
+            return vec![self.cancel_reject(owner, msg, '2', "Unknown order")];
+        };
+        let cl_ord_id: &str = msg.get(11).unwrap_or_default();
+        if cl_ord_id.is_empty() || self.cl_ord_ids.contains_key(&(owner.to_string(), cl_ord_id.to_string())) {
+            return vec![self.cancel_reject(owner, msg, '2', "Duplicate ClOrdID [11]")];
+        }
+        let order: &SimOrder = &self.orders[&order_id];
+        let quantity: f64 = msg.get(38).and_then(|v| v.parse().ok()).unwrap_or(order.quantity);
+        let price: Option<f64> = msg.get(44).and_then(|v| v.parse().ok()).or(order.price);
+        if order.ord_type != "2" || !matches!(price, Some(p) if p > 0.0) {
+            return vec![self.cancel_reject(owner, msg, '2', "Only Limit orders with Price [44] greater than zero can be replaced")];
+        }
+        if quantity <= order.cum_qty {
+            return vec![self.cancel_reject(owner, msg, '2', "OrderQty [38] must be greater than CumQty [14]")];
+        }
+        let symbol: String = order.symbol.clone();
+        if order.closed || self.engine.cancel(&symbol, &order_id).is_none() {
+            return vec![self.cancel_reject(owner, msg, '2', "Too late to replace")];
+        }
+
+        self.cl_ord_ids.insert((owner.to_string(), cl_ord_id.to_string()), order_id.clone());
+        let order = self.orders.get_mut(&order_id).expect("order exists");
+        order.cl_ord_id = cl_ord_id.to_string();
+        order.quantity = quantity;
+        order.price = price;
+        let order = order.clone();
+        let ord_status: char = if order.cum_qty > 0.0 { '1' } else { '0' };
+        let mut report = self.execution_report(&order, '5', ord_status);
+        report.message.set(41, orig_cl_ord_id);
+
+        let mut reports: Vec<SimReport> = vec![report];
+        reports.extend(self.match_order(&order_id));
+        reports.extend(self.trigger_stops(&symbol));
+        reports
+    }
+
+    /// `order_status`
+    ///
+    /// ExecutionReport (35=8) with ExecType [150] = 'I' for OrderStatusRequest (35=H),
+    /// order is looked up by ClOrdID [11] and then OrderID [37], unknown orders are reported with OrdStatus [39] = '8'
+    pub fn order_status(&mut self, owner: &str, msg: &RawMessage) -> SimReport {
+        let cl_ord_id: &str = msg.get(11).unwrap_or_default();
+        let order: Option<SimOrder> = self
+            .cl_ord_ids
+            .get(&(owner.to_string(), cl_ord_id.to_string()))
+            .map(String::as_str)
+            .or_else(|| msg.get(37))
+            .and_then(|order_id| self.orders.get(order_id))
+            .filter(|order| order.owner == owner)
+            .cloned();
+        let Some(order) = order else {
+            let mut report = self.reject_order(owner, msg, "Unknown order");
+            report.message.set(150, 'I');
+            return report;
+        };
+        let mut report = self.execution_report(&order, 'I', ord_status(&order));
+        if let Some(id) = msg.get(790) {
+            report.message.set(790, id);
+        }
+        report
+    }
+
+    /// `mass_status`
+    ///
+    /// ExecutionReports (35=8) with ExecType [150] = 'I' for OrderMassStatusRequest (35=AF), one per open order of owner
+    /// - MassStatusReqType [585] '1' orders for Symbol [55], any other type reports all orders
+    /// - every report carries MassStatusReqID [584] and TotNumReports [911], the last one LastRptRequested [912] = 'Y'
+    /// - no open orders is reported as a single ExecutionReport with OrderID [37] = 'NONE' and TotNumReports = 0
+    pub fn mass_status(&mut self, owner: &str, msg: &RawMessage) -> Vec<SimReport> {
+        let mass_status_req_id: &str = msg.get(584).unwrap_or_default();
+        let symbol: Option<&str> = match msg.get(585) {
+            Some("1") => msg.get(55),
+            _ => None,
+        };
+        let mut orders: Vec<SimOrder> = self
+            .orders
+            .values()
+            .filter(|order| order.owner == owner && !order.closed && symbol.map_or(true, |s| order.symbol == s))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| order.order_id.trim_start_matches("SIM-").parse::<u64>().unwrap_or_default());
+
+        let total: usize = orders.len();
+        let mut reports: Vec<SimReport> = orders.iter().map(|order| self.execution_report(order, 'I', ord_status(order))).collect();
+        if reports.is_empty() {
+            let mut report = RawMessage::new("8");
+            report
+                .set(37, "NONE")
+                .set(17, self.next_exec_id())
+                .set(150, 'I')
+                .set(39, '8')
+                .set(58, "No open orders")
+                .set(60, utc_timestamp());
+            reports.push(SimReport { owner: owner.to_string(), message: report });
+        }
+        for report in &mut reports {
+            report.message.set(584, mass_status_req_id).set(911, total);
+        }
+        if let Some(last) = reports.last_mut() {
+            last.message.set(912, 'Y');
+        }
+        reports
+    }
+
+    /// `mass_cancel`
+    ///
+    /// OrderMassCancelRequest (35=q) for the resting orders of owner
+    /// - ExecutionReport (35=8) with OrdStatus [39] = '4' for every cancelled order
+    /// - followed by OrderMassCancelReport (35=r) with TotalAffectedOrders [533] and the NoAffectedOrders [534] group
+    /// - MassCancelResponse [531] = '0' with MassCancelRejectReason [532] when MassCancelRequestType [530] is not
+    ///   '1' (Symbol [55]), '5' (SecurityType [167]) or '7' (all orders)
+    pub fn mass_cancel(&mut self, owner: &str, msg: &RawMessage) -> Vec<SimReport> {
+        let request_type: &str = msg.get(530).unwrap_or_default();
+        // Symbol [55] / SecurityType [167] orders must match
+        let (symbol, security_type): (Option<&str>, Option<&str>) = match (request_type, msg.get(55), msg.get(167)) {
+            ("7", _, _) => (None, None),
+            ("1", Some(symbol), _) => (Some(symbol), None),
+            ("1", None, _) => return vec![self.mass_cancel_reject(owner, msg, '1', "Symbol [55] is missing")],
+            ("5", _, Some(security_type)) => (None, Some(security_type)),
+            ("5", _, None) => return vec![self.mass_cancel_reject(owner, msg, '5', "SecurityType [167] is missing")],
+            _ => return vec![self.mass_cancel_reject(owner, msg, '0', "MassCancelRequestType [530] is not supported")],
+        };
+        let matches = |order: &SimOrder| {
+            symbol.map_or(true, |symbol| order.symbol == symbol)
+                && security_type.map_or(true, |security_type| order.security_type.as_deref() == Some(security_type))
+        };
+        let mut order_ids: Vec<String> = self
+            .orders
+            .values()
+            .filter(|order| order.owner == owner && !order.closed && matches(order))
+            .map(|order| order.order_id.clone())
+            .collect();
+        order_ids.sort_by_key(|order_id| order_id.trim_start_matches("SIM-").parse::<u64>().unwrap_or_default());
+
+        let mut reports: Vec<SimReport> = Vec::new();
+        let mut affected: Vec<(String, String)> = Vec::new();
+        for order_id in order_ids {
+            let symbol: String = self.orders[&order_id].symbol.clone();
+            if !is_off_book(&self.orders[&order_id]) && self.engine.cancel(&symbol, &order_id).is_none() {
+                continue;
+            }
+            let order = self.orders.get_mut(&order_id).expect("order exists");
+            order.closed = true;
+            let order = order.clone();
+            let mut cancelled = self.execution_report(&order, '4', '4');
+            cancelled.message.set(58, "Cancelled by mass cancel");
+            reports.push(cancelled);
+            affected.push((order.cl_ord_id, order.order_id));
+        }
+
+        let mut report = self.mass_cancel_report(msg);
+        report.set(531, request_type).set(533, affected.len()).set(534, affected.len());
+        for (cl_ord_id, order_id) in affected {
+            report.push(41, cl_ord_id).push(535, order_id);
+        }
+        reports.push(SimReport { owner: owner.to_string(), message: report });
+        reports
+    }
+
+    /// `mass_cancel_reject`
+    ///
+    /// OrderMassCancelReport (35=r) with MassCancelResponse [531] = '0' and MassCancelRejectReason [532]
+    fn mass_cancel_reject(&mut self, owner: &str, msg: &RawMessage, reject_reason: char, text: &str) -> SimReport {
+        let mut report = self.mass_cancel_report(msg);
+        report.set(531, '0').set(532, reject_reason).set(58, text);
+        SimReport { owner: owner.to_string(), message: report }
+    }
+
+    fn mass_cancel_report(&mut self, msg: &RawMessage) -> RawMessage {
+        self.next_order_id += 1;
+        let mut report = RawMessage::new("r");
+        report
+            .set(11, msg.get(11).unwrap_or_default())
+            .set(37, format!("SIM-{}", self.next_order_id))
+            .set(530, msg.get(530).unwrap_or_default());
+        report
+    }
+
+    /// `check_exec_inst`
+    ///
+    /// OrdRejReason [103] and Text [58] when ExecInst [18] stops the order
+    /// - '6' post-only, rejected with 100 when the order would take liquidity
+    /// - 'E' reduce-only, rejected with 101 when the order is larger than the position it reduces
+    fn check_exec_inst(&self, order: &SimOrder) -> Result<(), (u32, String)> {
+        for exec_inst in order.exec_inst.as_deref().unwrap_or_default().split(' ').filter(|v| !v.is_empty()) {
+            match exec_inst {
+                "6" if matches!(order.ord_type.as_str(), "1" | "3") => {
+                    return Err((11, "Post-only is not allowed for Market or Stop order".to_string()))
+                }
+                "6" => {
+                    let side: BookSide = BookSide::from_fix(&order.side).expect("side validated");
+                    if self.engine.crossing_quantity(&order.symbol, side, order.price) > 0.0 {
+                        return Err((100, "Post-only order would take liquidity".to_string()));
+                    }
+                }
+                "E" => {
+                    let position: f64 = self.positions.get(&(order.owner.clone(), order.symbol.clone())).copied().unwrap_or_default();
+                    let reducible: f64 = if order.side == "1" { -position } else { position };
+                    if order.quantity > reducible + 1e-9 {
+                        return Err((101, format!("Reduce-only order would increase the position of {position}")));
+                    }
+                }
+                other => return Err((11, format!("ExecInst [18] '{other}' is not supported"))),
+            }
+        }
+        Ok(())
+    }
+
+    /// `own_crossing_orders`
+    ///
+    /// resting orders of the same owner the order would trade against
+    fn own_crossing_orders(&self, order: &SimOrder) -> Vec<String> {
+        let side: BookSide = BookSide::from_fix(&order.side).expect("side validated");
+        let limit: Option<f64> = if matches!(order.ord_type.as_str(), "1" | "3") { None } else { order.price };
+        self.engine
+            .crossing_orders(&order.symbol, side, limit)
+            .into_iter()
+            .filter(|order_id| self.orders.get(order_id).map_or(false, |resting| resting.owner == order.owner))
+            .collect()
+    }
+
+    /// `cancel_resting`
+    ///
+    /// ExecutionReports (35=8) with OrdStatus [39] = '4' and Text [58] for resting orders removed from the book
+    fn cancel_resting(&mut self, order_ids: &[String], text: &str) -> Vec<SimReport> {
+        let mut reports: Vec<SimReport> = Vec::new();
+        for order_id in order_ids {
+            let symbol: String = self.orders[order_id].symbol.clone();
+            if self.engine.cancel(&symbol, order_id).is_none() {
+                continue;
+            }
+            let order = self.orders.get_mut(order_id).expect("order exists");
+            order.closed = true;
+            let order = order.clone();
+            let mut report = self.execution_report(&order, '4', '4');
+            report.message.set(58, text);
+            reports.push(report);
+        }
+        reports
+    }
+
+    fn submit(&mut self, order: SimOrder) -> Vec<SimReport> {
+        let order_id: String = order.order_id.clone();
+        let symbol: String = order.symbol.clone();
+        let mut reports: Vec<SimReport> = vec![self.execution_report(&order, '0', '0')];
+        self.cl_ord_ids.insert((order.owner.clone(), order.cl_ord_id.clone()), order.order_id.clone());
+        self.orders.insert(order.order_id.clone(), order);
+        reports.extend(self.match_order(&order_id));
+        reports.extend(self.trigger_stops(&symbol));
+        reports.extend(self.match_packages());
+        reports
+    }
+
+    /// `match_packages`
+    ///
+    /// tries every waiting multi-leg package again, in arrival order, after the books changed
+    fn match_packages(&mut self) -> Vec<SimReport> {
+        let mut order_ids: Vec<String> =
+            self.orders.values().filter(|order| !order.legs.is_empty() && !order.closed).map(|order| order.order_id.clone()).collect();
+        order_ids.sort_by_key(|order_id| order_id.trim_start_matches("SIM-").parse::<u64>().unwrap_or_default());
+        order_ids.iter().flat_map(|order_id| self.match_package(order_id)).collect()
+    }
+
+    /// `match_package`
+    ///
+    /// fills the open quantity of a multi-leg package when every leg can be filled in full and the net price -
+    /// sum of LegRatioQty [623] x leg price, Buy legs added and Sell legs subtracted - is within the package Price [44].
+    /// Leg ExecutionReports (442=2) are followed by the package ExecutionReport (442=3), an IOC / FOK / Market package
+    /// that cannot be filled is cancelled, any other keeps waiting
+    fn match_package(&mut self, order_id: &str) -> Vec<SimReport> {
+        let order: &SimOrder = &self.orders[order_id];
+        let open_qty: f64 = order.quantity - order.cum_qty;
+        // a Sell package trades every leg the other way
+        let leg_side = |leg: &OrderLeg| match (leg.side == "1", order.side == "1") {
+            (true, true) | (false, false) => BookSide::Buy,
+            _ => BookSide::Sell,
+        };
+        let mut net_px: Option<f64> = Some(0.0);
+        for leg in &order.legs {
+            let ratio_qty: f64 = leg.ratio_qty.to_f64();
+            let leg_px: Option<f64> = self.engine.sweep_price(&leg.symbol, leg_side(leg), open_qty * ratio_qty);
+            net_px = net_px.zip(leg_px).map(|(net_px, leg_px)| net_px + (if leg.side == "1" { leg_px } else { -leg_px }) * ratio_qty);
+        }
+        let fillable: bool = match (net_px, order.ord_type.as_str(), order.price) {
+            (None, _, _) => false,
+            (Some(_), "1", _) => true,
+            (Some(net_px), _, Some(limit)) if order.side == "1" => net_px <= limit + 1e-9,
+            (Some(net_px), _, Some(limit)) => net_px >= limit - 1e-9,
+            _ => false,
+        };
+        if !fillable {
+            if order.ord_type != "1" && !matches!(order.time_in_force.as_str(), "3" | "4") {
+                return Vec::new();
+            }
+            let order = self.orders.get_mut(order_id).expect("order exists");
+            order.closed = true;
+            let order = order.clone();
+            let mut report = self.execution_report(&order, '4', '4');
+            report.message.set(58, "Multi-leg order cannot be filled in full");
+            return vec![report];
+        }
+
+        let legs: Vec<(OrderLeg, BookSide)> = order.legs.iter().map(|leg| (leg.clone(), leg_side(leg))).collect();
+        let mut reports: Vec<SimReport> = Vec::new();
+        let mut leg_reports: Vec<SimReport> = Vec::new();
+        for (index, (leg, side)) in legs.into_iter().enumerate() {
+            let book_order = BookOrder {
+                order_id: format!("{order_id}-L{}", index + 1),
+                side,
+                price: None,
+                quantity: open_qty * leg.ratio_qty.to_f64(),
+            };
+            let result = self.engine.submit(&leg.symbol, &book_order);
+            let mut leg_fill = OrderLeg::new(&leg.symbol, &leg.side, leg.ratio_qty);
+            for fill in &result.fills {
+                reports.push(self.apply_fill(&fill.maker_order_id, fill));
+                self.last_px.insert(leg_fill.symbol.clone(), fill.price);
+                leg_fill.apply_fill(to_decimal(fill.quantity), to_decimal(fill.price)).expect("book fill fits a Decimal");
+            }
+            let package = self.orders.get_mut(order_id).expect("order exists");
+            package.legs[index].apply_fill(leg_fill.cum_qty, leg_fill.avg_px).expect("book fill fits a Decimal");
+            let package = SimOrder { legs: Vec::new(), ..package.clone() };
+            let mut report = self.execution_report(&package, 'F', '2');
+            report
+                .message
+                .set(442, '2')
+                .set(600, &leg_fill.symbol)
+                .set(624, &leg_fill.side)
+                .set(623, leg_fill.ratio_qty)
+                .set(32, leg_fill.cum_qty)
+                .set(31, leg_fill.avg_px);
+            leg_reports.push(report);
+        }
+
+        let net_px: f64 = net_px.expect("package is fillable");
+        let order = self.orders.get_mut(order_id).expect("order exists");
+        order.avg_px = (order.avg_px * order.cum_qty + net_px * open_qty) / order.quantity;
+        order.cum_qty = order.quantity;
+        order.closed = true;
+        let order = order.clone();
+        let mut report = self.execution_report(&order, 'F', '2');
+        report.message.set(32, open_qty).set(31, net_px);
+        for leg_report in &mut leg_reports {
+            leg_report.message.set(14, order.cum_qty).set(151, 0).set(6, order.avg_px);
+        }
+        reports.extend(leg_reports);
+        reports.push(report);
+        reports
+    }
+
+    /// `trigger_stops`
+    ///
+    /// ExecutionReports (35=8) with ExecType [150] = 'L' for Stop / StopLimit orders on symbol whose StopPx [99]
+    /// was reached by the last trade - buy stops at or above, sell stops at or below - followed by their fills,
+    /// repeated while the triggered orders trade and trigger more stops
+    fn trigger_stops(&mut self, symbol: &str) -> Vec<SimReport> {
+        let mut reports: Vec<SimReport> = Vec::new();
+        while let Some(last_px) = self.last_px.get(symbol).copied() {
+            let mut order_ids: Vec<String> = self
+                .orders
+                .values()
+                .filter(|order| order.symbol == symbol && is_waiting_stop(order))
+                .filter(|order| match (order.side.as_str(), order.stop_px) {
+                    ("1", Some(stop_px)) => last_px >= stop_px,
+                    ("2", Some(stop_px)) => last_px <= stop_px,
+                    _ => false,
+                })
+                .map(|order| order.order_id.clone())
+                .collect();
+            if order_ids.is_empty() {
+                break;
+            }
+            order_ids.sort_by_key(|order_id| order_id.trim_start_matches("SIM-").parse::<u64>().unwrap_or_default());
+            for order_id in order_ids {
+                let order = self.orders.get_mut(&order_id).expect("order exists");
+                order.triggered = true;
+                let order = order.clone();
+                let mut report = self.execution_report(&order, 'L', '0');
+                report.message.set(58, format!("Stop triggered at {last_px}"));
+                reports.push(report);
+                reports.extend(self.match_order(&order_id));
+            }
+        }
+        reports
+    }
+
+    /// `expire_orders`
+    ///
+    /// ExecutionReports (35=8) with ExecType [150] / OrdStatus [39] = 'C' for Day and GoodTillDate orders expired at `now`
+    pub fn expire_orders(&mut self, now: DateTime<Utc>) -> Vec<SimReport> {
+        let mut order_ids: Vec<String> = self
+            .orders
+            .values()
+            .filter(|order| !order.closed && order.expire_at.map_or(false, |at| at <= now))
+            .map(|order| order.order_id.clone())
+            .collect();
+        order_ids.sort();
+        let mut reports: Vec<SimReport> = Vec::new();
+        for order_id in order_ids {
+            let symbol: String = self.orders[&order_id].symbol.clone();
+            self.engine.cancel(&symbol, &order_id);
+            let order = self.orders.get_mut(&order_id).expect("order exists");
+            order.closed = true;
+            let order = order.clone();
+            let mut report = self.execution_report(&order, 'C', 'C');
+            report.message.set(58, "Order expired");
+            reports.push(report);
+        }
+        reports
+    }
+
+    /// `match_order`
+    ///
+    /// submits the open quantity of order to the book, reports fills and cancels a Market or IOC remainder,
+    /// a FOK order is cancelled without matching unless it can be filled in full
+    fn match_order(&mut self, order_id: &str) -> Vec<SimReport> {
+        let order: &SimOrder = &self.orders[order_id];
+        if !order.legs.is_empty() {
+            return self.match_package(order_id);
+        }
+        if is_waiting_stop(order) {
+            return Vec::new();
+        }
+        let side: BookSide = BookSide::from_fix(&order.side).expect("side validated");
+        let limit: Option<f64> = if matches!(order.ord_type.as_str(), "1" | "3") { None } else { order.price };
+        if order.time_in_force == "4" && self.engine.crossing_quantity(&order.symbol, side, limit) + 1e-9 < order.quantity - order.cum_qty {
+            let order = self.orders.get_mut(order_id).expect("order exists");
+            order.closed = true;
+            let order = order.clone();
+            let mut report = self.execution_report(&order, '4', '4');
+            report.message.set(58, "FillOrKill order cannot be filled in full");
+            return vec![report];
+        }
+        let immediate: bool = order.time_in_force == "3";
+        let book_order = BookOrder { order_id: order.order_id.clone(), side, price: limit, quantity: order.quantity - order.cum_qty };
+        let symbol: String = order.symbol.clone();
+        let order_id: String = order.order_id.clone();
+        let mut reports: Vec<SimReport> = Vec::new();
+
+        let result = self.engine.submit(&symbol, &book_order);
+        for fill in &result.fills {
+            reports.push(self.apply_fill(&fill.maker_order_id, fill));
+            reports.push(self.apply_fill(&fill.taker_order_id, fill));
+            self.last_px.insert(symbol.clone(), fill.price);
+        }
+        let cancel_remainder: Option<&str> = match (result.rested, result.leaves_qty > 0.0) {
+            (false, true) => Some("Market order remainder cancelled"),
+            (true, _) if immediate => {
+                self.engine.cancel(&symbol, &order_id);
+                Some("ImmediateOrCancel remainder cancelled")
+            }
+            _ => None,
+        };
+        if let Some(text) = cancel_remainder {
+            let order = self.orders.get_mut(&order_id).expect("order exists");
+            order.closed = true;
+            let order = order.clone();
+            let mut report = self.execution_report(&order, '4', '4');
+            report.message.set(58, text);
+            reports.push(report);
+        }
+        reports
+    }
+
+    fn apply_fill(&mut self, order_id: &str, fill: &Fill) -> SimReport {
+        let order = self.orders.get_mut(order_id).expect("order exists");
+        order.avg_px = (order.avg_px * order.cum_qty + fill.price * fill.quantity) / (order.cum_qty + fill.quantity);
+        order.cum_qty += fill.quantity;
+        let signed_qty: f64 = if order.side == "1" { fill.quantity } else { -fill.quantity };
+        *self.positions.entry((order.owner.clone(), order.symbol.clone())).or_default() += signed_qty;
+        let is_filled: bool = order.quantity - order.cum_qty <= 1e-9;
+        order.closed = is_filled;
+        let order = order.clone();
+
+        let mut report = self.execution_report(&order, 'F', if is_filled { '2' } else { '1' });
+        report.message.set(32, fill.quantity).set(31, fill.price);
+        report
+    }
+
+    /// `cancel_reject`
+    ///
+    /// OrderCancelReject (35=9), `response_to` is CxlRejResponseTo [434] - '1' cancel, '2' cancel/replace
+    /// OrdStatus [39] is the current status of the order, '8' when unknown
+    fn cancel_reject(&mut self, owner: &str, msg: &RawMessage, response_to: char, reason: &str) -> SimReport {
+        let order: Option<&SimOrder> = self
+            .cl_ord_ids
+            .get(&(owner.to_string(), msg.get(41).unwrap_or_default().to_string()))
+            .and_then(|order_id| self.orders.get(order_id));
+        let ord_status: char = order.map_or('8', ord_status);
+        let mut reject = RawMessage::new("9");
+        reject
+            .set(11, msg.get(11).unwrap_or_default())
+            .set(41, msg.get(41).unwrap_or_default())
+            .set(37, order.map_or("NONE", |o| o.order_id.as_str()))
+            .set(39, ord_status)
+            .set(434, response_to)
+            .set(102, 1)
+            .set(58, reason);
+        SimReport { owner: owner.to_string(), message: reject }
+    }
+
+    fn parse_order(&mut self, owner: &str, msg: &RawMessage) -> Result<SimOrder, String> {
+        let cl_ord_id: &str = msg.get(11).filter(|v| !v.is_empty()).ok_or("ClOrdID [11] is missing")?;
+        let symbol: &str = match msg.msg_type() {
+            "AB" => msg.get(55).unwrap_or(MULTILEG_SYMBOL),
+            _ => msg.get(55).filter(|v| !v.is_empty()).ok_or("Symbol [55] is missing")?,
+        };
+        let side: &str = msg.get(54).filter(|v| BookSide::from_fix(v).is_some()).ok_or("Side [54] must be 1 or 2")?;
+        let ord_type: &str = msg.get(40).unwrap_or("2");
+        let quantity: f64 =
+            msg.get(38).and_then(|v| v.parse().ok()).filter(|q: &f64| *q > 0.0).ok_or("OrderQty [38] must be greater than zero")?;
+        let price: Option<f64> = msg.get(44).and_then(|v| v.parse().ok());
+        let stop_px: Option<f64> = msg.get(99).and_then(|v| v.parse().ok());
+        let legs: Vec<OrderLeg> = match msg.msg_type() {
+            "AB" => OrderLeg::from_raw(msg).map_err(|error| error.to_string())?,
+            _ => Vec::new(),
+        };
+        match ord_type {
+            _ if msg.msg_type() == "AB" && legs.len() < 2 => return Err("NoLegs [555] must hold at least 2 legs".to_string()),
+            "1" if msg.msg_type() == "AB" && price.is_some() => return Err("Price [44] is not allowed for Market order".to_string()),
+            "2" if msg.msg_type() == "AB" && price.is_some() => {}
+            "3" | "4" if msg.msg_type() == "AB" => return Err("Stop orders are not supported for multi-leg order".to_string()),
+            "1" | "2" if stop_px.is_some() => return Err("StopPx [99] is only allowed for Stop and StopLimit orders".to_string()),
+            "3" | "4" if !matches!(stop_px, Some(p) if p > 0.0) => {
+                return Err("StopPx [99] must be greater than zero for Stop order".to_string())
+            }
+            "3" if price.is_some() => return Err("Price [44] is only allowed for StopLimit order".to_string()),
+            "1" | "3" => {}
+            "2" | "4" if matches!(price, Some(p) if p > 0.0) => {}
+            "2" | "4" => return Err("Price [44] must be greater than zero for Limit order".to_string()),
+            _ => return Err(format!("OrdType [40] '{ord_type}' is not supported")),
+        }
+        if !matches!(msg.get(2964), None | Some("1" | "2" | "3")) {
+            return Err("SelfMatchPreventionInstruction [2964] must be 1, 2 or 3".to_string());
+        }
+        let time_in_force: &str = msg.get(59).unwrap_or("1");
+        let expire_at: Option<DateTime<Utc>> = expire_at(time_in_force, msg.get(126), msg.get(432))?;
+        self.next_order_id += 1;
+        Ok(SimOrder {
+            cl_ord_id: cl_ord_id.to_string(),
+            order_id: format!("SIM-{}", self.next_order_id),
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            ord_type: ord_type.to_string(),
+            security_type: msg.get(167).map(str::to_string),
+            time_in_force: time_in_force.to_string(),
+            expire_time: msg.get(126).map(str::to_string),
+            expire_date: msg.get(432).map(str::to_string),
+            expire_at,
+            stop_px,
+            trigger_price_type: msg.get(1107).map(str::to_string),
+            triggered: false,
+            exec_inst: msg.get(18).map(str::to_string),
+            self_trade_prevention: msg.get(2964).map(str::to_string),
+            legs,
+            quantity,
+            price,
+            cum_qty: 0.0,
+            avg_px: 0.0,
+            owner: owner.to_string(),
+            closed: false,
+        })
+    }
+
+    fn execution_report(&mut self, order: &SimOrder, exec_type: char, ord_status: char) -> SimReport {
+        let leaves_qty: f64 = if order.closed { 0.0 } else { order.quantity - order.cum_qty };
+        let mut report = RawMessage::new("8");
+        report
+            .set(11, &order.cl_ord_id)
+            .set(37, &order.order_id)
+            .set(17, self.next_exec_id())
+            .set(150, exec_type)
+            .set(39, ord_status)
+            .set(55, &order.symbol)
+            .set(54, &order.side)
+            .set(40, &order.ord_type)
+            .set(38, order.quantity)
+            .set(151, leaves_qty)
+            .set(14, order.cum_qty)
+            .set(6, order.avg_px);
+        if let Some(price) = order.price {
+            report.set(44, price);
+        }
+        report.set(59, &order.time_in_force);
+        if let Some(expire_time) = &order.expire_time {
+            report.set(126, expire_time);
+        }
+        if let Some(expire_date) = &order.expire_date {
+            report.set(432, expire_date);
+        }
+        if let Some(stop_px) = order.stop_px {
+            report.set(99, stop_px);
+        }
+        if let Some(trigger_price_type) = &order.trigger_price_type {
+            report.set(1107, trigger_price_type);
+        }
+        if let Some(exec_inst) = &order.exec_inst {
+            report.set(18, exec_inst);
+        }
+        if let Some(self_trade_prevention) = &order.self_trade_prevention {
+            report.set(2964, self_trade_prevention);
+        }
+        if !order.legs.is_empty() {
+            report.set(442, '3').set(555, order.legs.len());
+            for leg in &order.legs {
+                report.push(600, &leg.symbol).push(624, &leg.side).push(623, leg.ratio_qty);
+            }
+        }
+        report.set(60, utc_timestamp());
+        SimReport { owner: order.owner.clone(), message: report }
+    }
+
+    fn next_exec_id(&mut self) -> String {
+        self.next_exec_id += 1;
+        format!("SIM-EXEC-{}", self.next_exec_id)
+    }
+}
+
+/// `expire_at`
+///
+/// expiry of a new order from TimeInForce [59] - Day orders expire at the end of the UTC day,
+/// GoodTillDate orders at ExpireTime [126] or at the end of ExpireDate [432]
+fn expire_at(time_in_force: &str, expire_time: Option<&str>, expire_date: Option<&str>) -> Result<Option<DateTime<Utc>>, String> {
+    let end_of_day = |date: NaiveDate| (date + Duration::days(1)).and_hms_opt(0, 0, 0).map(|time| time.and_utc());
+    let expire_at: Option<DateTime<Utc>> = match (time_in_force, expire_time, expire_date) {
+        ("1" | "3" | "4", None, None) => return Ok(None),
+        ("0", None, None) => end_of_day(Utc::now().date_naive()),
+        ("6", Some(time), _) => Some(parse_utc_timestamp(time).ok_or_else(|| format!("ExpireTime [126] '{time}' is not valid"))?),
+        ("6", None, Some(date)) => {
+            end_of_day(NaiveDate::parse_from_str(date, "%Y%m%d").map_err(|_| format!("ExpireDate [432] '{date}' is not valid"))?)
+        }
+        ("6", None, None) => return Err("GoodTillDate order requires ExpireTime [126] or ExpireDate [432]".to_string()),
+        ("0" | "1" | "3" | "4", _, _) => return Err("ExpireTime [126] / ExpireDate [432] is only allowed for GoodTillDate".to_string()),
+        _ => return Err(format!("TimeInForce [59] '{time_in_force}' is not supported")),
+    };
+    match expire_at {
+        Some(at) if at <= Utc::now() => Err("Order expiry is in the past".to_string()),
+        _ => Ok(expire_at),
+    }
+}
+
+// simulator quantity or price as a `Decimal`, f64 noise past 9 digits after the decimal point dropped
+fn to_decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value, 9).unwrap_or_default().normalize()
+}
+
+/// waiting Stop / StopLimit order or multi-leg package, i.e. not in the book
+fn is_off_book(order: &SimOrder) -> bool {
+    is_waiting_stop(order) || !order.legs.is_empty() && !order.closed
+}
+
+/// Stop / StopLimit order that is not triggered yet, i.e. not in the book
+fn is_waiting_stop(order: &SimOrder) -> bool {
+    order.stop_px.is_some() && !order.triggered && !order.closed
+}
+
+/// current OrdStatus [39] of a known order
+fn ord_status(order: &SimOrder) -> char {
+    match order {
+        order if order.closed && order.cum_qty + 1e-9 >= order.quantity => '2',
+        order if order.closed => '4',
+        order if order.cum_qty > 0.0 => '1',
+        _ => '0',
+    }
+}
+
+#[cfg(test)]
+mod sim_exchange_tests {
+
+    use super::{SimExchange, SimReport, HOUSE_OWNER};
+    use crate::fix::RawMessage;
+    use crate::sim::matching::BookSide;
+
+    fn order(cl_ord_id: &str, side: &str, ord_type: &str, quantity: f64, price: f64) -> RawMessage {
+        let mut msg = RawMessage::new("D");
+        msg.set(11, cl_ord_id).set(38, quantity).set(40, ord_type).set(44, price).set(54, side).set(55, "SOL-USD");
+        msg
+    }
+
+    #[test]
+    fn test_partial_then_full_fill_reports() {
+        let mut exchange = SimExchange::new();
+        exchange.add_liquidity("SOL-USD", BookSide::Buy, 1.0, 388.0);
+
+        let reports = exchange.new_order("client", &order("S1", "2", "2", 3.0, 388.0));
+        let statuses: Vec<(&str, Option<&str>, Option<&str>)> =
+            reports.iter().map(|r| (r.owner.as_str(), r.message.get(150), r.message.get(39))).collect();
+        assert_eq!(statuses, vec![("client", Some("0"), Some("0")), (HOUSE_OWNER, Some("F"), Some("2")), ("client", Some("F"), Some("1"))]);
+        assert_eq!(reports[2].message.get(151), Some("2"));
+
+        let reports = exchange.add_liquidity("SOL-USD", BookSide::Buy, 2.0, 390.0);
+        let client = reports.iter().find(|r| r.owner == "client").unwrap();
+        assert_eq!(client.message.get(39), Some("2"));
+        assert_eq!(client.message.get(31), Some("388"));
+        assert_eq!(client.message.get(14), Some("3"));
+    }
+
+    #[test]
+    fn test_market_remainder_is_cancelled() {
+        let mut exchange = SimExchange::new();
+        exchange.add_liquidity("SOL-USD", BookSide::Sell, 1.0, 390.0);
+        let reports = exchange.new_order("client", &order("B1", "1", "1", 2.0, 0.0));
+        let last = &reports.last().unwrap().message;
+        assert_eq!(last.get(39), Some("4"));
+        assert_eq!(last.get(14), Some("1"));
+    }
+
+    #[test]
+    fn test_cancel_filled_order_is_rejected() {
+        let mut exchange = SimExchange::new();
+        exchange.add_liquidity("SOL-USD", BookSide::Buy, 1.0, 388.0);
+        exchange.new_order("client", &order("S1", "2", "2", 1.0, 388.0));
+
+        let mut cancel = RawMessage::new("F");
+        cancel.set(11, "C1").set(41, "S1");
+        let report = exchange.cancel_order("client", &cancel);
+        assert_eq!(report.message.msg_type(), "9");
+    }
+
+    #[test]
+    fn test_replace_keeps_order_id_and_rejects_done_orders() {
+        let mut exchange = SimExchange::new();
+        exchange.new_order("client", &order("S1", "2", "2", 2.0, 392.0));
+
+        let mut replace = order("S2", "2", "2", 3.0, 391.0);
+        replace.set(41, "S1");
+        let reports = exchange.replace_order("client", &replace);
+        assert_eq!(reports.len(), 1);
+        let replaced = &reports[0].message;
+        assert_eq!(
+            (replaced.get(150), replaced.get(39), replaced.get(11), replaced.get(41)),
+            (Some("5"), Some("0"), Some("S2"), Some("S1"))
+        );
+        assert_eq!((replaced.get(37), replaced.get(38), replaced.get(44)), (Some("SIM-1"), Some("3"), Some("391")));
+        assert_eq!(exchange.engine().book("SOL-USD").unwrap().best_ask(), Some(391.0));
+
+        // replacing the old ClOrdID again is rejected with CxlRejResponseTo = '2'
+        let mut stale = order("S3", "2", "2", 3.0, 390.0);
+        stale.set(41, "S9");
+        let reject = &exchange.replace_order("client", &stale)[0].message;
+        assert_eq!((reject.msg_type(), reject.get(434)), ("9", Some("2")));
+    }
+
+    #[test]
+    fn test_mass_status_reports_open_orders() {
+        let mut exchange = SimExchange::new();
+        exchange.new_order("client", &order("S1", "2", "2", 2.0, 392.0));
+        exchange.new_order("client", &order("S2", "2", "2", 1.0, 393.0));
+        exchange.new_order("other", &order("S3", "2", "2", 1.0, 394.0));
+
+        let mut request = RawMessage::new("AF");
+        request.set(584, "M1").set(585, 7);
+        let reports = exchange.mass_status("client", &request);
+        let ids: Vec<(Option<&str>, Option<&str>, Option<&str>)> =
+            reports.iter().map(|r| (r.message.get(11), r.message.get(911), r.message.get(912))).collect();
+        assert_eq!(ids, vec![(Some("S1"), Some("2"), None), (Some("S2"), Some("2"), Some("Y"))]);
+        assert!(reports.iter().all(|r| r.message.get(150) == Some("I") && r.message.get(584) == Some("M1")));
+
+        let reports = exchange.mass_status("nobody", &request);
+        assert_eq!(reports.len(), 1);
+        assert_eq!((reports[0].message.get(37), reports[0].message.get(911)), (Some("NONE"), Some("0")));
+    }
+
+    #[test]
+    fn test_mass_cancel_by_symbol() {
+        let mut exchange = SimExchange::new();
+        exchange.new_order("client", &order("S1", "2", "2", 2.0, 392.0));
+        let mut other_symbol = order("S2", "2", "2", 1.0, 60000.0);
+        other_symbol.set(55, "BTC-USD");
+        exchange.new_order("client", &other_symbol);
+
+        let mut request = RawMessage::new("q");
+        request.set(11, "MC1").set(530, 1).set(55, "SOL-USD");
+        let reports = exchange.mass_cancel("client", &request);
+        let types: Vec<(&str, Option<&str>)> = reports.iter().map(|r| (r.message.msg_type(), r.message.get(11))).collect();
+        assert_eq!(types, vec![("8", Some("S1")), ("r", Some("MC1"))]);
+        let report = &reports[1].message;
+        assert_eq!((report.get(531), report.get(533), report.get(41), report.get(535)), (Some("1"), Some("1"), Some("S1"), Some("SIM-1")));
+        assert_eq!(exchange.engine().book("SOL-USD").unwrap().best_ask(), None);
+        assert_eq!(exchange.engine().book("BTC-USD").unwrap().best_ask(), Some(60000.0));
+
+        request.set(530, 3);
+        let reject = &exchange.mass_cancel("client", &request)[0].message;
+        assert_eq!((reject.get(531), reject.get(532)), (Some("0"), Some("0")));
+    }
+
+    #[test]
+    fn test_time_in_force_ioc_fok_and_expiry() {
+        let mut exchange = SimExchange::new();
+        exchange.add_liquidity("SOL-USD", BookSide::Buy, 1.0, 388.0);
+
+        // FOK for more than is available is cancelled without a fill
+        let mut fok = order("S1", "2", "2", 2.0, 388.0);
+        fok.set(59, 4);
+        let reports = exchange.new_order("client", &fok);
+        let last = &reports.last().unwrap().message;
+        assert_eq!((last.get(39), last.get(14)), (Some("4"), Some("0")));
+        assert_eq!(exchange.engine().book("SOL-USD").unwrap().best_bid(), Some(388.0));
+
+        // IOC fills what is available and cancels the remainder
+        let mut ioc = order("S2", "2", "2", 2.0, 388.0);
+        ioc.set(59, 3);
+        let reports = exchange.new_order("client", &ioc);
+        let last = &reports.last().unwrap().message;
+        assert_eq!((last.get(39), last.get(14), last.get(59)), (Some("4"), Some("1"), Some("3")));
+        assert_eq!(exchange.engine().book("SOL-USD").unwrap().best_ask(), None);
+
+        let mut gtd = order("S3", "2", "2", 1.0, 392.0);
+        gtd.set(59, 6).set(126, (chrono::Utc::now() + chrono::Duration::seconds(5)).format("%Y%m%d-%H:%M:%S").to_string());
+        exchange.new_order("client", &gtd);
+        assert!(exchange.expire_orders(chrono::Utc::now()).is_empty());
+        let expired = exchange.expire_orders(chrono::Utc::now() + chrono::Duration::seconds(10));
+        assert_eq!(
+            (expired[0].message.get(150), expired[0].message.get(39), expired[0].message.get(11)),
+            (Some("C"), Some("C"), Some("S3"))
+        );
+
+        let mut missing_expiry = order("S4", "2", "2", 1.0, 392.0);
+        missing_expiry.set(59, 6);
+        assert_eq!(exchange.new_order("client", &missing_expiry)[0].message.get(39), Some("8"));
+    }
+
+    #[test]
+    fn test_stop_limit_triggered_by_last_trade() {
+        let mut exchange = SimExchange::new();
+        let mut stop = order("S1", "2", "4", 1.0, 385.0);
+        stop.set(99, 386);
+        let reports = exchange.new_order("client", &stop);
+        assert_eq!((reports.len(), reports[0].message.get(39), reports[0].message.get(99)), (1, Some("0"), Some("386")));
+        assert_eq!(exchange.engine().book("SOL-USD").and_then(|book| book.best_ask()), None);
+
+        // house trade at 386 triggers the sell stop, which then rests at its limit price
+        exchange.add_liquidity("SOL-USD", BookSide::Buy, 1.0, 386.0);
+        let reports = exchange.add_liquidity("SOL-USD", BookSide::Sell, 1.0, 386.0);
+        let triggered = reports.iter().find(|report| report.owner == "client").unwrap();
+        assert_eq!((triggered.message.get(150), triggered.message.get(39)), (Some("L"), Some("0")));
+        assert_eq!(exchange.engine().book("SOL-USD").unwrap().best_ask(), Some(385.0));
+
+        let mut invalid = order("S2", "2", "3", 1.0, 385.0);
+        invalid.set(99, 386);
+        assert_eq!(exchange.new_order("client", &invalid)[0].message.get(39), Some("8"));
+    }
+
+    #[test]
+    fn test_post_only_reduce_only_and_self_trade_prevention() {
+        let mut exchange = SimExchange::new();
+        exchange.add_liquidity("SOL-USD", BookSide::Buy, 1.0, 388.0);
+
+        let mut post_only = order("S1", "2", "2", 1.0, 388.0);
+        post_only.set(18, "6");
+        assert_eq!(exchange.new_order("client", &post_only)[0].message.get(103), Some("100"));
+
+        // no position yet, a reduce-only sell would open a short
+        let mut reduce_only = order("S2", "2", "2", 1.0, 388.0);
+        reduce_only.set(18, "E");
+        assert_eq!(exchange.new_order("client", &reduce_only)[0].message.get(103), Some("101"));
+
+        exchange.new_order("client", &order("S3", "1", "2", 1.0, 390.0));
+        let mut self_trade = order("S4", "2", "2", 1.0, 389.0);
+        self_trade.set(2964, 2);
+        let reports = exchange.new_order("client", &self_trade);
+        assert_eq!((reports[0].message.get(11), reports[0].message.get(39)), (Some("S3"), Some("4")));
+        assert_eq!(reports[1].message.get(39), Some("0"));
+
+        let mut self_trade = order("S5", "1", "2", 1.0, 389.0);
+        self_trade.set(2964, 1);
+        assert_eq!(exchange.new_order("client", &self_trade)[0].message.get(103), Some("102"));
+    }
+
+    #[test]
+    fn test_multileg_package_fills_when_every_leg_can() {
+        let mut exchange = SimExchange::new();
+        exchange.add_liquidity("SOL-USD", BookSide::Sell, 2.0, 390.0);
+
+        // buy 1 SOL-USD, sell 0.01 BTC-USD at a net price of -200 or better
+        let mut package = RawMessage::new("AB");
+        package.set(11, "P1").set(38, 1).set(40, 2).set(44, -200).set(54, 1).set(555, 2);
+        package.push(600, "SOL-USD").push(624, 1).push(623, 1).push(600, "BTC-USD").push(624, 2).push(623, 0.01);
+        let reports = exchange.new_order("client", &package);
+        assert_eq!((reports.len(), reports[0].message.get(39), reports[0].message.get(442)), (1, Some("0"), Some("3")));
+
+        let reports: Vec<SimReport> =
+            exchange.add_liquidity("BTC-USD", BookSide::Buy, 1.0, 60000.0).into_iter().filter(|r| r.owner == "client").collect();
+        let legs: Vec<(Option<&str>, Option<&str>, Option<&str>)> = reports
+            .iter()
+            .filter(|r| r.message.get(442) == Some("2"))
+            .map(|r| (r.message.get(600), r.message.get(32), r.message.get(31)))
+            .collect();
+        assert_eq!(legs, vec![(Some("SOL-USD"), Some("1"), Some("390")), (Some("BTC-USD"), Some("0.01"), Some("60000"))]);
+        let package = &reports.last().unwrap().message;
+        assert_eq!((package.get(442), package.get(39), package.get(6)), (Some("3"), Some("2"), Some("-210")));
+        assert_eq!(exchange.engine().book("SOL-USD").unwrap().best_ask(), Some(390.0));
+    }
+}
@@ -0,0 +1,320 @@
+// src/sim/matching.rs
+//
+// Limit order book per symbol with price-time priority, used by the simulator for paper trading
+//
+
+use std::collections::HashMap;
+
+// quantities below this are treated as fully filled
+const QTY_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Buy,
+    Sell,
+}
+
+impl BookSide {
+    /// `from_fix`
+    ///
+    /// maps Side [54] value, '1' = Buy and '2' = Sell
+    pub fn from_fix(side: &str) -> Option<BookSide> {
+        match side {
+            "1" => Some(BookSide::Buy),
+            "2" => Some(BookSide::Sell),
+            _ => None,
+        }
+    }
+}
+
+/// `BookOrder`
+///
+/// order submitted to the book, `price` is None for Market orders
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookOrder {
+    pub order_id: String,
+    pub side: BookSide,
+    pub price: Option<f64>,
+    pub quantity: f64,
+}
+
+/// `Fill`
+///
+/// one match between an incoming (taker) order and a resting (maker) order, at the maker price
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub maker_order_id: String,
+    pub taker_order_id: String,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// `MatchResult`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchResult {
+    pub fills: Vec<Fill>,
+    /// quantity left after matching
+    pub leaves_qty: f64,
+    /// true when the remaining quantity was added to the book
+    pub rested: bool,
+}
+
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    order_id: String,
+    price: f64,
+    leaves_qty: f64,
+}
+
+/// `OrderBook`
+///
+/// bids sorted best (highest) price first, asks best (lowest) price first, then by arrival
+#[derive(Debug, Default)]
+pub struct OrderBook {
+    bids: Vec<RestingOrder>,
+    asks: Vec<RestingOrder>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        OrderBook::default()
+    }
+
+    /// `submit`
+    ///
+    /// matches order against the opposite side, then rests any Limit remainder
+    /// n.b. Market remainder is not rested, caller decides how to report it
+    pub fn submit(&mut self, order: &BookOrder) -> MatchResult {
+        let mut leaves_qty: f64 = order.quantity;
+        let mut fills: Vec<Fill> = Vec::new();
+        let opposite: &mut Vec<RestingOrder> = match order.side {
+            BookSide::Buy => &mut self.asks,
+            BookSide::Sell => &mut self.bids,
+        };
+
+        while leaves_qty > QTY_EPSILON {
+            let Some(best) = opposite.first_mut() else { break };
+            let crosses: bool = match (order.side, order.price) {
+                (_, None) => true,
+                (BookSide::Buy, Some(limit)) => best.price <= limit,
+                (BookSide::Sell, Some(limit)) => best.price >= limit,
+            };
+            if !crosses {
+                break;
+            }
+            let quantity: f64 = leaves_qty.min(best.leaves_qty);
+            fills.push(Fill {
+                maker_order_id: best.order_id.clone(),
+                taker_order_id: order.order_id.clone(),
+                price: best.price,
+                quantity,
+            });
+            leaves_qty -= quantity;
+            best.leaves_qty -= quantity;
+            if best.leaves_qty <= QTY_EPSILON {
+                opposite.remove(0);
+            }
+        }
+        if leaves_qty <= QTY_EPSILON {
+            leaves_qty = 0.0;
+        }
+
+        let rested: bool = match order.price {
+            Some(price) if leaves_qty > 0.0 => {
+                self.rest(order.side, &order.order_id, price, leaves_qty);
+                true
+            }
+            _ => false,
+        };
+        MatchResult { fills, leaves_qty, rested }
+    }
+
+    /// `cancel`
+    ///
+    /// removes resting order, returns its remaining quantity
+    pub fn cancel(&mut self, order_id: &str) -> Option<f64> {
+        for side in [&mut self.bids, &mut self.asks] {
+            if let Some(index) = side.iter().position(|o| o.order_id == order_id) {
+                return Some(side.remove(index).leaves_qty);
+            }
+        }
+        None
+    }
+
+    /// `crossing_quantity`
+    ///
+    /// quantity resting on the opposite side at `price` or better, all of it for a Market order (price None)
+    pub fn crossing_quantity(&self, side: BookSide, price: Option<f64>) -> f64 {
+        let opposite: &Vec<RestingOrder> = match side {
+            BookSide::Buy => &self.asks,
+            BookSide::Sell => &self.bids,
+        };
+        opposite
+            .iter()
+            .take_while(|o| match (side, price) {
+                (_, None) => true,
+                (BookSide::Buy, Some(limit)) => o.price <= limit,
+                (BookSide::Sell, Some(limit)) => o.price >= limit,
+            })
+            .map(|o| o.leaves_qty)
+            .sum()
+    }
+
+    /// `crossing_orders`
+    ///
+    /// OrderIDs resting on the opposite side at `price` or better, in matching order
+    pub fn crossing_orders(&self, side: BookSide, price: Option<f64>) -> Vec<String> {
+        let opposite: &Vec<RestingOrder> = match side {
+            BookSide::Buy => &self.asks,
+            BookSide::Sell => &self.bids,
+        };
+        opposite
+            .iter()
+            .take_while(|o| match (side, price) {
+                (_, None) => true,
+                (BookSide::Buy, Some(limit)) => o.price <= limit,
+                (BookSide::Sell, Some(limit)) => o.price >= limit,
+            })
+            .map(|o| o.order_id.clone())
+            .collect()
+    }
+
+    /// `sweep_price`
+    ///
+    /// average price of taking `quantity` from the opposite side, None when not enough is resting
+    pub fn sweep_price(&self, side: BookSide, quantity: f64) -> Option<f64> {
+        let opposite: &Vec<RestingOrder> = match side {
+            BookSide::Buy => &self.asks,
+            BookSide::Sell => &self.bids,
+        };
+        let mut remaining: f64 = quantity;
+        let mut notional: f64 = 0.0;
+        for resting in opposite {
+            if remaining <= QTY_EPSILON {
+                break;
+            }
+            let taken: f64 = remaining.min(resting.leaves_qty);
+            notional += taken * resting.price;
+            remaining -= taken;
+        }
+        (remaining <= QTY_EPSILON && quantity > 0.0).then(|| notional / quantity)
+    }
+
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.first().map(|o| o.price)
+    }
+
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.first().map(|o| o.price)
+    }
+
+    fn rest(&mut self, side: BookSide, order_id: &str, price: f64, leaves_qty: f64) {
+        let resting = RestingOrder { order_id: order_id.to_string(), price, leaves_qty };
+        let orders: &mut Vec<RestingOrder> = match side {
+            BookSide::Buy => &mut self.bids,
+            BookSide::Sell => &mut self.asks,
+        };
+        // insert after every order with a better or equal price (time priority within a level)
+        let better = |other: f64| if side == BookSide::Buy { price > other } else { price < other };
+        let index: usize = orders.iter().position(|o| better(o.price)).unwrap_or(orders.len());
+        orders.insert(index, resting);
+    }
+}
+
+/// `MatchingEngine`
+///
+/// one `OrderBook` per Symbol [55]
+#[derive(Debug, Default)]
+pub struct MatchingEngine {
+    books: HashMap<String, OrderBook>,
+}
+
+impl MatchingEngine {
+    pub fn new() -> Self {
+        MatchingEngine::default()
+    }
+
+    pub fn submit(&mut self, symbol: &str, order: &BookOrder) -> MatchResult {
+        self.books.entry(symbol.to_string()).or_default().submit(order)
+    }
+
+    pub fn cancel(&mut self, symbol: &str, order_id: &str) -> Option<f64> {
+        self.books.get_mut(symbol).and_then(|book| book.cancel(order_id))
+    }
+
+    pub fn crossing_quantity(&self, symbol: &str, side: BookSide, price: Option<f64>) -> f64 {
+        self.books.get(symbol).map_or(0.0, |book| book.crossing_quantity(side, price))
+    }
+
+    pub fn crossing_orders(&self, symbol: &str, side: BookSide, price: Option<f64>) -> Vec<String> {
+        self.books.get(symbol).map_or(Vec::new(), |book| book.crossing_orders(side, price))
+    }
+
+    pub fn sweep_price(&self, symbol: &str, side: BookSide, quantity: f64) -> Option<f64> {
+        self.books.get(symbol).and_then(|book| book.sweep_price(side, quantity))
+    }
+
+    pub fn book(&self, symbol: &str) -> Option<&OrderBook> {
+        self.books.get(symbol)
+    }
+}
+
+#[cfg(test)]
+mod matching_tests {
+
+    use super::{BookOrder, BookSide, OrderBook};
+
+    fn order(order_id: &str, side: BookSide, price: Option<f64>, quantity: f64) -> BookOrder {
+        BookOrder { order_id: order_id.to_string(), side, price, quantity }
+    }
+
+    #[test]
+    fn test_limit_orders_rest_without_cross() {
+        let mut book = OrderBook::new();
+        assert!(book.submit(&order("B1", BookSide::Buy, Some(99.0), 1.0)).rested);
+        assert!(book.submit(&order("S1", BookSide::Sell, Some(101.0), 1.0)).rested);
+        assert_eq!(book.best_bid(), Some(99.0));
+        assert_eq!(book.best_ask(), Some(101.0));
+    }
+
+    #[test]
+    fn test_price_then_time_priority() {
+        let mut book = OrderBook::new();
+        book.submit(&order("S1", BookSide::Sell, Some(101.0), 1.0));
+        book.submit(&order("S2", BookSide::Sell, Some(100.0), 1.0));
+        book.submit(&order("S3", BookSide::Sell, Some(100.0), 1.0));
+
+        let result = book.submit(&order("B1", BookSide::Buy, Some(101.0), 2.5));
+        let makers: Vec<&str> = result.fills.iter().map(|f| f.maker_order_id.as_str()).collect();
+        assert_eq!(makers, vec!["S2", "S3", "S1"]);
+        assert_eq!(result.fills[2].quantity, 0.5);
+        assert_eq!(result.leaves_qty, 0.0);
+        assert!(!result.rested);
+        assert_eq!(book.best_ask(), Some(101.0));
+    }
+
+    #[test]
+    fn test_partial_fill_rests_remainder_at_limit() {
+        let mut book = OrderBook::new();
+        book.submit(&order("S1", BookSide::Sell, Some(100.0), 1.0));
+        let result = book.submit(&order("B1", BookSide::Buy, Some(100.0), 3.0));
+        assert_eq!(result.fills.len(), 1);
+        assert_eq!(result.fills[0].price, 100.0);
+        assert_eq!(result.leaves_qty, 2.0);
+        assert!(result.rested);
+        assert_eq!(book.best_bid(), Some(100.0));
+        assert_eq!(book.cancel("B1"), Some(2.0));
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn test_market_order_does_not_rest() {
+        let mut book = OrderBook::new();
+        book.submit(&order("B1", BookSide::Buy, Some(99.0), 1.0));
+        let result = book.submit(&order("S1", BookSide::Sell, None, 2.0));
+        assert_eq!(result.fills.len(), 1);
+        assert_eq!(result.leaves_qty, 1.0);
+        assert!(!result.rested);
+        assert_eq!(book.best_ask(), None);
+    }
+}
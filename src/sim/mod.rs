@@ -0,0 +1,391 @@
+// src/sim/mod.rs
+//
+// Local FIX 4.4 acceptor behaving like power.trade order entry, used for integration tests
+//  - plain TCP or TLS (self-signed certificate loaded from PEM files)
+//  - validates the JWT sent in Logon RawData [554]
+//  - answers NewOrderSingle (35=D) with 39=0, OrderCancelRequest (35=F) with 39=4 and
+//    OrderCancelReplaceRequest (35=G) with 150=5
+//  - matches orders in a limit order book per symbol for paper trading
+//  - answers SecurityListRequest (35=x) with the instruments in `SimConfig::instruments`
+//  - copies ExecutionReports to drop-copy sessions (Logon with TargetCompID [56] = "PT-DC")
+//  - rejects, sequence gaps and disconnects can be injected with `SimCommand`
+//
+
+pub mod auth;
+mod connection;
+pub mod exchange;
+pub mod matching;
+
+use crate::fix::{Decimal, RawMessage};
+use crate::instruments::{Instrument, PutOrCall, SecurityType, Symbol};
+use chrono::{Datelike, Duration as Days, NaiveDate, Utc};
+use exchange::{SimExchange, SimReport};
+use log::{error, info};
+use matching::BookSide;
+use native_tls::{Identity, TlsAcceptor};
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    fmt,
+    io::ErrorKind,
+    net::{SocketAddr, TcpListener, TcpStream},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// `SimTls`
+///
+/// PEM encoded certificate and PKCS#8 private key presented by the simulator
+/// e.g. generate a self-signed pair with
+/// `openssl req -x509 -newkey ec -pkeyopt ec_paramgen_curve:prime256v1 -nodes -days 365 -subj "/CN=localhost" -keyout pt-sim-key.pem -out pt-sim-cert.pem`
+#[derive(Debug, Clone)]
+pub struct SimTls {
+    pub cert_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+}
+
+/// `SimConfig`
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    /// address to listen on, use port 0 to pick a free port
+    pub bind_addr: String,
+    /// SenderCompID [49] used by the simulator, power.trade order entry uses "PT-OE"
+    pub comp_id: String,
+    /// SenderCompID [49] used for drop-copy sessions, clients logging on to it receive copies of their ExecutionReports
+    pub drop_copy_comp_id: String,
+    /// serve TLS when set, plain TCP otherwise
+    pub tls: Option<SimTls>,
+    /// PEM public key used to verify the Logon JWT signature, claims only are checked when None
+    pub verify_key_pem: Option<Vec<u8>>,
+    pub poll_interval: Duration,
+    /// reference data sent in SecurityList (35=y), spot, perpetual and SOL options by default
+    pub instruments: Vec<Instrument>,
+}
+
+impl SimConfig {
+    pub fn new(bind_addr: &str) -> Self {
+        SimConfig {
+            bind_addr: bind_addr.to_string(),
+            comp_id: "PT-OE".to_string(),
+            drop_copy_comp_id: "PT-DC".to_string(),
+            tls: None,
+            verify_key_pem: None,
+            poll_interval: Duration::from_millis(50),
+            instruments: default_instruments(Utc::now().date_naive()),
+        }
+    }
+}
+
+/// `default_instruments`
+///
+/// spot and perpetual instruments, SOL 150 calls and puts expiring on the next Friday and four weeks later
+pub fn default_instruments(today: NaiveDate) -> Vec<Instrument> {
+    let derivative = |symbol: &str, tick_size: Decimal, lot_size: Decimal, security_type: SecurityType| {
+        let mut instrument = Instrument::new(symbol, tick_size, lot_size, lot_size);
+        instrument.security_type = Some(security_type);
+        instrument
+    };
+    let mut instruments: Vec<Instrument> = vec![
+        derivative("SOL-USD", Decimal::new(1, 2), Decimal::new(1, 2), SecurityType::Spot),
+        derivative("BTC-USD", Decimal::new(1, 2), Decimal::new(1, 4), SecurityType::Spot),
+        derivative("ETH-USD", Decimal::new(1, 2), Decimal::new(1, 3), SecurityType::Spot),
+        derivative("BTC-USD-PERPETUAL", Decimal::new(5, 1), Decimal::new(1, 4), SecurityType::Perpetual),
+    ];
+    let friday: NaiveDate = today + Days::days((11 - i64::from(today.weekday().num_days_from_monday())) % 7);
+    for expiry in [friday, friday + Days::weeks(4)] {
+        for put_or_call in [PutOrCall::Call, PutOrCall::Put] {
+            let symbol: Symbol = Symbol::option("SOL", expiry, 150.0, put_or_call).expect("valid option symbol");
+            let mut option = derivative(&symbol.to_string(), Decimal::new(1, 2), Decimal::new(1, 2), SecurityType::Option);
+            option.underlying = Some("SOL".to_string());
+            option.expiry = Some(expiry);
+            option.strike = Some(150.0);
+            option.put_or_call = Some(put_or_call);
+            instruments.push(option);
+        }
+    }
+    instruments
+}
+
+/// `SimCommand`
+///
+/// faults injected into running sessions, or house liquidity added to the order book
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimCommand {
+    /// next NewOrderSingle (35=D) is answered by an ExecutionReport with OrdStatus [39] = '8'
+    RejectNextOrder(String),
+    /// next application message is answered by a session level Reject (35=3)
+    RejectNextMessage(String),
+    /// skip this many outbound MsgSeqNum [34] values on the next message sent
+    Gap(u32),
+    /// close every connected session without Logout
+    Disconnect,
+    /// rest a house Limit order in the book of `symbol`, fills are reported to client sessions
+    Liquidity { symbol: String, side: BookSide, quantity: f64, price: f64 },
+}
+
+impl FromStr for SimCommand {
+    type Err = String;
+
+    /// parses console commands
+    /// - `reject-order [text]`
+    /// - `reject [text]`
+    /// - `gap <count>`
+    /// - `disconnect`
+    /// - `liquidity <symbol> <buy|sell> <quantity>@<price>`
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (command, args) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let text = |default: &str| if args.trim().is_empty() { default.to_string() } else { args.trim().to_string() };
+        match command {
+            "reject-order" => Ok(SimCommand::RejectNextOrder(text("Rejected by simulator"))),
+            "reject" => Ok(SimCommand::RejectNextMessage(text("Rejected by simulator"))),
+            "gap" => args.trim().parse().map(SimCommand::Gap).map_err(|_| format!("invalid gap count '{args}'")),
+            "disconnect" => Ok(SimCommand::Disconnect),
+            "liquidity" => {
+                parse_liquidity(args).ok_or_else(|| format!("invalid liquidity '{args}', expected <symbol> <buy|sell> <quantity>@<price>"))
+            }
+            _ => Err(format!("unknown command '{line}'")),
+        }
+    }
+}
+
+impl fmt::Display for SimCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimCommand::RejectNextOrder(text) => write!(f, "reject-order {text}"),
+            SimCommand::RejectNextMessage(text) => write!(f, "reject {text}"),
+            SimCommand::Gap(count) => write!(f, "gap {count}"),
+            SimCommand::Disconnect => write!(f, "disconnect"),
+            SimCommand::Liquidity { symbol, side, quantity, price } => {
+                let side: &str = if *side == BookSide::Buy { "buy" } else { "sell" };
+                write!(f, "liquidity {symbol} {side} {quantity}@{price}")
+            }
+        }
+    }
+}
+
+fn parse_liquidity(args: &str) -> Option<SimCommand> {
+    let mut parts = args.split_whitespace();
+    let symbol: String = parts.next()?.to_string();
+    let side: BookSide = match parts.next()? {
+        "buy" => BookSide::Buy,
+        "sell" => BookSide::Sell,
+        _ => return None,
+    };
+    let (quantity, price) = parts.next()?.split_once('@')?;
+    let quantity: f64 = quantity.parse().ok().filter(|q: &f64| *q > 0.0)?;
+    let price: f64 = price.parse().ok().filter(|p: &f64| *p > 0.0)?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(SimCommand::Liquidity { symbol, side, quantity, price })
+}
+
+#[derive(Debug, Default)]
+struct SimControl {
+    reject_orders: VecDeque<String>,
+    reject_messages: VecDeque<String>,
+    gap: u32,
+    disconnect_epoch: u64,
+}
+
+struct SimContext {
+    config: SimConfig,
+    control: Mutex<SimControl>,
+    exchange: Mutex<SimExchange>,
+    /// logged on sessions by SenderCompID [49], used to deliver fills to the other side of a match
+    sessions: Mutex<HashMap<String, Sender<RawMessage>>>,
+    /// logged on drop-copy sessions, peer address and SenderCompID [49]
+    drop_copies: Mutex<Vec<(SocketAddr, String, Sender<RawMessage>)>>,
+    running: AtomicBool,
+}
+
+impl SimContext {
+    /// `route`
+    ///
+    /// delivers reports to the sessions owning them, reports for house orders are dropped
+    fn route(&self, reports: Vec<SimReport>) {
+        self.copy(&reports);
+        let sessions = self.sessions.lock().unwrap();
+        for report in reports {
+            match sessions.get(&report.owner) {
+                Some(session) => {
+                    let _ = session.send(report.message);
+                }
+                None => info!("pt-sim no session for {} - dropping {}", report.owner, report.message.to_display()),
+            }
+        }
+    }
+
+    /// `copy`
+    ///
+    /// sends copy of each report to the drop-copy sessions of its owner
+    fn copy(&self, reports: &[SimReport]) {
+        for (_, client_comp_id, drop_copy) in self.drop_copies.lock().unwrap().iter() {
+            for report in reports.iter().filter(|r| r.owner == *client_comp_id) {
+                let _ = drop_copy.send(report.message.clone());
+            }
+        }
+    }
+}
+
+/// `Simulator`
+///
+/// running acceptor, stopped with `stop`
+pub struct Simulator {
+    ctx: Arc<SimContext>,
+    local_addr: SocketAddr,
+    server: JoinHandle<()>,
+}
+
+impl Simulator {
+    /// `start`
+    ///
+    /// # Errors
+    ///
+    /// function will return error if
+    /// - `bind_addr` cannot be bound
+    /// - TLS certificate or key cannot be loaded
+    pub fn start(config: SimConfig) -> Result<Simulator, Box<dyn Error>> {
+        let acceptor: Option<TlsAcceptor> = match &config.tls {
+            Some(tls) => Some(TlsAcceptor::new(Identity::from_pkcs8(&tls.cert_pem, &tls.key_pem)?)?),
+            None => None,
+        };
+        let listener: TcpListener = TcpListener::bind(&config.bind_addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr: SocketAddr = listener.local_addr()?;
+
+        let ctx = Arc::new(SimContext {
+            config,
+            control: Mutex::new(SimControl::default()),
+            exchange: Mutex::new(SimExchange::new()),
+            sessions: Mutex::new(HashMap::new()),
+            drop_copies: Mutex::new(Vec::new()),
+            running: AtomicBool::new(true),
+        });
+        let server = {
+            let ctx = Arc::clone(&ctx);
+            thread::Builder::new()
+                .name("pt-sim-acceptor".to_string())
+                .spawn(move || accept_loop(&listener, acceptor.map(Arc::new), &ctx))?
+        };
+        info!("pt-sim listening on {local_addr} (tls: {})", ctx.config.tls.is_some());
+        Ok(Simulator { ctx, local_addr, server })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// `command`
+    ///
+    /// queues fault for the sessions currently connected (or the next one to connect),
+    /// liquidity is added to the book immediately
+    pub fn command(&self, command: SimCommand) {
+        info!("pt-sim command: {command}");
+        if let SimCommand::Liquidity { symbol, side, quantity, price } = command {
+            let reports: Vec<SimReport> = self.ctx.exchange.lock().unwrap().add_liquidity(&symbol, side, quantity, price);
+            self.ctx.route(reports);
+            return;
+        }
+        let mut control = self.ctx.control.lock().unwrap();
+        match command {
+            SimCommand::RejectNextOrder(text) => control.reject_orders.push_back(text),
+            SimCommand::RejectNextMessage(text) => control.reject_messages.push_back(text),
+            SimCommand::Gap(count) => control.gap += count,
+            SimCommand::Disconnect => control.disconnect_epoch += 1,
+            SimCommand::Liquidity { .. } => {}
+        }
+    }
+
+    /// `stop`
+    ///
+    /// closes listener and all sessions
+    pub fn stop(self) {
+        self.ctx.running.store(false, Ordering::SeqCst);
+        if self.server.join().is_err() {
+            error!("pt-sim acceptor thread panicked");
+        }
+        info!("pt-sim stopped");
+    }
+}
+
+fn accept_loop(listener: &TcpListener, acceptor: Option<Arc<TlsAcceptor>>, ctx: &Arc<SimContext>) {
+    let mut connections: Vec<JoinHandle<()>> = Vec::new();
+    while ctx.running.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                info!("pt-sim accepted connection from {peer}");
+                let ctx = Arc::clone(ctx);
+                let acceptor = acceptor.clone();
+                let spawned =
+                    thread::Builder::new().name(format!("pt-sim-{peer}")).spawn(move || serve(stream, peer, acceptor.as_deref(), &ctx));
+                match spawned {
+                    Ok(handle) => connections.push(handle),
+                    Err(error) => error!("pt-sim failed to start session thread for {peer}: {error}"),
+                }
+            }
+            Err(ref error) if error.kind() == ErrorKind::WouldBlock => thread::sleep(ctx.config.poll_interval),
+            Err(error) => {
+                error!("pt-sim accept failed: {error}");
+                thread::sleep(ctx.config.poll_interval);
+            }
+        }
+        connections.retain(|handle| !handle.is_finished());
+    }
+    for handle in connections {
+        let _ = handle.join();
+    }
+}
+
+fn serve(stream: TcpStream, peer: SocketAddr, acceptor: Option<&TlsAcceptor>, ctx: &SimContext) {
+    let prepared = stream
+        .set_nonblocking(false)
+        .and_then(|()| stream.set_nodelay(true))
+        .and_then(|()| stream.set_read_timeout(Some(ctx.config.poll_interval)));
+    if let Err(error) = prepared {
+        error!("pt-sim failed to configure connection from {peer}: {error}");
+        return;
+    }
+    match acceptor {
+        None => connection::run(stream, peer, ctx),
+        Some(acceptor) => {
+            // handshake with a generous timeout, then fall back to the poll interval for reads
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+            match acceptor.accept(stream) {
+                Ok(tls_stream) => {
+                    let _ = tls_stream.get_ref().set_read_timeout(Some(ctx.config.poll_interval));
+                    connection::run(tls_stream, peer, ctx);
+                }
+                Err(error) => error!("pt-sim TLS handshake with {peer} failed: {error}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod sim_command_tests {
+
+    use super::SimCommand;
+    use crate::sim::matching::BookSide;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!("gap 3".parse(), Ok(SimCommand::Gap(3)));
+        assert_eq!("disconnect".parse(), Ok(SimCommand::Disconnect));
+        assert_eq!("reject-order too big".parse(), Ok(SimCommand::RejectNextOrder("too big".to_string())));
+        assert_eq!("reject".parse(), Ok(SimCommand::RejectNextMessage("Rejected by simulator".to_string())));
+        assert!("gap x".parse::<SimCommand>().is_err());
+        assert!("explode".parse::<SimCommand>().is_err());
+        assert_eq!(
+            "liquidity SOL-USD buy 10@388.5".parse(),
+            Ok(SimCommand::Liquidity { symbol: "SOL-USD".to_string(), side: BookSide::Buy, quantity: 10.0, price: 388.5 })
+        );
+        assert!("liquidity SOL-USD hold 10@388".parse::<SimCommand>().is_err());
+    }
+}
@@ -0,0 +1,25 @@
+// tests/kill_switch_signal.rs
+//
+// Kill switch SIGINT handling, in a test binary of its own so the raised signal
+// does not reach the handlers other tests register
+//
+
+use client_rust_fix::session::{KillSwitch, KillTrigger};
+use signal_hook::{consts::SIGINT, low_level::raise};
+
+#[test]
+fn test_first_sigint_after_flag_file_does_not_end_process() {
+    let path = std::env::temp_dir().join(format!("pt-kill-signal-{}", std::process::id()));
+    let flagged = KillSwitch::new().flag_file(&path);
+    std::fs::write(&path, "").unwrap();
+    assert!(flagged.is_tripped());
+    std::fs::remove_file(&path).unwrap();
+    let fresh = KillSwitch::new();
+    flagged.register_signals().unwrap();
+    fresh.register_signals().unwrap();
+
+    // n.b. only one SIGINT may be raised in this binary, a second one would end it
+    raise(SIGINT).unwrap();
+    assert_eq!(flagged.trigger(), Some(KillTrigger::FileFlag(path)));
+    assert_eq!(fresh.trigger(), Some(KillTrigger::Signal));
+}
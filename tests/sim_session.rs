@@ -0,0 +1,561 @@
+// tests/sim_session.rs
+//
+// Session reader/writer threads exercised against the local pt-sim acceptor
+//
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use client_rust_fix::{
+    fix::{Decimal, RawMessage},
+    instruments::{InstrumentQuery, InstrumentRegistry, PutOrCall},
+    orders::{MassStatusSnapshot, OrdStatus, OrderManager, ReportSource},
+    positions::PositionKeeper,
+    risk::{RiskGate, RiskLimits, RiskRejection},
+    session::{BatchOutcome, InboundEvent, KillSwitch, KillTrigger, OrderBatch, Session, SessionConfig, SessionError, ThrottleConfig},
+    sim::{default_instruments, SimCommand, SimConfig, Simulator},
+};
+use chrono::NaiveDate;
+use std::{
+    net::TcpStream,
+    sync::{mpsc::Receiver, Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const APIKEY: &str = "sim-test-key";
+
+fn jwt(sub: &str) -> String {
+    let exp: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 600;
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"ES256","typ":"JWT"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(format!(r#"{{"sub":"{sub}","exp":{exp}}}"#));
+    format!("{header}.{claims}.c2lnbmF0dXJl")
+}
+
+fn connect(simulator: &Simulator, sub: &str) -> Session {
+    let config = SessionConfig::new(APIKEY, "PT-OE");
+    let stream = TcpStream::connect(simulator.local_addr()).unwrap();
+    let session = Session::spawn(stream, config.clone(), Arc::new(Mutex::new(1))).unwrap();
+
+    let mut logon = config.admin_message("A");
+    logon.set(98, 0).set(108, 30).set(141, 'Y').set(554, jwt(sub));
+    session.outbound.send_raw(logon).unwrap();
+    session
+}
+
+fn next_app_event(inbound: &Receiver<InboundEvent>) -> InboundEvent {
+    loop {
+        match inbound.recv_timeout(Duration::from_secs(5)).expect("no event from simulator") {
+            InboundEvent::Heartbeat(_) => continue,
+            event => return event,
+        }
+    }
+}
+
+fn new_order(cl_ord_id: &str) -> RawMessage {
+    let mut order = RawMessage::new("D");
+    order
+        .set_header(34, 0)
+        .set(11, cl_ord_id)
+        .set(38, 2)
+        .set(40, 2)
+        .set(44, 388)
+        .set(54, 2)
+        .set(55, "SOL-USD")
+        .set(59, 1);
+    order
+}
+
+#[test]
+fn test_order_is_acknowledged_and_cancelled() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let session = connect(&simulator, APIKEY);
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+
+    session.outbound.send_raw(new_order("ORDER-1")).unwrap();
+    let InboundEvent::ExecutionReport(ack) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!(ack.get(11), Some("ORDER-1"));
+    assert_eq!(ack.get(39), Some("0"));
+
+    let mut cancel = RawMessage::new("F");
+    cancel.set_header(34, 0).set(11, "CANCEL-1").set(41, "ORDER-1").set(54, 2).set(55, "SOL-USD");
+    session.outbound.send_raw(cancel).unwrap();
+    let InboundEvent::ExecutionReport(cancelled) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!(cancelled.get(41), Some("ORDER-1"));
+    assert_eq!(cancelled.get(39), Some("4"));
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_orders_are_matched_against_house_liquidity() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let session = connect(&simulator, APIKEY);
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+
+    // taker - sell 2@388 crosses the house bid at 390 for 1, remainder rests at 388
+    simulator.command("liquidity SOL-USD buy 1@390".parse().unwrap());
+    session.outbound.send_raw(new_order("ORDER-1")).unwrap();
+    let InboundEvent::ExecutionReport(ack) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!(ack.get(39), Some("0"));
+    let InboundEvent::ExecutionReport(fill) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!(fill.get(39), Some("1"));
+    assert_eq!(fill.get(31), Some("390"));
+    assert_eq!(fill.get(32), Some("1"));
+
+    // maker - house order lifts the resting remainder, fill is routed to the session
+    simulator.command("liquidity SOL-USD buy 1@388".parse().unwrap());
+    let InboundEvent::ExecutionReport(fill) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!(fill.get(11), Some("ORDER-1"));
+    assert_eq!(fill.get(39), Some("2"));
+    assert_eq!(fill.get(31), Some("388"));
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_throttled_orders_are_spaced_and_cancel_goes_first() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let mut config = SessionConfig::new(APIKEY, "PT-OE");
+    config.throttle = ThrottleConfig { new: Some("10:1".parse().unwrap()), ..ThrottleConfig::default() };
+    let stream = TcpStream::connect(simulator.local_addr()).unwrap();
+    let session = Session::spawn(stream, config.clone(), Arc::new(Mutex::new(1))).unwrap();
+    let mut logon = config.admin_message("A");
+    logon.set(98, 0).set(108, 30).set(141, 'Y').set(554, jwt(APIKEY));
+    session.outbound.send_raw(logon).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+
+    let started = Instant::now();
+    for cl_ord_id in ["ORDER-1", "ORDER-2", "ORDER-3"] {
+        session.outbound.send_raw(new_order(cl_ord_id)).unwrap();
+    }
+    // cancel of the first order overtakes the orders still waiting for a token
+    let mut cancel = RawMessage::new("F");
+    cancel.set_header(34, 0).set(11, "CANCEL-1").set(41, "ORDER-1").set(54, 2).set(55, "SOL-USD");
+    session.outbound.send_raw(cancel).unwrap();
+    let mut reports: Vec<(String, String)> = Vec::new();
+    for _ in 0..4 {
+        let InboundEvent::ExecutionReport(report) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+        reports.push((report.get(11).unwrap_or_default().to_string(), report.get(39).unwrap_or_default().to_string()));
+    }
+    let expected: Vec<(String, String)> = [("ORDER-1", "0"), ("CANCEL-1", "4"), ("ORDER-2", "0"), ("ORDER-3", "0")]
+        .iter()
+        .map(|(c, s)| (c.to_string(), s.to_string()))
+        .collect();
+    assert_eq!(reports, expected);
+    assert!(started.elapsed() >= Duration::from_millis(200));
+
+    let metrics = session.outbound.throttle_metrics();
+    assert_eq!((metrics.new.sent, metrics.new.delayed, metrics.cancel.sent, metrics.queued), (3, 2, 1, 0));
+    assert!(metrics.new.max_queued >= Duration::from_millis(100));
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_order_and_cancel_round_trips_are_measured() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let session = connect(&simulator, APIKEY);
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+
+    let started = Instant::now();
+    session.outbound.send_raw(new_order("ORDER-1")).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::ExecutionReport(_)));
+    let mut cancel = RawMessage::new("F");
+    cancel.set_header(34, 0).set(11, "CANCEL-1").set(41, "ORDER-1").set(54, 2).set(55, "SOL-USD");
+    session.outbound.send_raw(cancel).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::ExecutionReport(_)));
+    let elapsed: Duration = started.elapsed();
+
+    let latency = session.outbound.latency_tracker();
+    let latency = latency.lock().unwrap();
+    let round_trips: Vec<(&str, &str, &str)> =
+        latency.round_trips().iter().map(|r| (r.cl_ord_id.as_str(), r.msg_type.as_str(), r.response_type.as_str())).collect();
+    assert_eq!(round_trips, [("ORDER-1", "D", "8"), ("CANCEL-1", "F", "8")]);
+    let (order, cancel) = (latency.stats("D").unwrap(), latency.stats("F").unwrap());
+    assert_eq!((order.count, cancel.count, latency.pending()), (1, 1, 0));
+    assert!(order.max > Duration::ZERO && order.max + cancel.max <= elapsed);
+    drop(latency);
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_kill_switch_cancels_open_orders_and_logs_out() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let orders = OrderManager::shared();
+    let mut config = SessionConfig::new(APIKEY, "PT-OE");
+    config.throttle = ThrottleConfig { new: Some("0.2:2".parse().unwrap()), ..ThrottleConfig::default() };
+    let stream = TcpStream::connect(simulator.local_addr()).unwrap();
+    let session = Session::spawn_tracked(stream, config.clone(), Arc::new(Mutex::new(1)), Arc::clone(&orders)).unwrap();
+    let mut logon = config.admin_message("A");
+    logon.set(98, 0).set(108, 30).set(141, 'Y').set(554, jwt(APIKEY));
+    session.outbound.send_raw(logon).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+    for cl_ord_id in ["ORDER-1", "ORDER-2"] {
+        orders.lock().unwrap().track_new(&new_order(cl_ord_id)).unwrap();
+        session.outbound.send_raw(new_order(cl_ord_id)).unwrap();
+        assert!(matches!(next_app_event(&session.inbound), InboundEvent::ExecutionReport(_)));
+    }
+    // the burst is used, ORDER-QUEUED waits behind the throttle when the switch trips and is never sent
+    orders.lock().unwrap().track_new(&new_order("ORDER-QUEUED")).unwrap();
+    session.outbound.send_raw(new_order("ORDER-QUEUED")).unwrap();
+
+    let kill_switch = KillSwitch::new();
+    session.outbound.set_kill_switch(kill_switch.clone());
+    let watch =
+        kill_switch.watch(session.outbound.clone(), config.clone(), Arc::clone(&orders), Duration::from_secs(5), Duration::from_millis(10));
+    assert!(kill_switch.trip(KillTrigger::Command("kill".to_string())));
+    assert_eq!(session.outbound.send_raw(new_order("ORDER-3")), Err(SessionError::KillSwitch(KillTrigger::Command("kill".to_string()))));
+    watch.join().unwrap();
+
+    assert_eq!(orders.lock().unwrap().open_orders().count(), 0);
+    assert_eq!(orders.lock().unwrap().get("ORDER-2").map(|order| order.ord_status), Some(OrdStatus::Canceled));
+    assert_eq!(orders.lock().unwrap().get("ORDER-QUEUED").map(|order| order.ord_status), Some(OrdStatus::Rejected));
+    loop {
+        match next_app_event(&session.inbound) {
+            InboundEvent::Logout(_) => break,
+            InboundEvent::ExecutionReport(report) => assert_eq!((report.get(11) != Some("ORDER-QUEUED"), report.get(39)), (true, Some("4"))),
+            InboundEvent::OrderMassCancelReport(report) => assert_eq!(report.get(533), Some("2")),
+            event => panic!("unexpected {event:?}"),
+        }
+    }
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_orders_working_before_reconnect_are_cancelled_on_logon() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let orders = OrderManager::shared();
+    let mut config = SessionConfig::new(APIKEY, "PT-OE");
+    config.cancel_on_reconnect = true;
+    config.reconnect_interval = Duration::from_millis(100);
+    let address = simulator.local_addr();
+    let logon_config = config.clone();
+    let connect = move || {
+        let mut logon = logon_config.admin_message("A");
+        logon.set(98, 0).set(108, 30).set(141, 'Y').set(554, jwt(APIKEY));
+        TcpStream::connect(address).map(|stream| (stream, logon))
+    };
+
+    // an order open in order state on the first Logon is not cancelled, that Logon does not follow a reconnect
+    orders.lock().unwrap().track_new(&new_order("NEVER-SENT")).unwrap();
+    let (stream, logon) = connect().unwrap();
+    let session = Session::spawn_reconnecting(stream, config, Arc::new(Mutex::new(1)), Arc::clone(&orders), connect).unwrap();
+    session.outbound.send_raw(logon).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+    assert!(session.inbound.recv_timeout(Duration::from_millis(300)).is_err());
+    orders.lock().unwrap().reject_local("NEVER-SENT", "not sent");
+
+    orders.lock().unwrap().track_new(&new_order("ORDER-1")).unwrap();
+    session.outbound.send_raw(new_order("ORDER-1")).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::ExecutionReport(_)));
+
+    // dropped without Logout, the session logs on again by itself and cancels what was working
+    simulator.command(SimCommand::Disconnect);
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+    let InboundEvent::ExecutionReport(report) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!((report.get(11), report.get(39)), (Some("ORDER-1"), Some("4")));
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::OrderMassCancelReport(_)));
+    assert_eq!(orders.lock().unwrap().open_orders().count(), 0);
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_batch_is_written_back_to_back_and_acks_collected_by_cl_ord_id() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let orders = OrderManager::shared();
+    let config = SessionConfig::new(APIKEY, "PT-OE");
+    let stream = TcpStream::connect(simulator.local_addr()).unwrap();
+    let seqnum = Arc::new(Mutex::new(1));
+    let session = Session::spawn_tracked(stream, config.clone(), Arc::clone(&seqnum), Arc::clone(&orders)).unwrap();
+    let mut logon = config.admin_message("A");
+    logon.set(98, 0).set(108, 30).set(141, 'Y').set(554, jwt(APIKEY));
+    session.outbound.send_raw(logon).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+
+    // GoodTillDate without ExpireTime, rejected by the simulator
+    let mut missing_expiry = new_order("ORDER-3");
+    missing_expiry.set(59, 6);
+    let mut untracked = new_order("");
+    untracked.set(11, "");
+    // ClOrdID already used earlier in the batch, neither sent nor allowed to reject ORDER-1
+    let duplicate = new_order("ORDER-1");
+    let batch = vec![new_order("ORDER-1"), new_order("ORDER-2"), missing_expiry, untracked, new_order("ORDER-4"), duplicate];
+    let first: u32 = *seqnum.lock().unwrap();
+    let batch = OrderBatch::submit(&session.outbound, &orders, batch);
+    assert_eq!(batch.pending(), 4);
+    let results = batch.wait(Duration::from_secs(5));
+
+    let outcomes: Vec<(&str, &BatchOutcome)> = results.iter().map(|result| (result.cl_ord_id.as_str(), &result.outcome)).collect();
+    assert!(matches!(outcomes[0], ("ORDER-1", BatchOutcome::Accepted { ord_status: OrdStatus::New, .. })));
+    assert!(matches!(outcomes[1], ("ORDER-2", BatchOutcome::Accepted { ord_status: OrdStatus::New, .. })));
+    assert!(matches!(outcomes[2], ("ORDER-3", BatchOutcome::Rejected(_))));
+    assert!(matches!(outcomes[3], ("", BatchOutcome::NotSent(_))));
+    assert!(matches!(outcomes[4], ("ORDER-4", BatchOutcome::Accepted { ord_status: OrdStatus::New, .. })));
+    assert!(matches!(outcomes[5], ("ORDER-1", BatchOutcome::NotSent(_))));
+    assert_eq!(orders.lock().unwrap().get("ORDER-1").map(|order| order.ord_status), Some(OrdStatus::New));
+    // four orders written with consecutive MsgSeqNums, nothing else in between
+    assert_eq!(*seqnum.lock().unwrap(), first + 4);
+    assert_eq!(orders.lock().unwrap().open_orders().count(), 3);
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_risk_gate_stops_order_before_it_is_sent() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let session = connect(&simulator, APIKEY);
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+    let gate = RiskGate::new(RiskLimits::new().price_band_pct(0.1), OrderManager::shared(), PositionKeeper::new().shared()).shared();
+    session.outbound.set_risk_gate(Arc::clone(&gate));
+
+    // no reference price yet, the fill at 390 received by the reader becomes the reference
+    simulator.command("liquidity SOL-USD buy 2@390".parse().unwrap());
+    session.outbound.send_raw(new_order("ORDER-1")).unwrap();
+    let InboundEvent::ExecutionReport(ack) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!(ack.get(39), Some("0"));
+    let InboundEvent::ExecutionReport(fill) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!(fill.get(39), Some("2"));
+    assert_eq!(gate.lock().unwrap().reference_price("SOL-USD"), Some(390.0));
+
+    let result = session.outbound.send_raw(new_order("ORDER-2"));
+    assert!(matches!(result, Err(SessionError::RiskRejected(RiskRejection::PriceBand { .. }))));
+    let mut status = RawMessage::new("H");
+    status.set_header(34, 0).set(11, "ORDER-2").set(54, 2).set(55, "SOL-USD");
+    session.outbound.send_raw(status).unwrap();
+    let InboundEvent::ExecutionReport(unknown) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!((unknown.get(11), unknown.get(39)), (Some("ORDER-2"), Some("8")));
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_multileg_order_fills_every_leg() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    simulator.command("liquidity SOL-USD sell 1@390".parse().unwrap());
+    simulator.command("liquidity BTC-USD buy 1@60000".parse().unwrap());
+    let orders = OrderManager::shared();
+    let config = SessionConfig::new(APIKEY, "PT-OE");
+    let stream = TcpStream::connect(simulator.local_addr()).unwrap();
+    let session = Session::spawn_tracked(stream, config.clone(), Arc::new(Mutex::new(1)), Arc::clone(&orders)).unwrap();
+    let mut logon = config.admin_message("A");
+    logon.set(98, 0).set(108, 30).set(141, 'Y').set(554, jwt(APIKEY));
+    session.outbound.send_raw(logon).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+
+    let mut package = RawMessage::new("AB");
+    package.set_header(34, 0).set(11, "PACKAGE-1").set(38, 1).set(40, 2).set(44, -200).set(54, 1).set(55, "[N/A]").set(59, 1).set(555, 2);
+    package.push(600, "SOL-USD").push(624, 1).push(623, 1).push(600, "BTC-USD").push(624, 2).push(623, 0.01);
+    orders.lock().unwrap().track_new(&package).unwrap();
+    session.outbound.send_raw(package).unwrap();
+    // New, one fill per leg, then the package fill
+    for _ in 0..4 {
+        assert!(matches!(next_app_event(&session.inbound), InboundEvent::ExecutionReport(_)));
+    }
+
+    let orders = orders.lock().unwrap();
+    let order = orders.get("PACKAGE-1").unwrap();
+    assert_eq!((order.ord_status, order.avg_px), (OrdStatus::Filled, Decimal::from(-210)));
+    let legs: Vec<(&str, String, String)> =
+        order.legs.iter().map(|leg| (leg.symbol.as_str(), leg.cum_qty.to_string(), leg.avg_px.to_string())).collect();
+    assert_eq!(legs, vec![("SOL-USD", "1".to_string(), "390".to_string()), ("BTC-USD", "0.01".to_string(), "60000".to_string())]);
+    drop(orders);
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_drop_copy_reports_merge_into_order_state() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let orders = OrderManager::shared();
+    let logon = |config: &SessionConfig| {
+        let stream = TcpStream::connect(simulator.local_addr()).unwrap();
+        let session = Session::spawn_tracked(stream, config.clone(), Arc::new(Mutex::new(1)), Arc::clone(&orders)).unwrap();
+        let mut logon = config.admin_message("A");
+        logon.set(98, 0).set(108, 30).set(141, 'Y').set(554, jwt(APIKEY));
+        session.outbound.send_raw(logon).unwrap();
+        assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+        session
+    };
+    let order_entry = logon(&SessionConfig::new(APIKEY, "PT-OE"));
+    let drop_copy = logon(&SessionConfig::drop_copy(APIKEY, "PT-DC"));
+
+    // order is tracked before it is sent, so reports arriving first on drop-copy are not taken as external
+    simulator.command("liquidity SOL-USD buy 2@390".parse().unwrap());
+    orders.lock().unwrap().track_new(&new_order("ORDER-1")).unwrap();
+    order_entry.outbound.send_raw(new_order("ORDER-1")).unwrap();
+    for ord_status in ["0", "2"] {
+        let InboundEvent::ExecutionReport(report) = next_app_event(&drop_copy.inbound) else { panic!("expected ExecutionReport") };
+        assert_eq!(report.get(49), Some("PT-DC"));
+        assert_eq!(report.get(39), Some(ord_status));
+    }
+
+    // same ExecID seen on both sessions is applied once
+    let orders = orders.lock().unwrap();
+    assert_eq!(orders.len(), 1);
+    let order = orders.get("ORDER-1").unwrap();
+    assert_eq!(order.ord_status, OrdStatus::Filled);
+    assert_eq!(order.cum_qty, Decimal::from(2));
+    assert!(!order.external);
+    assert_ne!(order.source, ReportSource::Local);
+    drop(orders);
+
+    drop_copy.logout();
+    order_entry.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_fills_from_both_sessions_build_one_position() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let positions = PositionKeeper::new().shared();
+    let order_entry = connect(&simulator, APIKEY);
+    let drop_copy = {
+        let config = SessionConfig::drop_copy(APIKEY, "PT-DC");
+        let stream = TcpStream::connect(simulator.local_addr()).unwrap();
+        let session = Session::spawn(stream, config.clone(), Arc::new(Mutex::new(1))).unwrap();
+        let mut logon = config.admin_message("A");
+        logon.set(98, 0).set(108, 30).set(141, 'Y').set(554, jwt(APIKEY));
+        session.outbound.send_raw(logon).unwrap();
+        session
+    };
+    for session in [&order_entry, &drop_copy] {
+        assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+        session.outbound.set_position_keeper(Arc::clone(&positions));
+    }
+
+    // sell 2@388 fills against the house bid at 390, the fill is reported on both sessions
+    simulator.command("liquidity SOL-USD buy 2@390".parse().unwrap());
+    order_entry.outbound.send_raw(new_order("ORDER-1")).unwrap();
+    for session in [&order_entry, &drop_copy] {
+        for ord_status in ["0", "2"] {
+            let InboundEvent::ExecutionReport(report) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+            assert_eq!(report.get(39), Some(ord_status));
+        }
+    }
+
+    let mut positions = positions.lock().unwrap();
+    let position = positions.position("", "SOL-USD").cloned().unwrap();
+    assert_eq!((position.quantity, position.avg_px, position.realized_pnl), (-2.0, 390.0, 0.0));
+    positions.set_mark_price("SOL-USD", 385.0);
+    assert_eq!(positions.unrealized_pnl(&position), Some(10.0));
+    drop(positions);
+
+    drop_copy.logout();
+    order_entry.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_mass_status_after_reconnect_restores_order_state() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let session = connect(&simulator, APIKEY);
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+    for cl_ord_id in ["ORDER-1", "ORDER-2"] {
+        session.outbound.send_raw(new_order(cl_ord_id)).unwrap();
+        assert!(matches!(next_app_event(&session.inbound), InboundEvent::ExecutionReport(_)));
+    }
+    session.logout();
+
+    // client restarts with empty order state, only an order it never got to send is known
+    let orders = OrderManager::shared();
+    orders.lock().unwrap().track_new(&new_order("ORDER-3")).unwrap();
+    let config = SessionConfig::new(APIKEY, "PT-OE");
+    let stream = TcpStream::connect(simulator.local_addr()).unwrap();
+    let session = Session::spawn_tracked(stream, config.clone(), Arc::new(Mutex::new(1)), Arc::clone(&orders)).unwrap();
+    let mut logon = config.admin_message("A");
+    logon.set(98, 0).set(108, 30).set(141, 'Y').set(554, jwt(APIKEY));
+    session.outbound.send_raw(logon).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+
+    let mut request = RawMessage::new("AF");
+    request.set_header(34, 0).set(584, "MS-1").set(585, 7);
+    session.outbound.send_raw(request).unwrap();
+    let mut snapshot = MassStatusSnapshot::new("MS-1", None);
+    while !snapshot.is_complete() {
+        let InboundEvent::ExecutionReport(report) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+        assert!(snapshot.accept(&report));
+    }
+    assert_eq!(snapshot.len(), 2);
+
+    let orders = orders.lock().unwrap();
+    assert_eq!(orders.get("ORDER-1").map(|order| order.ord_status), Some(OrdStatus::New));
+    assert_eq!(orders.get("ORDER-2").map(|order| order.leaves_qty), Some(Decimal::from(2)));
+    let missing: Vec<&str> = orders.reconcile(&snapshot).map(|order| order.cl_ord_id.as_str()).collect();
+    assert_eq!(missing, vec!["ORDER-3"]);
+    drop(orders);
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_security_list_feeds_instrument_catalogue() {
+    // Wednesday, weekly options expire on Friday 2024-06-28
+    let today = NaiveDate::from_ymd_opt(2024, 6, 26).unwrap();
+    let mut config = SimConfig::new("127.0.0.1:0");
+    config.instruments = default_instruments(today);
+    let simulator = Simulator::start(config).unwrap();
+    let session = connect(&simulator, APIKEY);
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logon(_)));
+
+    let mut request = RawMessage::new("x");
+    request.set_header(34, 0).set(320, "SL-1").set(559, 1).set(167, "OPT");
+    session.outbound.send_raw(request).unwrap();
+    let InboundEvent::Other(list) = next_app_event(&session.inbound) else { panic!("expected SecurityList") };
+    assert_eq!((list.msg_type(), list.get(320), list.get(560), list.get(893)), ("y", Some("SL-1"), Some("0"), Some("Y")));
+
+    let mut registry = InstrumentRegistry::new();
+    assert_eq!(registry.apply_security_list(&list), 4);
+    let query: InstrumentQuery = "underlying=SOL type=option expires=week".parse().unwrap();
+    let symbols: Vec<&str> = registry.query(&query, today).iter().map(|instrument| instrument.symbol.as_str()).collect();
+    assert_eq!(symbols, vec!["SOL-20240628-150C", "SOL-20240628-150P"]);
+    let call = registry.get("SOL-20240628-150C").unwrap();
+    assert_eq!((call.strike, call.put_or_call, call.tick_size), (Some(150.0), Some(PutOrCall::Call), Decimal::new(1, 2)));
+
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_logon_with_wrong_subject_is_logged_out() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let session = connect(&simulator, "someone-else");
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Logout(_)));
+    session.logout();
+    simulator.stop();
+}
+
+#[test]
+fn test_injected_reject_gap_and_disconnect() {
+    let simulator = Simulator::start(SimConfig::new("127.0.0.1:0")).unwrap();
+    let session = connect(&simulator, APIKEY);
+    let InboundEvent::Logon(logon) = next_app_event(&session.inbound) else { panic!("expected Logon") };
+    let logon_seqnum: u32 = logon.get(34).unwrap().parse().unwrap();
+
+    simulator.command(SimCommand::RejectNextOrder("too big".to_string()));
+    simulator.command(SimCommand::Gap(5));
+    session.outbound.send_raw(new_order("ORDER-2")).unwrap();
+    let InboundEvent::ExecutionReport(reject) = next_app_event(&session.inbound) else { panic!("expected ExecutionReport") };
+    assert_eq!(reject.get(39), Some("8"));
+    assert_eq!(reject.get(58), Some("too big"));
+    assert_eq!(reject.get(34).unwrap().parse::<u32>().unwrap(), logon_seqnum + 6);
+
+    simulator.command(SimCommand::RejectNextMessage("throttled".to_string()));
+    session.outbound.send_raw(new_order("ORDER-3")).unwrap();
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Reject(_)));
+
+    simulator.command(SimCommand::Disconnect);
+    assert!(matches!(next_app_event(&session.inbound), InboundEvent::Disconnected(_)));
+    session.logout();
+    simulator.stop();
+}